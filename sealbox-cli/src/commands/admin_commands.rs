@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    AdminCommands,
+    config::Config,
+    http::{self, SendWithRetries},
+    output::OutputManager,
+    server_error::server_error,
+};
+
+/// Header carrying the TOTP code for destructive operations
+pub(crate) const TOTP_HEADER: &str = "X-TOTP";
+
+pub async fn handle_command(command: AdminCommands, config: &Config) -> Result<()> {
+    let output = OutputManager::new(config.output.format.clone())
+        .with_color(!config.output.no_color)
+        .with_quiet(config.output.quiet);
+
+    match command {
+        AdminCommands::Backup { out, force } => backup_database(config, &output, out, force).await,
+        AdminCommands::CleanupExpired { totp } => cleanup_expired(config, &output, totp).await,
+        AdminCommands::Scan => scan(config, &output).await,
+    }
+}
+
+async fn backup_database(
+    config: &Config,
+    output: &OutputManager,
+    out: PathBuf,
+    force: bool,
+) -> Result<()> {
+    config
+        .validate()
+        .context("Configuration validation failed")?;
+
+    if out.exists() && !force {
+        anyhow::bail!(
+            "Backup file already exists: {}. Use --force to overwrite",
+            out.display()
+        );
+    }
+
+    output.print_info("Downloading database backup from server...");
+
+    let response = http::client(config)
+        .get(format!("{}/v1/admin/backup", config.server.url))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    let data = response
+        .bytes()
+        .await
+        .context("Failed to download backup")?;
+
+    fs::write(&out, &data)
+        .with_context(|| format!("Failed to write backup file: {}", out.display()))?;
+
+    // The backup holds every ciphertext and wrapped data key, so restrict it to its owner
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&out)?.permissions();
+        perms.set_mode(0o600);
+        fs::set_permissions(&out, perms)?;
+    }
+
+    output.print_success(&format!(
+        "Backup saved to: {} ({} bytes)",
+        out.display(),
+        data.len()
+    ));
+    Ok(())
+}
+
+async fn cleanup_expired(
+    config: &Config,
+    output: &OutputManager,
+    totp: Option<String>,
+) -> Result<()> {
+    config
+        .validate()
+        .context("Configuration validation failed")?;
+
+    let mut request = http::client(config)
+        .delete(format!("{}/v1/admin/cleanup-expired", config.server.url))
+        .bearer_auth(&config.server.token);
+    if let Some(totp) = totp {
+        request = request.header(TOTP_HEADER, totp);
+    }
+    let response = request
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
+
+    output.print_success(&format!(
+        "Deleted {} expired secret versions",
+        body["deleted_count"].as_u64().unwrap_or(0)
+    ));
+    Ok(())
+}
+
+/// Report corrupt secret versions, failing if there are any so scripts can alert on it
+async fn scan(config: &Config, output: &OutputManager) -> Result<()> {
+    config
+        .validate()
+        .context("Configuration validation failed")?;
+
+    output.print_info("Scanning stored secrets...");
+    let response = http::client(config)
+        .get(format!("{}/v1/admin/scan", config.server.url))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
+
+    let scanned = body["scanned"].as_u64().unwrap_or(0);
+    let corrupt = body["corrupt"].as_array().cloned().unwrap_or_default();
+    if corrupt.is_empty() {
+        output.print_success(&format!("Scanned {scanned} secret versions, none corrupt"));
+        return Ok(());
+    }
+
+    output.print_value(&serde_json::Value::Array(corrupt.clone()))?;
+    anyhow::bail!("{} of {scanned} secret versions are corrupt", corrupt.len())
+}
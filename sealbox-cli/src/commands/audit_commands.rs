@@ -0,0 +1,235 @@
+use anyhow::{Context, Result};
+use reqwest::{Client, Url};
+use sealbox_server::repo::AuditEntry;
+use serde::Deserialize;
+use std::{io::Write, path::PathBuf};
+
+use crate::{
+    AuditCommands,
+    config::Config,
+    http::{self, SendWithRetries},
+    output::OutputManager,
+    server_error::server_error,
+};
+
+/// Entries fetched per request, the largest page the server allows
+const PAGE_SIZE: u32 = 1000;
+
+pub async fn handle_command(command: AuditCommands, config: &Config) -> Result<()> {
+    let output = OutputManager::new(config.output.format.clone())
+        .with_color(!config.output.no_color)
+        .with_quiet(config.output.quiet);
+
+    match command {
+        AuditCommands::Export {
+            file,
+            format,
+            since,
+            until,
+            action,
+            key,
+        } => {
+            let filter = ExportFilter {
+                since,
+                until,
+                action,
+                key,
+            };
+            export_audit(config, &output, file, &format, &filter).await
+        }
+    }
+}
+
+/// Filters passed through to `GET /v1/admin/audit`
+struct ExportFilter {
+    since: Option<i64>,
+    until: Option<i64>,
+    action: Option<String>,
+    key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditPage {
+    entries: Vec<AuditEntry>,
+    next_cursor: Option<i64>,
+}
+
+async fn export_audit(
+    config: &Config,
+    output: &OutputManager,
+    file: Option<PathBuf>,
+    format: &str,
+    filter: &ExportFilter,
+) -> Result<()> {
+    config
+        .validate()
+        .context("Configuration validation failed")?;
+    // Fail on a bad format before paging through the whole log
+    render_audit(format, &[])?;
+
+    let client = http::client(config);
+    let mut entries = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = fetch_audit_page(&client, config, filter, cursor).await?;
+        entries.extend(page.entries);
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+    // The server pages newest first; exports read oldest first
+    entries.reverse();
+
+    let rendered = render_audit(format, &entries)?;
+    match file {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write file: {}", path.display()))?;
+            output.print_success(&format!(
+                "Exported {} audit entries to: {}",
+                entries.len(),
+                path.display()
+            ));
+        }
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(rendered.as_bytes())
+                .and_then(|_| stdout.flush())
+                .context("Failed to write audit entries to stdout")?;
+        }
+    }
+    Ok(())
+}
+
+async fn fetch_audit_page(
+    client: &Client,
+    config: &Config,
+    filter: &ExportFilter,
+    cursor: Option<i64>,
+) -> Result<AuditPage> {
+    let mut url = Url::parse(&format!("{}/v1/admin/audit", config.server.url))
+        .context("Invalid server URL")?;
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("limit", &PAGE_SIZE.to_string());
+        if let Some(since) = filter.since {
+            query.append_pair("since", &since.to_string());
+        }
+        if let Some(until) = filter.until {
+            query.append_pair("until", &until.to_string());
+        }
+        if let Some(action) = &filter.action {
+            query.append_pair("action", action);
+        }
+        if let Some(key) = &filter.key {
+            query.append_pair("key", key);
+        }
+        if let Some(cursor) = cursor {
+            query.append_pair("cursor", &cursor.to_string());
+        }
+    }
+
+    let response = client
+        .get(url)
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    response
+        .json()
+        .await
+        .context("Failed to parse server response")
+}
+
+/// Render audit entries as `csv` (with a header row) or a `json` array
+fn render_audit(format: &str, entries: &[AuditEntry]) -> Result<String> {
+    match format {
+        "csv" => {
+            let mut csv = String::from(
+                "id,timestamp,request_id,action,target_key,target_version,status,user_agent\n",
+            );
+            for entry in entries {
+                let fields = [
+                    entry.id.to_string(),
+                    entry.timestamp.to_string(),
+                    entry.request_id.clone().unwrap_or_default(),
+                    entry.action.clone(),
+                    entry.target_key.clone().unwrap_or_default(),
+                    entry
+                        .target_version
+                        .map(|v| v.to_string())
+                        .unwrap_or_default(),
+                    entry.status.to_string(),
+                    entry.user_agent.clone().unwrap_or_default(),
+                ];
+                let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            Ok(csv)
+        }
+        "json" => Ok(serde_json::to_string_pretty(entries)? + "\n"),
+        _ => anyhow::bail!("Unsupported audit export format: {format} (use csv or json)"),
+    }
+}
+
+/// Quote a CSV field (RFC 4180) if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry(target_key: Option<&str>) -> AuditEntry {
+        AuditEntry {
+            id: 7,
+            timestamp: 1_703_876_543,
+            request_id: Some("req-1".to_string()),
+            action: "secret.get".to_string(),
+            target_key: target_key.map(str::to_string),
+            target_version: None,
+            status: 200,
+            user_agent: Some("sealbox-cli/0.1.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_render_audit_csv() {
+        let csv = render_audit(
+            "csv",
+            &[sample_entry(Some("db,\"prod\"")), sample_entry(None)],
+        )
+        .unwrap();
+        assert_eq!(
+            csv,
+            "id,timestamp,request_id,action,target_key,target_version,status,user_agent\n\
+             7,1703876543,req-1,secret.get,\"db,\"\"prod\"\"\",,200,sealbox-cli/0.1.0\n\
+             7,1703876543,req-1,secret.get,,,200,sealbox-cli/0.1.0\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render_audit("json", &[sample_entry(None)]).unwrap()).unwrap();
+        assert_eq!(json[0]["action"], "secret.get");
+
+        assert!(render_audit("xml", &[]).is_err());
+    }
+}
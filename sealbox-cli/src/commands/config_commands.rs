@@ -1,6 +1,13 @@
-use crate::{ConfigCommands, config::Config, output::OutputManager};
+use crate::{
+    ConfigCommands,
+    commands::key_commands::{list_server_keys_internal, load_key_pair},
+    config::{Config, DEFAULT_PROFILE},
+    http::{self, SendWithRetries},
+    output::OutputManager,
+};
 use anyhow::{Context, Result};
-use serde_json::json;
+use sealbox_server::repo::MasterKeyStatus;
+use serde_json::{Value, json};
 
 struct InitOptions {
     url: Option<String>,
@@ -12,7 +19,9 @@ struct InitOptions {
 }
 
 pub async fn handle_command(command: ConfigCommands, config: &mut Config) -> Result<()> {
-    let output = OutputManager::new(config.output.format.clone());
+    let output = OutputManager::new(config.output.format.clone())
+        .with_color(!config.output.no_color)
+        .with_quiet(config.output.quiet);
 
     match command {
         ConfigCommands::Show => show_config(config, &output).await,
@@ -39,18 +48,25 @@ pub async fn handle_command(command: ConfigCommands, config: &mut Config) -> Res
             )
             .await
         }
+        ConfigCommands::Use { name, create } => use_profile(config, &output, &name, create).await,
+        ConfigCommands::ListProfiles => list_profiles(config, &output).await,
+        ConfigCommands::Validate => validate_config(config, &output).await,
     }
 }
 
 async fn show_config(config: &Config, output: &OutputManager) -> Result<()> {
     let config_value = json!({
+        "profile": config.active_profile_name(),
         "server": {
             "url": config.server.url,
-            "token": if config.server.token.is_empty() { "<not set>" } else { "<configured>" }
+            "token": if config.server.token.is_empty() { "<not set>" } else { "<configured>" },
+            "timeout": config.server.timeout,
+            "retries": config.server.retries
         },
         "keys": {
             "public_key_path": config.keys.public_key_path,
-            "private_key_path": config.keys.private_key_path
+            "private_key_path": config.keys.private_key_path,
+            "client_encrypt": config.keys.client_encrypt
         },
         "output": {
             "format": config.output.format
@@ -77,6 +93,22 @@ async fn set_config(
             config.server.token = value.to_string();
             output.print_success("Authentication token configured");
         }
+        "server.timeout" => {
+            config.server.timeout = value
+                .parse()
+                .ok()
+                .filter(|timeout| *timeout > 0)
+                .with_context(|| {
+                    format!("Invalid timeout: {value}. Use a whole number of seconds, at least 1")
+                })?;
+            output.print_success(&format!("Server timeout set to: {value}s"));
+        }
+        "server.retries" => {
+            config.server.retries = value.parse().with_context(|| {
+                format!("Invalid retry count: {value}. Use a whole number, 0 to disable retries")
+            })?;
+            output.print_success(&format!("Server retries set to: {value}"));
+        }
         "keys.public_key_path" => {
             config.keys.public_key_path = value.into();
             output.print_success(&format!("Public key path set to: {value}"));
@@ -85,6 +117,12 @@ async fn set_config(
             config.keys.private_key_path = value.into();
             output.print_success(&format!("Private key path set to: {value}"));
         }
+        "keys.client_encrypt" => {
+            config.keys.client_encrypt = value
+                .parse()
+                .with_context(|| format!("Invalid boolean: {value}. Use true or false"))?;
+            output.print_success(&format!("Client-side encryption set to: {value}"));
+        }
         "output.format" => match value.to_lowercase().as_str() {
             "json" => {
                 config.output.format = crate::config::OutputFormat::Json;
@@ -107,7 +145,7 @@ async fn set_config(
         },
         _ => {
             anyhow::bail!(
-                "Unknown configuration key: {}. Supported keys:\n  - server.url\n  - server.token\n  - keys.public_key_path\n  - keys.private_key_path\n  - output.format",
+                "Unknown configuration key: {}. Supported keys:\n  - server.url\n  - server.token\n  - server.timeout\n  - server.retries\n  - keys.public_key_path\n  - keys.private_key_path\n  - keys.client_encrypt\n  - output.format",
                 key
             );
         }
@@ -117,6 +155,41 @@ async fn set_config(
     Ok(())
 }
 
+async fn use_profile(
+    config: &mut Config,
+    output: &OutputManager,
+    name: &str,
+    create: bool,
+) -> Result<()> {
+    config.switch_profile(name, create)?;
+    config.current_profile = (name != DEFAULT_PROFILE).then(|| name.to_string());
+    config.save().context("Failed to save configuration")?;
+
+    output.print_success(&format!("Now using profile: {name}"));
+    if std::env::var("SEALBOX_PROFILE").is_ok_and(|env| !env.is_empty() && env != name) {
+        output.print_warning("SEALBOX_PROFILE is set and takes precedence over this choice");
+    }
+    Ok(())
+}
+
+async fn list_profiles(config: &Config, output: &OutputManager) -> Result<()> {
+    let active = config.active_profile_name();
+    let profiles: Vec<_> = config
+        .profile_names()
+        .into_iter()
+        .map(|name| {
+            json!({
+                "name": name,
+                "url": config.profile_url(name),
+                "active": name == active,
+            })
+        })
+        .collect();
+
+    output.print_value(&json!(profiles))?;
+    Ok(())
+}
+
 async fn init_config(
     config: &mut Config,
     output: &OutputManager,
@@ -212,6 +285,80 @@ async fn init_config(
     Ok(())
 }
 
+/// Actively check the configuration against the server, reporting every check
+/// and failing if any of them did
+async fn validate_config(config: &Config, output: &OutputManager) -> Result<()> {
+    let mut checks = vec![("configuration", config.validate())];
+
+    let reachable = http::client(config)
+        .get(format!("{}/healthz/ready", config.server.url))
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")
+        .and_then(|response| {
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else {
+                anyhow::bail!("Server is not ready (status code: {status})")
+            }
+        });
+    checks.push(("server reachable", reachable));
+
+    // Listing master keys needs a valid token, and tells us which keys the server knows
+    let server_keys = list_server_keys_internal(config).await;
+    checks.push((
+        "token authenticates",
+        server_keys
+            .as_ref()
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("{e}")),
+    ));
+
+    let key_pair = load_key_pair(
+        &config.keys.public_key_path.display().to_string(),
+        &config.keys.private_key_path.display().to_string(),
+    );
+    checks.push((
+        "local key pair",
+        key_pair
+            .as_ref()
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("{e:#}")),
+    ));
+
+    // The server never returns public keys, so only the presence of an active key can be checked
+    let active_key = match &server_keys {
+        Ok(keys) if keys.iter().any(|key| key.status == MasterKeyStatus::Active) => Ok(()),
+        Ok(_) => Err(anyhow::anyhow!(
+            "The server has no active master key; run `sealbox-cli key register`"
+        )),
+        Err(_) => Err(anyhow::anyhow!("Skipped: needs the server key list")),
+    };
+    checks.push(("active master key", active_key));
+
+    let (report, failed) = validation_report(&checks);
+    output.print_value(&report)?;
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} configuration checks failed", checks.len());
+    }
+    output.print_success("All configuration checks passed");
+    Ok(())
+}
+
+/// Per-check pass/fail report and the number of failed checks
+fn validation_report(checks: &[(&str, Result<()>)]) -> (Value, usize) {
+    let items: Vec<Value> = checks
+        .iter()
+        .map(|(name, result)| match result {
+            Ok(()) => json!({ "check": name, "passed": true }),
+            Err(e) => json!({ "check": name, "passed": false, "error": format!("{e:#}") }),
+        })
+        .collect();
+    let failed = checks.iter().filter(|(_, result)| result.is_err()).count();
+    (json!({ "checks": items }), failed)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -296,6 +443,8 @@ mod tests {
             let test_cases = vec![
                 ("server.url", "https://example.com"),
                 ("server.token", "test-token"),
+                ("server.timeout", "120"),
+                ("server.retries", "5"),
                 ("keys.public_key_path", "/path/to/public.pem"),
                 ("keys.private_key_path", "/path/to/private.pem"),
                 ("output.format", "json"),
@@ -310,6 +459,8 @@ mod tests {
                 match key {
                     "server.url" => assert_eq!(test_config.server.url, value),
                     "server.token" => assert_eq!(test_config.server.token, value),
+                    "server.timeout" => assert_eq!(test_config.server.timeout, 120),
+                    "server.retries" => assert_eq!(test_config.server.retries, 5),
                     "keys.public_key_path" => {
                         assert_eq!(test_config.keys.public_key_path, PathBuf::from(value))
                     }
@@ -324,4 +475,31 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn test_validation_report_counts_failures() {
+        let checks = vec![
+            ("configuration", Ok(())),
+            (
+                "server reachable",
+                Err(anyhow::anyhow!("connection refused")),
+            ),
+        ];
+
+        let (report, failed) = validation_report(&checks);
+        assert_eq!(failed, 1);
+        assert_eq!(report["checks"][0]["passed"], json!(true));
+        assert_eq!(report["checks"][1]["passed"], json!(false));
+        assert_eq!(report["checks"][1]["error"], json!("connection refused"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_config_fails_when_server_unreachable() {
+        let mut config = Config::default();
+        config.server.url = "http://127.0.0.1:1".to_string();
+        config.server.token = "test-token".to_string();
+        let output = OutputManager::new(OutputFormat::Json);
+
+        assert!(validate_config(&config, &output).await.is_err());
+    }
 }
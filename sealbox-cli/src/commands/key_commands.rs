@@ -1,37 +1,103 @@
 use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use reqwest::Client;
-use rsa::pkcs1::DecodeRsaPublicKey;
+use sealbox_server::crypto::{
+    key_file,
+    master_key::{KeyAlgorithm, PrivateMasterKey, PublicMasterKey},
+};
+use sealbox_server::repo::{MasterKey, MasterKeyStatus, SecretInfo};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use std::{fs, path::Path};
+use std::{fs, path::Path, str::FromStr};
 use uuid::Uuid;
 
-use crate::{KeyCommands, config::Config, output::OutputManager};
+use crate::{
+    KeyCommands,
+    commands::secret_commands::decrypt_secret_response,
+    config::Config,
+    http::{self, SendWithRetries},
+    output::OutputManager,
+    server_error::server_error,
+    server_info::server_info,
+};
 
 pub async fn handle_command(command: KeyCommands, config: &Config) -> Result<()> {
-    let output = OutputManager::new(config.output.format.clone());
+    let output = OutputManager::new(config.output.format.clone())
+        .with_color(!config.output.no_color)
+        .with_quiet(config.output.quiet);
 
     match command {
         KeyCommands::Generate {
             public_key_path,
             private_key_path,
+            algorithm,
+            bits,
             force,
-        } => generate_keys(config, &output, public_key_path, private_key_path, force).await,
-        KeyCommands::Register => register_key(config, &output).await,
-        KeyCommands::List => list_keys(config, &output).await,
+            passphrase,
+        } => {
+            generate_keys(
+                config,
+                &output,
+                public_key_path,
+                private_key_path,
+                algorithm.into(),
+                bits,
+                force,
+                passphrase,
+            )
+            .await
+        }
+        KeyCommands::Register { default_ttl } => register_key(config, &output, default_ttl).await,
+        KeyCommands::List {
+            status,
+            description_contains,
+            limit,
+            offset,
+        } => {
+            let filter = ListFilter {
+                status: status.map(Into::into),
+                description_contains,
+                limit,
+                offset,
+            };
+            list_keys(config, &output, &filter).await
+        }
+        KeyCommands::Stale { days } => list_stale_keys(config, &output, days).await,
         KeyCommands::Rotate {
             new_key_id,
             old_key_id,
-        } => rotate_keys(config, &output, new_key_id, old_key_id).await,
+            new_public_key,
+            new_private_key,
+            dry_run,
+        } => {
+            let new_key_files = new_public_key.zip(new_private_key);
+            rotate_keys(
+                config,
+                &output,
+                new_key_id,
+                old_key_id,
+                new_key_files,
+                dry_run,
+            )
+            .await
+        }
         KeyCommands::Status => check_key_status(config, &output).await,
+        KeyCommands::Fingerprint { public_key_path } => {
+            print_fingerprint(config, &output, public_key_path)
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn generate_keys(
     config: &Config,
     output: &OutputManager,
     public_key_path: Option<String>,
     private_key_path: Option<String>,
+    algorithm: KeyAlgorithm,
+    bits: usize,
     force: bool,
+    passphrase: bool,
 ) -> Result<()> {
     let public_path = public_key_path
         .as_deref()
@@ -49,10 +115,26 @@ async fn generate_keys(
         );
     }
 
-    output.print_info("Generating RSA key pair...");
+    let (private_key_pem, public_key_pem) = match algorithm {
+        KeyAlgorithm::Rsa => {
+            output.print_info(&format!("Generating {bits}-bit RSA key pair..."));
+            sealbox_server::crypto::master_key::generate_key_pair_with_bits(bits)
+        }
+        KeyAlgorithm::X25519 => {
+            output.print_info("Generating X25519 key pair...");
+            sealbox_server::crypto::master_key::generate_key_pair_with_algorithm(algorithm)
+        }
+    }
+    .context("Failed to generate key pair")?;
 
-    let (private_key_pem, public_key_pem) = sealbox_server::crypto::master_key::generate_key_pair()
-        .context("Failed to generate key pair")?;
+    let private_key_pem = if passphrase {
+        let passphrase = new_passphrase()?;
+        output.print_info("Encrypting private key...");
+        key_file::encrypt_private_key(&private_key_pem, &passphrase, key_file::DEFAULT_ITERATIONS)
+            .context("Failed to encrypt private key")?
+    } else {
+        private_key_pem
+    };
 
     // Ensure directories exist
     if let Some(parent) = Path::new(private_path).parent() {
@@ -96,7 +178,11 @@ async fn generate_keys(
     Ok(())
 }
 
-async fn register_key(config: &Config, output: &OutputManager) -> Result<()> {
+async fn register_key(
+    config: &Config,
+    output: &OutputManager,
+    default_ttl: Option<i64>,
+) -> Result<()> {
     config
         .validate()
         .context("Configuration validation failed")?;
@@ -118,93 +204,187 @@ async fn register_key(config: &Config, output: &OutputManager) -> Result<()> {
         .with_context(|| format!("Failed to read public key file: {public_key_path}"))?;
 
     // Validate public key format
-    rsa::RsaPublicKey::from_pkcs1_pem(&public_key_pem)
+    let public_key = PublicMasterKey::from_str(&public_key_pem)
         .with_context(|| format!("Invalid public key format: {public_key_path}"))?;
 
-    output.print_info("Registering public key to server...");
-
-    let client = Client::new();
-    let response = client
-        .post(format!("{}/v1/master-key", config.server.url))
-        .bearer_auth(&config.server.token)
-        .json(&json!({ "public_key": public_key_pem }))
-        .send()
-        .await
-        .context("Failed to request server")?;
+    // Prove possession of the key pair when its private half is at hand
+    let private_key_path = &config.keys.private_key_path;
+    let private_key_pem = if private_key_path.exists() {
+        Some(read_private_key_pem(private_key_path)?)
+    } else {
+        None
+    };
 
-    let status = response.status();
-    if status.is_success() {
-        let master_key: sealbox_server::repo::MasterKey = response
-            .json()
-            .await
-            .context("Failed to parse server response")?;
+    output.print_info("Registering public key to server...");
 
-        output.print_success("Public key registered successfully!");
+    let master_key = register_public_key_internal(
+        config,
+        &public_key_pem,
+        private_key_pem.as_deref(),
+        default_ttl,
+    )
+    .await?;
 
-        let formatted_keys = vec![master_key];
-        output.print_master_keys(&formatted_keys)?;
-    } else {
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unable to get error information".to_string());
-        anyhow::bail!(
-            "Server returned error (status code: {}):\n{}",
-            status,
-            error_body
-        );
+    output.print_success("Public key registered successfully!");
+    // Servers predating fingerprints report none
+    if let Some(fingerprint) = &master_key.fingerprint {
+        if !public_key.verify_fingerprint(fingerprint)? {
+            output.print_warning(&format!(
+                "Server reports fingerprint {fingerprint}, but the local public key's is {}",
+                public_key.fingerprint()?
+            ));
+        }
     }
 
+    let formatted_keys = vec![master_key];
+    output.print_master_keys(&formatted_keys)?;
+
     Ok(())
 }
 
-async fn list_keys(config: &Config, output: &OutputManager) -> Result<()> {
+/// Print the fingerprint of a local public key, to compare with the keys the server lists
+fn print_fingerprint(
+    config: &Config,
+    output: &OutputManager,
+    public_key_path: Option<String>,
+) -> Result<()> {
+    let public_key_path = match public_key_path {
+        Some(path) => path,
+        None => config
+            .keys
+            .public_key_path
+            .to_str()
+            .context("Public key path contains invalid characters")?
+            .to_string(),
+    };
+    let public_key_pem = fs::read_to_string(&public_key_path)
+        .with_context(|| format!("Failed to read public key file: {public_key_path}"))?;
+    let public_key = PublicMasterKey::from_str(&public_key_pem)
+        .with_context(|| format!("Invalid public key format: {public_key_path}"))?;
+
+    output.print_value(&json!({
+        "public_key_path": public_key_path,
+        "algorithm": public_key.algorithm(),
+        "fingerprint": public_key.fingerprint()?,
+    }))
+}
+
+/// Filters passed through to `GET /v1/master-key`
+struct ListFilter {
+    status: Option<MasterKeyStatus>,
+    description_contains: Option<String>,
+    limit: Option<u32>,
+    offset: u32,
+}
+
+/// One page of `GET /v1/master-key`
+#[derive(Debug, Deserialize)]
+struct MasterKeyList {
+    total: usize,
+    master_keys: Vec<MasterKey>,
+}
+
+async fn list_keys(config: &Config, output: &OutputManager, filter: &ListFilter) -> Result<()> {
     config
         .validate()
         .context("Configuration validation failed")?;
 
     output.print_info("Fetching master key list...");
 
-    let client = Client::new();
+    let mut url = reqwest::Url::parse(&format!("{}/v1/master-key", config.server.url))
+        .context("Invalid server URL")?;
+    {
+        let mut query = url.query_pairs_mut();
+        if let Some(status) = &filter.status {
+            query.append_pair("status", &format!("{status:?}"));
+        }
+        if let Some(text) = &filter.description_contains {
+            query.append_pair("description_contains", text);
+        }
+        if let Some(limit) = filter.limit {
+            query.append_pair("limit", &limit.to_string());
+        }
+        if filter.offset > 0 {
+            query.append_pair("offset", &filter.offset.to_string());
+        }
+    }
+
+    let client = http::client(config);
     let response = client
-        .get(format!("{}/v1/master-key", config.server.url))
+        .get(url)
         .bearer_auth(&config.server.token)
-        .send()
+        .send_with_retries(config)
         .await
         .context("Failed to request server")?;
 
     let status = response.status();
     if status.is_success() {
-        let master_keys: Vec<sealbox_server::repo::MasterKey> = response
+        let list: MasterKeyList = response
             .json()
             .await
             .context("Failed to parse server response")?;
 
-        if master_keys.is_empty() {
-            output.print_info("No master keys on server");
+        if list.master_keys.is_empty() {
+            output.print_info(&format!(
+                "No master keys on server ({} matching)",
+                list.total
+            ));
         } else {
-            output.print_master_keys(&master_keys)?;
+            output.print_master_keys(&list.master_keys)?;
+            if list.master_keys.len() < list.total {
+                let first = filter.offset as usize + 1;
+                output.print_info(&format!(
+                    "Showing {}-{} of {} master keys",
+                    first,
+                    first + list.master_keys.len() - 1,
+                    list.total
+                ));
+            }
         }
     } else {
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unable to get error information".to_string());
-        anyhow::bail!(
-            "Server returned error (status code: {}):\n{}",
-            status,
-            error_body
-        );
+        return Err(server_error(response).await);
     }
 
     Ok(())
 }
 
+async fn list_stale_keys(config: &Config, output: &OutputManager, days: u32) -> Result<()> {
+    config
+        .validate()
+        .context("Configuration validation failed")?;
+
+    let response = http::client(config)
+        .get(format!(
+            "{}/v1/admin/stale-keys?days={days}",
+            config.server.url
+        ))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+
+    let master_keys: Vec<sealbox_server::repo::MasterKey> = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
+    if master_keys.is_empty() {
+        output.print_info(&format!("No master keys unused for {days} days"));
+    } else {
+        output.print_master_keys(&master_keys)?;
+    }
+    Ok(())
+}
+
 async fn rotate_keys(
     config: &Config,
     output: &OutputManager,
-    new_key_id: String,
+    new_key_id: Option<String>,
     old_key_id: String,
+    new_key_files: Option<(String, String)>,
+    dry_run: bool,
 ) -> Result<()> {
     config
         .validate()
@@ -223,14 +403,49 @@ async fn rotate_keys(
         );
     }
 
-    let old_private_key_pem = fs::read_to_string(private_key_path)
-        .with_context(|| format!("Failed to read private key file: {private_key_path}"))?;
+    let old_private_key_pem = read_private_key_pem(Path::new(private_key_path))?;
 
-    let new_key_uuid = Uuid::parse_str(&new_key_id)
-        .with_context(|| format!("Invalid new key ID format: {new_key_id}"))?;
     let old_key_uuid = Uuid::parse_str(&old_key_id)
         .with_context(|| format!("Invalid old key ID format: {old_key_id}"))?;
 
+    // Use the provided key pair instead of one already registered on the server
+    let new_key_pair = match &new_key_files {
+        Some((public_path, private_path)) => Some(load_key_pair(public_path, private_path)?),
+        None => None,
+    };
+
+    if dry_run {
+        return preview_rotation(
+            config,
+            output,
+            old_key_uuid,
+            &old_private_key_pem,
+            new_key_id.as_deref(),
+            new_key_files.as_ref(),
+        )
+        .await;
+    }
+
+    let new_key_uuid = match (&new_key_pair, new_key_id) {
+        (Some((public_key_pem, private_key_pem)), _) => {
+            output.print_info("Registering provided public key to server...");
+            let key_id =
+                register_public_key_internal(config, public_key_pem, Some(private_key_pem), None)
+                    .await?
+                    .id;
+            // Secrets are about to be wrapped with whatever the server stored, so make sure
+            // it is the key we uploaded before rotating or replacing local key files
+            let stored_pem = fetch_public_key(config, key_id).await?;
+            verify_stored_public_key(key_id, &stored_pem, public_key_pem)?;
+            key_id
+        }
+        (None, Some(new_key_id)) => Uuid::parse_str(&new_key_id)
+            .with_context(|| format!("Invalid new key ID format: {new_key_id}"))?,
+        (None, None) => {
+            anyhow::bail!("Either --new-key-id or --new-public-key/--new-private-key is required")
+        }
+    };
+
     output.print_info("Performing key rotation...");
     output.print_warning("This operation will re-encrypt all secrets using the old key, please ensure the operation is correct!");
 
@@ -240,48 +455,419 @@ async fn rotate_keys(
         "old_private_key_pem": old_private_key_pem
     });
 
-    let client = Client::new();
+    let client = http::client(config);
     let response = client
         .put(format!("{}/v1/master-key", config.server.url))
         .bearer_auth(&config.server.token)
         .json(&payload)
-        .send()
+        .send_with_retries(config)
         .await
         .context("Failed to request server")?;
 
     let status = response.status();
-    if status.is_success() {
-        let result: serde_json::Value = response
-            .json()
-            .await
-            .context("Failed to parse server response")?;
+    if !status.is_success() {
+        return Err(server_error(response).await);
+    }
+    let result: serde_json::Value = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
 
-        output.print_success("Key rotation completed!");
-        output.print_value(&result)?;
+    // Install the private key file as given so a passphrase-protected key stays protected
+    let new_private_key_file = match &new_key_files {
+        Some((_, private_path)) => Some(
+            fs::read_to_string(private_path)
+                .with_context(|| format!("Failed to read private key file: {private_path}"))?,
+        ),
+        None => None,
+    };
+    let new_key_pair = new_key_pair
+        .as_ref()
+        .map(|(public_key_pem, _)| public_key_pem.as_str())
+        .zip(new_private_key_file.as_deref());
+    finish_rotation(config, output, &result, new_key_pair)
+}
 
-        if let Some(failed_keys) = result.get("failed_secret_keys") {
-            if !failed_keys.as_array().unwrap_or(&vec![]).is_empty() {
-                output.print_warning(
-                    "The following secrets failed to rotate and may need manual handling:",
-                );
-                output.print_value(failed_keys)?;
-            }
+/// Report the server's rotation result and, once every secret is on the new master key,
+/// install the new key pair over the configured key files.
+///
+/// When some secrets failed to rotate they are still wrapped with the old master key, so
+/// the old key files are left in place and the rotation is reported as incomplete.
+fn finish_rotation(
+    config: &Config,
+    output: &OutputManager,
+    result: &serde_json::Value,
+    new_key_pair: Option<(&str, &str)>,
+) -> Result<()> {
+    let failed_keys = result
+        .get("failed_secret_keys")
+        .and_then(|failed| failed.as_array())
+        .filter(|failed| !failed.is_empty());
+
+    if let Some(failed_keys) = failed_keys {
+        output.print_warning(
+            "The following secrets failed to rotate and still use the old master key:",
+        );
+        output.print_value(&json!(failed_keys))?;
+        if new_key_pair.is_some() {
+            output.print_info(&format!(
+                "Local key files were left unchanged: {} and {}",
+                config.keys.public_key_path.display(),
+                config.keys.private_key_path.display()
+            ));
         }
-    } else {
-        let error_body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unable to get error information".to_string());
         anyhow::bail!(
-            "Server returned error (status code: {}):\n{}",
-            status,
-            error_body
+            "Key rotation incomplete: {} secret(s) failed to rotate; run the rotation again once they are fixed",
+            failed_keys.len()
+        );
+    }
+
+    output.print_success("Key rotation completed!");
+    output.print_value(result)?;
+
+    if let Some((public_key_pem, private_key_file)) = new_key_pair {
+        install_key_pair(config, public_key_pem, private_key_file)?;
+        output.print_info(&format!(
+            "New key pair installed to: {} and {}",
+            config.keys.public_key_path.display(),
+            config.keys.private_key_path.display()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Report what `key rotate` would do: which secret versions are wrapped with the old master
+/// key, whether the old private key can unwrap them, and which local key files would be
+/// replaced. Only reads from the server, so no key is registered and nothing is re-encrypted
+async fn preview_rotation(
+    config: &Config,
+    output: &OutputManager,
+    old_key_uuid: Uuid,
+    old_private_key_pem: &str,
+    new_key_id: Option<&str>,
+    new_key_files: Option<&(String, String)>,
+) -> Result<()> {
+    let old_private_key = PrivateMasterKey::from_str(old_private_key_pem)
+        .context("Failed to parse old private key")?;
+
+    let server_keys = list_server_keys_internal(config).await?;
+    if !server_keys.iter().any(|key| key.id == old_key_uuid) {
+        anyhow::bail!("Old master key {old_key_uuid} is not registered on the server");
+    }
+    let new_key = match (new_key_files, new_key_id) {
+        (Some((public_path, _)), _) => json!({ "register_public_key": public_path }),
+        (None, Some(new_key_id)) => {
+            let new_key_uuid = Uuid::parse_str(new_key_id)
+                .with_context(|| format!("Invalid new key ID format: {new_key_id}"))?;
+            if !server_keys.iter().any(|key| key.id == new_key_uuid) {
+                anyhow::bail!("New master key {new_key_uuid} is not registered on the server");
+            }
+            json!({ "id": new_key_uuid })
+        }
+        (None, None) => {
+            anyhow::bail!("Either --new-key-id or --new-public-key/--new-private-key is required")
+        }
+    };
+
+    output.print_info("Checking which secrets the rotation would re-encrypt...");
+    let client = http::client(config);
+    let list = fetch_json(
+        &client,
+        config,
+        &format!("{}/v1/secrets", config.server.url),
+    )
+    .await?;
+    let secrets: Vec<SecretInfo> = serde_json::from_value(
+        list.get("secrets")
+            .cloned()
+            .context("Missing 'secrets' field in response")?,
+    )
+    .context("Invalid 'secrets' field in response")?;
+
+    let mut versions_to_rotate = 0;
+    let mut secret_keys = Vec::new();
+    let mut undecryptable = Vec::new();
+    for secret in &secrets {
+        let history = fetch_json(
+            &client,
+            config,
+            http::secret_url(config, &secret.key, &["versions"])?.as_str(),
+        )
+        .await?;
+        let versions: Vec<SecretInfo> = serde_json::from_value(
+            history
+                .get("versions")
+                .cloned()
+                .context("Missing 'versions' field in response")?,
+        )
+        .context("Invalid 'versions' field in response")?;
+
+        let mut uses_old_key = false;
+        for version in versions {
+            let secret_data = fetch_json(
+                &client,
+                config,
+                {
+                    let mut url = http::secret_url(config, &secret.key, &[])?;
+                    url.query_pairs_mut()
+                        .append_pair("version", &version.version.to_string());
+                    url
+                }
+                .as_str(),
+            )
+            .await?;
+            let master_key_id = secret_data
+                .get("master_key_id")
+                .and_then(|id| id.as_str())
+                .and_then(|id| Uuid::parse_str(id).ok());
+            if master_key_id != Some(old_key_uuid) {
+                continue;
+            }
+
+            uses_old_key = true;
+            versions_to_rotate += 1;
+            if decrypt_secret_response(&old_private_key, &secret.key, &secret_data).is_err() {
+                undecryptable.push(format!("{}@{}", secret.key, version.version));
+            }
+        }
+        if uses_old_key {
+            secret_keys.push(secret.key.clone());
+        }
+    }
+
+    let local_key_files = match new_key_files {
+        Some(_) => json!({
+            "overwritten": [
+                config.keys.public_key_path.display().to_string(),
+                config.keys.private_key_path.display().to_string(),
+            ]
+        }),
+        None => json!({ "overwritten": [] }),
+    };
+    output.print_value(&json!({
+        "dry_run": true,
+        "old_master_key_id": old_key_uuid,
+        "new_master_key": new_key,
+        "versions_to_rotate": versions_to_rotate,
+        "secret_keys": secret_keys,
+        "undecryptable_versions": undecryptable,
+        "local_key_files": local_key_files,
+    }))?;
+
+    if !undecryptable.is_empty() {
+        output.print_warning(
+            "The old private key cannot decrypt some versions; they would fail to rotate",
         );
     }
+    output.print_success("Dry run, no changes made");
+    Ok(())
+}
+
+/// GET a JSON document from the server
+async fn fetch_json(client: &Client, config: &Config, url: &str) -> Result<serde_json::Value> {
+    let response = client
+        .get(url)
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    response
+        .json()
+        .await
+        .context("Failed to parse server response")
+}
+
+/// Passphrase for encrypted private key files, read instead of prompting when set
+const PASSPHRASE_ENV: &str = "SEALBOX_KEY_PASSPHRASE";
+
+/// Read a private key file, asking for its passphrase if it is encrypted.
+///
+/// Unencrypted files are returned as they are.
+pub(crate) fn read_private_key_pem(path: &Path) -> Result<String> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read private key file: {}", path.display()))?;
+    if !key_file::is_encrypted(&content) {
+        return Ok(content);
+    }
+
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => rpassword::prompt_password(format!("Passphrase for {}: ", path.display()))
+            .context("Failed to read passphrase")?,
+    };
+    key_file::decrypt_private_key(&content, &passphrase)
+        .with_context(|| format!("Failed to unlock private key file: {}", path.display()))
+}
+
+/// Passphrase for a new encrypted private key file, entered twice unless taken from the environment
+fn new_passphrase() -> Result<String> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = rpassword::prompt_password("New passphrase: ")
+                .context("Failed to read passphrase")?;
+            let confirmation = rpassword::prompt_password("Repeat passphrase: ")
+                .context("Failed to read passphrase")?;
+            if passphrase != confirmation {
+                anyhow::bail!("Passphrases do not match");
+            }
+            passphrase
+        }
+    };
+    if passphrase.is_empty() {
+        anyhow::bail!("Passphrase cannot be empty");
+    }
+    Ok(passphrase)
+}
+
+/// Load an externally generated key pair and verify both halves belong together
+pub(crate) fn load_key_pair(public_path: &str, private_path: &str) -> Result<(String, String)> {
+    let public_key_pem = fs::read_to_string(public_path)
+        .with_context(|| format!("Failed to read public key file: {public_path}"))?;
+    let private_key_pem = read_private_key_pem(Path::new(private_path))?;
+
+    let public_key = sealbox_server::crypto::master_key::PublicMasterKey::from_str(&public_key_pem)
+        .with_context(|| format!("Invalid public key format: {public_path}"))?;
+    let private_key =
+        sealbox_server::crypto::master_key::PrivateMasterKey::from_str(&private_key_pem)
+            .with_context(|| format!("Invalid private key format: {private_path}"))?;
+
+    let encrypted = public_key
+        .encrypt(b"test")
+        .context("Failed to encrypt with provided public key")?;
+    match private_key.decrypt(&encrypted) {
+        Ok(decrypted) if decrypted == b"test" => Ok((public_key_pem, private_key_pem)),
+        _ => anyhow::bail!(
+            "Provided key files are not a matching key pair:\n  Public key: {}\n  Private key: {}",
+            public_path,
+            private_path
+        ),
+    }
+}
+
+/// Write a key pair to the configured key paths so later commands use it
+fn install_key_pair(config: &Config, public_key_pem: &str, private_key_pem: &str) -> Result<()> {
+    let public_path = &config.keys.public_key_path;
+    let private_path = &config.keys.private_key_path;
+
+    for path in [public_path, private_path] {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create key directory: {}", parent.display()))?;
+        }
+    }
+
+    fs::write(private_path, private_key_pem).with_context(|| {
+        format!(
+            "Failed to write private key file: {}",
+            private_path.display()
+        )
+    })?;
+    fs::write(public_path, public_key_pem)
+        .with_context(|| format!("Failed to write public key file: {}", public_path.display()))?;
+
+    // Set private key file permissions (owner read/write only)
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(private_path)?.permissions();
+        perms.set_mode(0o600);
+        fs::set_permissions(private_path, perms)?;
+    }
 
     Ok(())
 }
 
+/// What `key status` found out about the local key pair and the server
+#[derive(Debug, Serialize)]
+pub(crate) struct KeyStatus {
+    pub local_keys: LocalKeyStatus,
+    pub server_keys: ServerKeyStatus,
+    /// Only reported for admin tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation_due: Option<RotationDue>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct LocalKeyStatus {
+    pub public_key_exists: bool,
+    pub private_key_exists: bool,
+    pub public_key_path: String,
+    pub private_key_path: String,
+    /// Whether the private key decrypts what the public key encrypts, if both could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_pair_valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum ServerKeyStatus {
+    Listed { count: usize, keys: Vec<MasterKey> },
+    Unavailable { error: String },
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RotationDue {
+    pub count: usize,
+    pub keys: Vec<String>,
+}
+
+impl LocalKeyStatus {
+    /// Check that both key files parse and form a working pair, recording any problem
+    fn check_key_pair(&mut self) {
+        let public_pem = match fs::read_to_string(&self.public_key_path) {
+            Ok(public_pem) => public_pem,
+            Err(e) => {
+                self.public_key_error = Some(format!("Failed to read public key file: {e}"));
+                return;
+            }
+        };
+        let private_pem = match read_private_key_pem(Path::new(&self.private_key_path)) {
+            Ok(private_pem) => private_pem,
+            Err(e) => {
+                self.private_key_error = Some(format!("{e:#}"));
+                return;
+            }
+        };
+        let public_key = match PublicMasterKey::from_str(&public_pem) {
+            Ok(public_key) => public_key,
+            Err(e) => {
+                self.public_key_error = Some(format!("Failed to parse public key: {e}"));
+                return;
+            }
+        };
+        let private_key = match PrivateMasterKey::from_str(&private_pem) {
+            Ok(private_key) => private_key,
+            Err(e) => {
+                self.private_key_error = Some(format!("Failed to parse private key: {e}"));
+                return;
+            }
+        };
+
+        // Test key pair compatibility by encrypting and decrypting a test message
+        let problem = match public_key.encrypt(b"test") {
+            Ok(encrypted) => match private_key.decrypt(&encrypted) {
+                Ok(decrypted) if decrypted == b"test" => None,
+                Ok(_) => Some("Key pair mismatch: decryption result doesn't match".to_string()),
+                Err(e) => Some(format!("Decryption failed: {e}")),
+            },
+            Err(e) => Some(format!("Encryption failed: {e}")),
+        };
+        self.key_pair_valid = Some(problem.is_none());
+        self.validation_error = problem;
+    }
+}
+
 async fn check_key_status(config: &Config, output: &OutputManager) -> Result<()> {
     let public_key_path = config
         .keys
@@ -294,123 +880,234 @@ async fn check_key_status(config: &Config, output: &OutputManager) -> Result<()>
         .to_str()
         .context("Private key path contains invalid characters")?;
 
-    let mut status_info = json!({
-        "local_keys": {
-            "public_key_exists": Path::new(public_key_path).exists(),
-            "private_key_exists": Path::new(private_key_path).exists(),
-            "public_key_path": public_key_path,
-            "private_key_path": private_key_path
-        }
-    });
-
-    // Check if key pair matches by reading and parsing both key files
-    if Path::new(public_key_path).exists() && Path::new(private_key_path).exists() {
-        use std::str::FromStr;
-
-        match fs::read_to_string(public_key_path) {
-            Ok(public_pem) => match fs::read_to_string(private_key_path) {
-                Ok(private_pem) => {
-                    // Try to parse both keys
-                    match (
-                        sealbox_server::crypto::master_key::PublicMasterKey::from_str(&public_pem),
-                        sealbox_server::crypto::master_key::PrivateMasterKey::from_str(
-                            &private_pem,
-                        ),
-                    ) {
-                        (Ok(public_key), Ok(private_key)) => {
-                            // Test key pair compatibility by encrypting and decrypting a test message
-                            match public_key.encrypt(b"test") {
-                                Ok(encrypted) => match private_key.decrypt(&encrypted) {
-                                    Ok(decrypted) if decrypted == b"test" => {
-                                        status_info["local_keys"]["key_pair_valid"] = json!(true);
-                                    }
-                                    Ok(_) => {
-                                        status_info["local_keys"]["key_pair_valid"] = json!(false);
-                                        status_info["local_keys"]["validation_error"] = json!(
-                                            "Key pair mismatch: decryption result doesn't match"
-                                        );
-                                    }
-                                    Err(e) => {
-                                        status_info["local_keys"]["key_pair_valid"] = json!(false);
-                                        status_info["local_keys"]["validation_error"] =
-                                            json!(format!("Decryption failed: {}", e));
-                                    }
-                                },
-                                Err(e) => {
-                                    status_info["local_keys"]["key_pair_valid"] = json!(false);
-                                    status_info["local_keys"]["validation_error"] =
-                                        json!(format!("Encryption failed: {}", e));
-                                }
-                            }
-                        }
-                        (Err(e), _) => {
-                            status_info["local_keys"]["public_key_error"] =
-                                json!(format!("Failed to parse public key: {}", e));
-                        }
-                        (_, Err(e)) => {
-                            status_info["local_keys"]["private_key_error"] =
-                                json!(format!("Failed to parse private key: {}", e));
-                        }
-                    }
-                }
-                Err(e) => {
-                    status_info["local_keys"]["private_key_error"] =
-                        json!(format!("Failed to read private key file: {}", e));
-                }
-            },
-            Err(e) => {
-                status_info["local_keys"]["public_key_error"] =
-                    json!(format!("Failed to read public key file: {}", e));
-            }
-        }
+    let mut local_keys = LocalKeyStatus {
+        public_key_exists: Path::new(public_key_path).exists(),
+        private_key_exists: Path::new(private_key_path).exists(),
+        public_key_path: public_key_path.to_string(),
+        private_key_path: private_key_path.to_string(),
+        key_pair_valid: None,
+        validation_error: None,
+        public_key_error: None,
+        private_key_error: None,
+    };
+    if local_keys.public_key_exists && local_keys.private_key_exists {
+        local_keys.check_key_pair();
     }
 
     // If configuration is valid, check keys on server
-    if config.validate().is_ok() {
-        match list_server_keys_internal(config).await {
-            Ok(server_keys) => {
-                status_info["server_keys"] = json!({
-                    "count": server_keys.len(),
-                    "keys": server_keys
-                });
-            }
-            Err(e) => {
-                status_info["server_keys"] = json!({
-                    "error": e.to_string()
-                });
-            }
-        }
+    let (server_keys, rotation_due) = if config.validate().is_ok() {
+        let server_keys = match list_server_keys_internal(config).await {
+            Ok(keys) => ServerKeyStatus::Listed {
+                count: keys.len(),
+                keys,
+            },
+            Err(e) => ServerKeyStatus::Unavailable {
+                error: e.to_string(),
+            },
+        };
+        // Reminders are an admin endpoint, so other tokens simply see no count
+        let rotation_due = fetch_rotation_due(config)
+            .await
+            .ok()
+            .map(|due| RotationDue {
+                count: due.len(),
+                keys: due.into_iter().map(|secret| secret.key).collect(),
+            });
+        (server_keys, rotation_due)
     } else {
-        status_info["server_keys"] = json!({
-            "error": "Configuration is invalid, cannot check server keys"
-        });
-    }
+        (
+            ServerKeyStatus::Unavailable {
+                error: "Configuration is invalid, cannot check server keys".to_string(),
+            },
+            None,
+        )
+    };
 
-    output.print_value(&status_info)?;
+    output.print_key_status(&KeyStatus {
+        local_keys,
+        server_keys,
+        rotation_due,
+    })?;
     Ok(())
 }
 
-async fn list_server_keys_internal(
+/// Secrets the server reports as past their rotation reminder
+async fn fetch_rotation_due(config: &Config) -> Result<Vec<sealbox_server::repo::SecretInfo>> {
+    let response = http::client(config)
+        .get(format!("{}/v1/admin/rotation-due", config.server.url))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    response
+        .json()
+        .await
+        .context("Failed to parse server response")
+}
+
+pub(crate) async fn list_server_keys_internal(
     config: &Config,
 ) -> Result<Vec<sealbox_server::repo::MasterKey>> {
-    let client = Client::new();
+    let client = http::client(config);
     let response = client
         .get(format!("{}/v1/master-key", config.server.url))
         .bearer_auth(&config.server.token)
-        .send()
+        .send_with_retries(config)
         .await
         .context("Failed to request server")?;
 
     if response.status().is_success() {
+        let list: MasterKeyList = response
+            .json()
+            .await
+            .context("Failed to parse server response")?;
+        Ok(list.master_keys)
+    } else {
+        Err(server_error(response).await)
+    }
+}
+
+/// Public key PEM the server stores for master key `key_id`
+async fn fetch_public_key(config: &Config, key_id: Uuid) -> Result<String> {
+    #[derive(Deserialize)]
+    struct StoredPublicKey {
+        public_key: String,
+    }
+
+    let response = http::client(config)
+        .get(format!(
+            "{}/v1/master-key/{key_id}/public-key",
+            config.server.url
+        ))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    let stored: StoredPublicKey = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
+    Ok(stored.public_key)
+}
+
+/// Check that `stored_pem`, the public key the server holds for master key `key_id`, is the
+/// `uploaded_pem` we registered
+///
+/// Guards against the uploaded key being swapped in transit or mangled by the server.
+fn verify_stored_public_key(key_id: Uuid, stored_pem: &str, uploaded_pem: &str) -> Result<()> {
+    if stored_pem.trim() != uploaded_pem.trim() {
+        anyhow::bail!(
+            "Server stores a different public key for master key {key_id} than the one uploaded; \
+             aborting rotation"
+        );
+    }
+    Ok(())
+}
+
+/// Register a public key, proving possession of `private_key_pem` when given
+async fn register_public_key_internal(
+    config: &Config,
+    public_key_pem: &str,
+    private_key_pem: Option<&str>,
+    default_ttl: Option<i64>,
+) -> Result<sealbox_server::repo::MasterKey> {
+    // Servers without /v1/info predate capability reporting; let them decide
+    let algorithm = KeyAlgorithm::detect(public_key_pem);
+    if let Ok(info) = server_info(config).await {
+        if !info.supports_algorithm(algorithm) {
+            anyhow::bail!(
+                "Server {} does not support {} master keys (supported: {})",
+                config.server.url,
+                algorithm,
+                info.key_algorithms.join(", ")
+            );
+        }
+    }
+
+    let client = http::client(config);
+    let proof = match private_key_pem {
+        Some(private_key_pem) => {
+            prove_possession(&client, config, public_key_pem, private_key_pem).await?
+        }
+        None => None,
+    };
+    let response = client
+        .post(format!("{}/v1/master-key", config.server.url))
+        .bearer_auth(&config.server.token)
+        .json(&json!({
+            "public_key": public_key_pem,
+            "default_ttl": default_ttl,
+            "proof": proof,
+        }))
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+
+    let status = response.status();
+    if status.is_success() {
         response
             .json()
             .await
             .context("Failed to parse server response")
     } else {
-        anyhow::bail!("Server returned error: {}", response.status());
+        Err(server_error(response).await)
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct KeyChallenge {
+    challenge_id: Uuid,
+    encrypted_challenge: String,
+}
+
+/// Answer a server challenge for `public_key_pem` with its private key
+///
+/// Returns `None` for servers predating proof-of-possession challenges.
+async fn prove_possession(
+    client: &Client,
+    config: &Config,
+    public_key_pem: &str,
+    private_key_pem: &str,
+) -> Result<Option<serde_json::Value>> {
+    let response = client
+        .post(format!("{}/v1/master-key/challenge", config.server.url))
+        .bearer_auth(&config.server.token)
+        .json(&json!({ "public_key": public_key_pem }))
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(server_error(response).await);
+    }
+    let challenge: KeyChallenge = response
+        .json()
+        .await
+        .context("Failed to parse server response")?;
+
+    let encrypted = BASE64
+        .decode(&challenge.encrypted_challenge)
+        .context("Server sent an invalid challenge")?;
+    let answer = PrivateMasterKey::from_str(private_key_pem)
+        .context("Invalid private key format")?
+        .decrypt(&encrypted)
+        .context(
+            "Failed to answer the server challenge; does the private key match the public key?",
+        )?;
+    Ok(Some(json!({
+        "challenge_id": challenge.challenge_id,
+        "response": BASE64.encode(answer),
+    })))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -430,7 +1127,17 @@ mod tests {
         let (config, _temp_dir) = create_test_config();
         let output = OutputManager::new(OutputFormat::Json);
 
-        let result = generate_keys(&config, &output, None, None, true).await;
+        let result = generate_keys(
+            &config,
+            &output,
+            None,
+            None,
+            KeyAlgorithm::Rsa,
+            2048,
+            true,
+            false,
+        )
+        .await;
         assert!(result.is_ok());
 
         // Check if key files are generated
@@ -438,6 +1145,29 @@ mod tests {
         assert!(config.keys.private_key_path.exists());
     }
 
+    #[tokio::test]
+    async fn test_generate_x25519_keys() {
+        let (config, _temp_dir) = create_test_config();
+        let output = OutputManager::new(OutputFormat::Json);
+
+        generate_keys(
+            &config,
+            &output,
+            None,
+            None,
+            KeyAlgorithm::X25519,
+            2048,
+            true,
+            false,
+        )
+        .await
+        .expect("Should generate X25519 keys");
+
+        let public_pem = fs::read_to_string(&config.keys.public_key_path).unwrap();
+        let public_key = PublicMasterKey::from_str(&public_pem).expect("Should parse public key");
+        assert_eq!(public_key.algorithm(), KeyAlgorithm::X25519);
+    }
+
     #[tokio::test]
     async fn test_generate_keys_without_force_existing_files() {
         let (config, _temp_dir) = create_test_config();
@@ -446,10 +1176,44 @@ mod tests {
         // Create empty file first
         fs::write(&config.keys.public_key_path, "").unwrap();
 
-        let result = generate_keys(&config, &output, None, None, false).await;
+        let result = generate_keys(
+            &config,
+            &output,
+            None,
+            None,
+            KeyAlgorithm::Rsa,
+            2048,
+            false,
+            false,
+        )
+        .await;
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_generate_keys_unsupported_bits() {
+        let (config, _temp_dir) = create_test_config();
+        let output = OutputManager::new(OutputFormat::Json);
+
+        let result = generate_keys(
+            &config,
+            &output,
+            None,
+            None,
+            KeyAlgorithm::Rsa,
+            1024,
+            true,
+            false,
+        )
+        .await;
+        let err = format!("{:#}", result.unwrap_err());
+        assert!(err.contains("Unsupported RSA key size: 1024"));
+
+        // Nothing is written when the size is rejected
+        assert!(!config.keys.public_key_path.exists());
+        assert!(!config.keys.private_key_path.exists());
+    }
+
     #[tokio::test]
     async fn test_check_key_status() {
         let (config, _temp_dir) = create_test_config();
@@ -458,4 +1222,118 @@ mod tests {
         // This test mainly verifies no panic occurs
         assert!(check_key_status(&config, &output).await.is_ok());
     }
+
+    #[test]
+    fn test_print_fingerprint() {
+        let (config, _temp_dir) = create_test_config();
+        let output = OutputManager::new(OutputFormat::Json);
+        assert!(print_fingerprint(&config, &output, None).is_err());
+
+        let (_, public_pem) = sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        fs::write(&config.keys.public_key_path, public_pem).unwrap();
+        assert!(print_fingerprint(&config, &output, None).is_ok());
+    }
+
+    #[test]
+    fn test_rotate_uses_provided_key_pair() {
+        let (config, temp_dir) = create_test_config();
+        let (private_pem, public_pem) =
+            sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+
+        // Key pair exported from elsewhere, e.g. an HSM
+        let public_path = temp_dir.path().join("external_public.pem");
+        let private_path = temp_dir.path().join("external_private.pem");
+        fs::write(&public_path, &public_pem).unwrap();
+        fs::write(&private_path, &private_pem).unwrap();
+
+        let (loaded_public, loaded_private) = load_key_pair(
+            public_path.to_str().unwrap(),
+            private_path.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(loaded_public, public_pem);
+        assert_eq!(loaded_private, private_pem);
+
+        install_key_pair(&config, &loaded_public, &loaded_private).unwrap();
+
+        // The configured keys are the provided ones, not freshly generated
+        assert_eq!(
+            fs::read_to_string(&config.keys.public_key_path).unwrap(),
+            public_pem
+        );
+        assert_eq!(
+            fs::read_to_string(&config.keys.private_key_path).unwrap(),
+            private_pem
+        );
+    }
+
+    #[test]
+    fn test_partial_rotation_keeps_old_key_files() {
+        let (config, _temp_dir) = create_test_config();
+        let output = OutputManager::new(OutputFormat::Json);
+        let (old_private_pem, old_public_pem) =
+            sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        let (new_private_pem, new_public_pem) =
+            sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        install_key_pair(&config, &old_public_pem, &old_private_pem).unwrap();
+
+        let partial = json!({ "master_key": Uuid::new_v4(), "failed_secret_keys": ["db"] });
+        let err = finish_rotation(
+            &config,
+            &output,
+            &partial,
+            Some((&new_public_pem, &new_private_pem)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("1 secret(s) failed"), "{err}");
+        // "db" still needs the old private key
+        assert_eq!(
+            fs::read_to_string(&config.keys.private_key_path).unwrap(),
+            old_private_pem
+        );
+
+        let complete = json!({ "master_key": Uuid::new_v4(), "failed_secret_keys": [] });
+        finish_rotation(
+            &config,
+            &output,
+            &complete,
+            Some((&new_public_pem, &new_private_pem)),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&config.keys.private_key_path).unwrap(),
+            new_private_pem
+        );
+    }
+
+    #[test]
+    fn test_verify_stored_public_key() {
+        let (_, public_pem) = sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        let (_, other_public_pem) =
+            sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        let key_id = Uuid::new_v4();
+
+        assert!(verify_stored_public_key(key_id, &public_pem, &public_pem).is_ok());
+        // The server returned a key other than the uploaded one
+        assert!(verify_stored_public_key(key_id, &other_public_pem, &public_pem).is_err());
+    }
+
+    #[test]
+    fn test_rotate_rejects_mismatched_key_pair() {
+        let (_config, temp_dir) = create_test_config();
+        let (_, public_pem) = sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+        let (other_private_pem, _) =
+            sealbox_server::crypto::master_key::generate_key_pair().unwrap();
+
+        let public_path = temp_dir.path().join("external_public.pem");
+        let private_path = temp_dir.path().join("external_private.pem");
+        fs::write(&public_path, public_pem).unwrap();
+        fs::write(&private_path, other_private_pem).unwrap();
+
+        let result = load_key_pair(
+            public_path.to_str().unwrap(),
+            private_path.to_str().unwrap(),
+        );
+        assert!(result.is_err());
+    }
 }
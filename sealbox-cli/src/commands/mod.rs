@@ -1,3 +1,5 @@
+pub mod admin_commands;
+pub mod audit_commands;
 pub mod config_commands;
 pub mod key_commands;
 pub mod secret_commands;
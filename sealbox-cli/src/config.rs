@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
 };
@@ -6,28 +7,107 @@ use std::{
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+/// Name of the profile stored in the top-level `server` and `keys` sections
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Seconds to wait for the server before a request fails, unless configured otherwise
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Retries for requests that fail transiently, unless configured otherwise
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// Configuration of the active profile, plus every other profile from the config file.
+///
+/// `server` and `keys` always hold the active profile's settings, so commands never need
+/// to know which profile is selected. In the file, the top-level sections are the
+/// `default` profile and named profiles live under `[profiles.<name>]`.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_profile: Option<String>, // Profile selected by `config use`
     pub server: ServerConfig,
     pub keys: KeyConfig,
     pub output: OutputConfig,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(skip)]
+    active_profile: Option<String>, // Named profile loaded into `server`/`keys`, None for default
+    #[serde(skip)]
+    default_profile: Option<Profile>, // Top-level settings set aside while a named profile is active
+}
+
+/// Server and key settings that differ between profiles; output settings are shared
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Profile {
+    pub server: ServerConfig,
+    pub keys: KeyConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ServerConfig {
     pub url: String,
     pub token: String,
+    #[serde(default = "default_timeout")]
+    pub timeout: u64, // Seconds to wait for a response; also set by `--timeout`
+    #[serde(default = "default_retries")]
+    pub retries: u32, // Retries after connection errors, 429 and 5xx; also set by `--retries`
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_TIMEOUT_SECS
 }
 
+fn default_retries() -> u32 {
+    DEFAULT_RETRIES
+}
+
+/// Key pair used by commands, plus any named identities that can replace it with `--identity`
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct KeyConfig {
     pub public_key_path: PathBuf,
     pub private_key_path: PathBuf,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub identities: BTreeMap<String, Identity>, // Extra key pairs under `[keys.identities.<name>]`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub client_encrypt: bool, // Encrypt secrets locally so the server never sees plaintext
+}
+
+/// A named key pair for machines that act as more than one identity
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Identity {
+    pub public_key_path: PathBuf,
+    pub private_key_path: PathBuf,
+}
+
+impl KeyConfig {
+    /// Use the key pair of identity `name` instead of the top-level one
+    pub fn select_identity(&mut self, name: &str) -> Result<()> {
+        let Some(identity) = self.identities.get(name) else {
+            let available: Vec<_> = self.identities.keys().map(String::as_str).collect();
+            anyhow::bail!(
+                "Identity '{name}' not found. Available identities: {}",
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            );
+        };
+        self.public_key_path = identity.public_key_path.clone();
+        self.private_key_path = identity.private_key_path.clone();
+        Ok(())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OutputConfig {
     pub format: OutputFormat,
+    /// Never emit ANSI colors; also set by `--no-color` or `NO_COLOR`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_color: bool,
+    /// Print only data and errors; set by `--quiet` and never saved
+    #[serde(skip)]
+    pub quiet: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -44,34 +124,54 @@ impl Default for Config {
         let config_dir = home_dir.join(".config").join("sealbox");
 
         Self {
+            current_profile: None,
             server: ServerConfig {
                 url: "http://127.0.0.1:8080".to_string(),
                 token: String::new(),
+                timeout: DEFAULT_TIMEOUT_SECS,
+                retries: DEFAULT_RETRIES,
             },
             keys: KeyConfig {
                 public_key_path: config_dir.join("public_key.pem"),
                 private_key_path: config_dir.join("private_key.pem"),
+                identities: BTreeMap::new(),
+                client_encrypt: false,
             },
             output: OutputConfig {
                 format: OutputFormat::Table,
+                no_color: false,
+                quiet: false,
             },
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            default_profile: None,
         }
     }
 }
 
 impl Config {
-    pub fn load() -> Result<Self> {
+    /// Load the config file with the given profile active.
+    ///
+    /// The profile is taken from `profile` (the `--profile` flag), then `SEALBOX_PROFILE`,
+    /// then the profile chosen with `config use`, and finally `default`.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
         let config_path = Self::config_file_path()?;
-
-        if !config_path.exists() {
-            return Ok(Self::default());
-        }
-
-        let config_content = fs::read_to_string(&config_path)
-            .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
-
-        let mut config: Config = toml::from_str(&config_content)
-            .with_context(|| format!("Invalid config file format: {}", config_path.display()))?;
+        let env_profile = std::env::var("SEALBOX_PROFILE")
+            .ok()
+            .filter(|name| !name.is_empty());
+        let profile = profile.or(env_profile.as_deref());
+
+        let mut config = if config_path.exists() {
+            let config_content = fs::read_to_string(&config_path).with_context(|| {
+                format!("Failed to read config file: {}", config_path.display())
+            })?;
+            Self::from_toml(&config_content, profile)
+                .with_context(|| format!("Invalid config file: {}", config_path.display()))?
+        } else {
+            let mut config = Self::default();
+            config.activate(profile)?;
+            config
+        };
 
         // Apply environment variable overrides
         config.apply_env_overrides();
@@ -82,6 +182,23 @@ impl Config {
         Ok(config)
     }
 
+    /// Parse config file contents and activate `profile`, or the file's current profile
+    fn from_toml(content: &str, profile: Option<&str>) -> Result<Self> {
+        let mut config: Config = toml::from_str(content).context("Invalid config file format")?;
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| config.current_profile.clone());
+        config.activate(profile.as_deref())?;
+        Ok(config)
+    }
+
+    /// Serialize to the file layout, with the active profile stored back under its name
+    fn to_toml(&self) -> Result<String> {
+        let mut file = self.clone();
+        file.activate(None)?;
+        toml::to_string_pretty(&file).context("Failed to serialize config")
+    }
+
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_file_path()?;
 
@@ -91,12 +208,92 @@ impl Config {
             })?;
         }
 
-        let config_content = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        let config_content = self.to_toml()?;
 
         fs::write(&config_path, config_content)
             .with_context(|| format!("Failed to write config file: {}", config_path.display()))?;
 
-        println!("Configuration saved to: {}", config_path.display());
+        if !self.output.quiet {
+            println!("Configuration saved to: {}", config_path.display());
+        }
+        Ok(())
+    }
+
+    /// Name of the profile whose settings are in `server` and `keys`
+    pub fn active_profile_name(&self) -> &str {
+        self.active_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Names of every profile, `default` first
+    pub fn profile_names(&self) -> Vec<&str> {
+        std::iter::once(DEFAULT_PROFILE)
+            .chain(self.profiles.keys().map(String::as_str))
+            .collect()
+    }
+
+    /// Server URL configured for `profile`
+    pub fn profile_url(&self, profile: &str) -> Option<&str> {
+        if profile == self.active_profile_name() {
+            Some(&self.server.url)
+        } else if profile == DEFAULT_PROFILE {
+            self.default_profile
+                .as_ref()
+                .map(|default| default.server.url.as_str())
+        } else {
+            self.profiles
+                .get(profile)
+                .map(|profile| profile.server.url.as_str())
+        }
+    }
+
+    /// Switch to `profile`, optionally creating it with default settings
+    pub fn switch_profile(&mut self, profile: &str, create: bool) -> Result<()> {
+        if create && profile != DEFAULT_PROFILE && !self.profiles.contains_key(profile) {
+            let defaults = Self::default();
+            self.profiles.insert(
+                profile.to_string(),
+                Profile {
+                    server: defaults.server,
+                    keys: defaults.keys,
+                },
+            );
+        }
+        self.activate(Some(profile))
+    }
+
+    /// Load `profile` into `server`/`keys`, first putting the active profile's settings back
+    fn activate(&mut self, profile: Option<&str>) -> Result<()> {
+        let profile = profile.filter(|name| *name != DEFAULT_PROFILE);
+        if let Some(name) = profile {
+            if !self.profiles.contains_key(name) {
+                anyhow::bail!(
+                    "Profile '{name}' not found. Available profiles: {}.
+Create it with 'sealbox config use {name} --create'",
+                    self.profile_names().join(", ")
+                );
+            }
+        }
+
+        if let Some(active) = self.active_profile.take() {
+            let active_settings = Profile {
+                server: self.server.clone(),
+                keys: self.keys.clone(),
+            };
+            self.profiles.insert(active, active_settings);
+            if let Some(default) = self.default_profile.take() {
+                self.server = default.server;
+                self.keys = default.keys;
+            }
+        }
+
+        if let Some(name) = profile {
+            let selected = self.profiles[name].clone();
+            self.default_profile = Some(Profile {
+                server: std::mem::replace(&mut self.server, selected.server),
+                keys: std::mem::replace(&mut self.keys, selected.keys),
+            });
+            self.active_profile = Some(name.to_string());
+        }
         Ok(())
     }
 
@@ -122,6 +319,20 @@ impl Config {
             self.server.token = token;
         }
 
+        if let Some(timeout) = std::env::var("SEALBOX_TIMEOUT")
+            .ok()
+            .and_then(|timeout| timeout.parse().ok())
+        {
+            self.server.timeout = timeout;
+        }
+
+        if let Some(retries) = std::env::var("SEALBOX_RETRIES")
+            .ok()
+            .and_then(|retries| retries.parse().ok())
+        {
+            self.server.retries = retries;
+        }
+
         if let Ok(public_key) = std::env::var("SEALBOX_PUBLIC_KEY") {
             self.keys.public_key_path = PathBuf::from(public_key);
         }
@@ -138,6 +349,11 @@ impl Config {
                 _ => {} // Keep default value
             }
         }
+
+        // https://no-color.org: any non-empty value disables color
+        if std::env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+            self.output.no_color = true;
+        }
     }
 
     pub fn validate(&self) -> Result<()> {
@@ -146,6 +362,9 @@ impl Config {
                 "Server authentication token not configured. Please set SEALBOX_TOKEN environment variable or run 'sealbox config set token <your-token>'"
             );
         }
+        if self.server.timeout == 0 {
+            anyhow::bail!("Server timeout must be at least 1 second");
+        }
 
         Ok(())
     }
@@ -153,6 +372,10 @@ impl Config {
     fn expand_paths(&mut self) -> Result<()> {
         self.keys.public_key_path = Self::expand_home_dir(&self.keys.public_key_path)?;
         self.keys.private_key_path = Self::expand_home_dir(&self.keys.private_key_path)?;
+        for identity in self.keys.identities.values_mut() {
+            identity.public_key_path = Self::expand_home_dir(&identity.public_key_path)?;
+            identity.private_key_path = Self::expand_home_dir(&identity.private_key_path)?;
+        }
         Ok(())
     }
 
@@ -192,6 +415,8 @@ mod tests {
         );
         assert_eq!(config.server.url, "http://127.0.0.1:8080");
         assert_eq!(config.server.token, "");
+        assert_eq!(config.server.timeout, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(config.server.retries, DEFAULT_RETRIES);
     }
 
     #[test]
@@ -210,14 +435,21 @@ mod tests {
             server: ServerConfig {
                 url: "http://test.com".to_string(),
                 token: "test-token".to_string(),
+                timeout: DEFAULT_TIMEOUT_SECS,
+                retries: DEFAULT_RETRIES,
             },
             keys: KeyConfig {
                 public_key_path: PathBuf::from("~/test/public.pem"),
                 private_key_path: PathBuf::from("~/test/private.pem"),
+                identities: BTreeMap::new(),
+                client_encrypt: false,
             },
             output: OutputConfig {
                 format: OutputFormat::Json,
+                no_color: false,
+                quiet: false,
             },
+            ..Config::default()
         };
 
         config.expand_paths().unwrap();
@@ -280,4 +512,151 @@ mod tests {
         // Should pass validation
         assert!(config.validate().is_ok());
     }
+
+    const PROFILES_TOML: &str = r#"
+[server]
+url = "http://127.0.0.1:8080"
+token = "dev-token"
+
+[keys]
+public_key_path = "/keys/dev/public.pem"
+private_key_path = "/keys/dev/private.pem"
+
+[output]
+format = "table"
+
+[profiles.prod-eu.server]
+url = "https://eu.sealbox.example"
+token = "eu-token"
+
+[profiles.prod-eu.keys]
+public_key_path = "/keys/eu/public.pem"
+private_key_path = "/keys/eu/private.pem"
+"#;
+
+    #[test]
+    fn test_profile_resolution() {
+        let config = Config::from_toml(PROFILES_TOML, None).unwrap();
+        assert_eq!(config.active_profile_name(), DEFAULT_PROFILE);
+        assert_eq!(config.server.token, "dev-token");
+        assert_eq!(config.profile_names(), vec!["default", "prod-eu"]);
+        assert_eq!(
+            config.profile_url("prod-eu"),
+            Some("https://eu.sealbox.example")
+        );
+
+        let config = Config::from_toml(PROFILES_TOML, Some("prod-eu")).unwrap();
+        assert_eq!(config.active_profile_name(), "prod-eu");
+        assert_eq!(config.server.url, "https://eu.sealbox.example");
+        assert_eq!(
+            config.keys.private_key_path,
+            PathBuf::from("/keys/eu/private.pem")
+        );
+        assert_eq!(config.profile_url("default"), Some("http://127.0.0.1:8080"));
+
+        // The profile chosen with `config use` applies when none is given explicitly
+        let with_current = format!("current_profile = \"prod-eu\"\n{PROFILES_TOML}");
+        let config = Config::from_toml(&with_current, None).unwrap();
+        assert_eq!(config.server.token, "eu-token");
+        let config = Config::from_toml(&with_current, Some("default")).unwrap();
+        assert_eq!(config.server.token, "dev-token");
+
+        assert!(Config::from_toml(PROFILES_TOML, Some("prod-us")).is_err());
+    }
+
+    #[test]
+    fn test_profile_changes_saved_to_their_profile() {
+        let mut config = Config::from_toml(PROFILES_TOML, Some("prod-eu")).unwrap();
+        config.server.token = "rotated-eu-token".to_string();
+
+        let saved = Config::from_toml(&config.to_toml().unwrap(), None).unwrap();
+        assert_eq!(saved.server.token, "dev-token");
+        assert_eq!(saved.profiles["prod-eu"].server.token, "rotated-eu-token");
+    }
+
+    #[test]
+    fn test_switch_profile() {
+        let mut config = Config::from_toml(PROFILES_TOML, None).unwrap();
+
+        assert!(config.switch_profile("staging", false).is_err());
+        config.switch_profile("staging", true).unwrap();
+        assert_eq!(config.active_profile_name(), "staging");
+        assert_eq!(config.server.token, "");
+        config.server.url = "https://staging.sealbox.example".to_string();
+
+        config.switch_profile("prod-eu", false).unwrap();
+        assert_eq!(config.server.token, "eu-token");
+
+        config.switch_profile(DEFAULT_PROFILE, false).unwrap();
+        assert_eq!(config.server.token, "dev-token");
+        assert_eq!(
+            config.profiles["staging"].server.url,
+            "https://staging.sealbox.example"
+        );
+    }
+
+    const LEGACY_KEYS_TOML: &str = r#"
+[server]
+url = "http://127.0.0.1:8080"
+token = "dev-token"
+
+[keys]
+public_key_path = "/keys/public.pem"
+private_key_path = "/keys/private.pem"
+
+[output]
+format = "table"
+"#;
+
+    #[test]
+    fn test_legacy_single_identity_config() {
+        let mut config = Config::from_toml(LEGACY_KEYS_TOML, None).unwrap();
+        assert!(config.keys.identities.is_empty());
+        assert_eq!(
+            config.keys.public_key_path,
+            PathBuf::from("/keys/public.pem")
+        );
+        assert!(!config.to_toml().unwrap().contains("identities"));
+
+        let err = config.keys.select_identity("ci").unwrap_err();
+        assert!(err.to_string().contains("Available identities: none"));
+    }
+
+    #[test]
+    fn test_multi_identity_config() {
+        let content = format!(
+            r#"{LEGACY_KEYS_TOML}
+[keys.identities.ci]
+public_key_path = "/keys/ci/public.pem"
+private_key_path = "/keys/ci/private.pem"
+
+[keys.identities.deploy]
+public_key_path = "/keys/deploy/public.pem"
+private_key_path = "/keys/deploy/private.pem"
+"#
+        );
+        let mut config = Config::from_toml(&content, None).unwrap();
+        assert_eq!(
+            config.keys.identities.keys().collect::<Vec<_>>(),
+            vec!["ci", "deploy"]
+        );
+        // Without --identity the top-level key pair is used
+        assert_eq!(
+            config.keys.private_key_path,
+            PathBuf::from("/keys/private.pem")
+        );
+
+        config.keys.select_identity("deploy").unwrap();
+        assert_eq!(
+            config.keys.public_key_path,
+            PathBuf::from("/keys/deploy/public.pem")
+        );
+        assert_eq!(
+            config.keys.private_key_path,
+            PathBuf::from("/keys/deploy/private.pem")
+        );
+
+        let err = config.keys.select_identity("prod").unwrap_err();
+        assert!(err.to_string().contains("ci, deploy"));
+    }
 }
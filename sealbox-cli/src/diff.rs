@@ -0,0 +1,82 @@
+/// One line of a line-based diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl DiffLine<'_> {
+    pub fn is_change(&self) -> bool {
+        !matches!(self, DiffLine::Same(_))
+    }
+}
+
+/// Line-based diff of `old` and `new` from their longest common subsequence.
+///
+/// Removed lines are listed before the added lines replacing them.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    lines.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "host=db\nuser=app\npassword=old\nport=5432";
+        let new = "host=db\nuser=app\npassword=new\nport=5432\nssl=true";
+
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                DiffLine::Same("host=db"),
+                DiffLine::Same("user=app"),
+                DiffLine::Removed("password=old"),
+                DiffLine::Added("password=new"),
+                DiffLine::Same("port=5432"),
+                DiffLine::Added("ssl=true"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_identical_and_empty() {
+        assert!(!diff_lines("a\nb", "a\nb").iter().any(DiffLine::is_change));
+        assert_eq!(diff_lines("", "a"), vec![DiffLine::Added("a")]);
+        assert_eq!(diff_lines("a", ""), vec![DiffLine::Removed("a")]);
+    }
+}
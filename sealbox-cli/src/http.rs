@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, Response, StatusCode, Url, header::RETRY_AFTER};
+
+use crate::config::Config;
+
+/// `User-Agent` sent with every request, so the server can record which tool saved a secret
+pub const USER_AGENT: &str = concat!("sealbox-cli/", env!("CARGO_PKG_VERSION"));
+
+/// Longest wait for a connection, so unreachable servers fail before the request timeout
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Wait before the first retry, doubled after every further failure
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Upper bound for the backoff between retries
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Upper bound for a server's `Retry-After`, so a misconfigured server cannot stall the CLI
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// HTTP client for talking to the Sealbox server, giving up on requests after
+/// `server.timeout` seconds
+pub fn client(config: &Config) -> Client {
+    let timeout = Duration::from_secs(config.server.timeout);
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .connect_timeout(CONNECT_TIMEOUT.min(timeout))
+        .build()
+        .expect("HTTP client configuration is valid")
+}
+
+/// URL of `/v1/secrets/{key}`, followed by `rest` as further path segments.
+///
+/// The key is percent-encoded as a single path segment, so namespaced keys such as
+/// `team/db/password` reach the secret routes instead of 404ing or, for keys like
+/// `app/meta`, silently hitting another route.
+pub fn secret_url(config: &Config, key: &str, rest: &[&str]) -> Result<Url> {
+    let mut url = Url::parse(&config.server.url)
+        .with_context(|| format!("Invalid server URL: {}", config.server.url))?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid server URL: {}", config.server.url))?
+        .pop_if_empty()
+        .extend(["v1", "secrets", key])
+        .extend(rest);
+    Ok(url)
+}
+
+/// Sending requests with retries for failures that are likely to go away on their own
+pub trait SendWithRetries {
+    /// Send the request, retrying up to `server.retries` times when the server could not be
+    /// reached or answered 429 or 5xx.
+    ///
+    /// Waits for the server's `Retry-After` if it sent one, otherwise backs off
+    /// exponentially with jitter. Other responses, including other 4xx, are returned at
+    /// once. Requests that time out after being sent are not retried, as the server may
+    /// already have acted on them.
+    #[allow(async_fn_in_trait)]
+    async fn send_with_retries(self, config: &Config) -> reqwest::Result<Response>;
+}
+
+impl SendWithRetries for RequestBuilder {
+    async fn send_with_retries(self, config: &Config) -> reqwest::Result<Response> {
+        let mut request = self;
+        let mut attempt = 0;
+        loop {
+            // Bodies that cannot be cloned are streamed, so they are only sent once
+            let retry = if attempt < config.server.retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let result = request.send().await;
+            let Some(retry) = retry else {
+                return result;
+            };
+
+            let wait = match &result {
+                Ok(response) if is_transient(response.status()) => {
+                    retry_after(response).unwrap_or_else(|| backoff(attempt))
+                }
+                Err(err) if err.is_connect() => backoff(attempt),
+                _ => return result,
+            };
+            tokio::time::sleep(wait).await;
+            request = retry;
+            attempt += 1;
+        }
+    }
+}
+
+/// Whether a response status means the same request may succeed later
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay the server asked for in `Retry-After`, if given in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds: u64 = response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+/// Exponential backoff before retry number `attempt + 1`, randomized between half and the
+/// full delay so that many clients failing together do not retry in lockstep
+fn backoff(attempt: u32) -> Duration {
+    let delay = INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF);
+    let millis = delay.as_millis() as u64;
+    Duration::from_millis(rand::thread_rng().gen_range(millis / 2..=millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Server answering each request with the next status line, repeating the last one
+    async fn spawn_server(statuses: &'static [&'static str]) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = [0u8; 4096];
+                let _ = stream.read(&mut buffer).await;
+                let index = counter.fetch_add(1, Ordering::SeqCst);
+                let status = statuses[index.min(statuses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn test_secret_url_encodes_key_as_one_segment() {
+        let mut config = Config::default();
+        config.server.url = "http://localhost:8080".to_string();
+        assert_eq!(
+            secret_url(&config, "team/db/password", &[])
+                .unwrap()
+                .as_str(),
+            "http://localhost:8080/v1/secrets/team%2Fdb%2Fpassword"
+        );
+        assert_eq!(
+            secret_url(&config, "app/meta", &["versions"])
+                .unwrap()
+                .as_str(),
+            "http://localhost:8080/v1/secrets/app%2Fmeta/versions"
+        );
+
+        // Servers behind a path prefix keep it
+        config.server.url = "https://example.com/sealbox/".to_string();
+        assert_eq!(
+            secret_url(&config, "db?x#y", &[]).unwrap().as_str(),
+            "https://example.com/sealbox/v1/secrets/db%3Fx%23y"
+        );
+    }
+
+    fn config_with_retries(retries: u32) -> Config {
+        let mut config = Config::default();
+        config.server.retries = retries;
+        config
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_are_retried() {
+        let (url, requests) =
+            spawn_server(&["503 Service Unavailable", "429 Too Many Requests", "200 OK"]).await;
+        let config = config_with_retries(3);
+        let response = client(&config)
+            .put(&url)
+            .body("value")
+            .send_with_retries(&config)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+        // Once the retries are used up, the last response is returned
+        let (url, requests) = spawn_server(&["503 Service Unavailable"]).await;
+        let config = config_with_retries(1);
+        let response = client(&config)
+            .get(&url)
+            .send_with_retries(&config)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        let (url, requests) = spawn_server(&["400 Bad Request", "200 OK"]).await;
+        let config = config_with_retries(3);
+        let response = client(&config)
+            .get(&url)
+            .send_with_retries(&config)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        for attempt in 0..3 {
+            let full = INITIAL_BACKOFF * 2u32.pow(attempt);
+            let delay = backoff(attempt);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "{delay:?} for {attempt}"
+            );
+        }
+        assert!(backoff(40) <= MAX_BACKOFF);
+    }
+}
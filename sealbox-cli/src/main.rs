@@ -1,11 +1,19 @@
 mod commands;
 mod config;
+mod diff;
+mod http;
 mod output;
+mod server_error;
+mod server_info;
 
-use crate::commands::{config_commands, key_commands, secret_commands};
+use crate::commands::{
+    admin_commands, audit_commands, config_commands, key_commands, secret_commands,
+};
 use crate::config::{Config, OutputFormat};
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use sealbox_server::{crypto::master_key::KeyAlgorithm, repo::MasterKeyStatus};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "sealbox")]
@@ -35,9 +43,33 @@ struct Cli {
     #[arg(long, global = true)]
     private_key: Option<String>,
 
+    /// Seconds to wait for the server before a request fails (default 30)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+
+    /// Times to retry requests after connection errors, 429 or 5xx responses (default 3)
+    #[arg(long, global = true)]
+    retries: Option<u32>,
+
     /// Output format
     #[arg(long, global = true, value_enum)]
     output: Option<OutputFormatArg>,
+
+    /// Configuration profile to use (also SEALBOX_PROFILE)
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Named key pair from [keys.identities] to use
+    #[arg(long, global = true)]
+    identity: Option<String>,
+
+    /// Disable colored output (also NO_COLOR; off automatically when not a terminal)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Print only data and errors, without success, warning or progress messages
+    #[arg(long, global = true)]
+    quiet: bool,
 }
 
 #[derive(clap::ValueEnum, Clone)]
@@ -57,6 +89,38 @@ impl From<OutputFormatArg> for OutputFormat {
     }
 }
 
+#[derive(clap::ValueEnum, Clone)]
+enum KeyAlgorithmArg {
+    Rsa,
+    X25519,
+}
+
+impl From<KeyAlgorithmArg> for KeyAlgorithm {
+    fn from(arg: KeyAlgorithmArg) -> Self {
+        match arg {
+            KeyAlgorithmArg::Rsa => KeyAlgorithm::Rsa,
+            KeyAlgorithmArg::X25519 => KeyAlgorithm::X25519,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone)]
+enum MasterKeyStatusArg {
+    Active,
+    Retired,
+    Disabled,
+}
+
+impl From<MasterKeyStatusArg> for MasterKeyStatus {
+    fn from(arg: MasterKeyStatusArg) -> Self {
+        match arg {
+            MasterKeyStatusArg::Active => MasterKeyStatus::Active,
+            MasterKeyStatusArg::Retired => MasterKeyStatus::Retired,
+            MasterKeyStatusArg::Disabled => MasterKeyStatus::Disabled,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Manage configuration
@@ -74,6 +138,22 @@ enum Commands {
         #[command(subcommand)]
         command: SecretCommands,
     },
+    /// Read the server's audit log (admin)
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommands,
+    },
+    /// Server administration (admin)
+    Admin {
+        #[command(subcommand)]
+        command: AdminCommands,
+    },
+    /// Print a shell completion script to stdout
+    Completion {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
 }
 
 #[derive(Subcommand)]
@@ -108,6 +188,18 @@ enum ConfigCommands {
         #[arg(long)]
         force: bool,
     },
+    /// Make a profile the default for later commands
+    Use {
+        /// Profile name
+        name: String,
+        /// Create the profile with default settings if it does not exist
+        #[arg(long)]
+        create: bool,
+    },
+    /// List configured profiles
+    ListProfiles,
+    /// Check that the server, token and key pair actually work
+    Validate,
 }
 
 #[derive(Subcommand)]
@@ -120,25 +212,72 @@ enum KeyCommands {
         /// Private key file path
         #[arg(long)]
         private_key_path: Option<String>,
+        /// Key algorithm
+        #[arg(long, value_enum, default_value = "rsa")]
+        algorithm: KeyAlgorithmArg,
+        /// RSA key size in bits (2048, 3072 or 4096); ignored for x25519
+        #[arg(long, default_value_t = sealbox_server::crypto::master_key::DEFAULT_KEY_BITS)]
+        bits: usize,
         /// Overwrite existing key files
         #[arg(long)]
         force: bool,
+        /// Encrypt the private key file with a passphrase (prompted, or SEALBOX_KEY_PASSPHRASE)
+        #[arg(long)]
+        passphrase: bool,
     },
     /// Register public key to server
-    Register,
-    /// List master keys on server
-    List,
+    Register {
+        /// TTL in seconds for secrets saved without one while this key is active
+        #[arg(long)]
+        default_ttl: Option<i64>,
+    },
+    /// List master keys on server, newest first
+    List {
+        /// Only list keys with this status
+        #[arg(long, value_enum)]
+        status: Option<MasterKeyStatusArg>,
+        /// Only list keys whose description contains this text
+        #[arg(long)]
+        description_contains: Option<String>,
+        /// Maximum number of keys to list
+        #[arg(long)]
+        limit: Option<u32>,
+        /// Number of matching keys to skip
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+    },
+    /// List master keys no secret read has used recently, so they can be disabled
+    Stale {
+        /// Report keys unused for at least this many days
+        #[arg(long, default_value_t = 90)]
+        days: u32,
+    },
     /// Rotate master key
     Rotate {
-        /// New master key ID
-        #[arg(long)]
-        new_key_id: String,
+        /// New master key ID (already registered on the server)
+        #[arg(long, required_unless_present = "new_public_key")]
+        new_key_id: Option<String>,
         /// Old master key ID
         #[arg(long)]
         old_key_id: String,
+        /// Public key file of an externally generated key pair to rotate to
+        #[arg(long, conflicts_with = "new_key_id", requires = "new_private_key")]
+        new_public_key: Option<String>,
+        /// Private key file of an externally generated key pair to rotate to
+        #[arg(long, requires = "new_public_key")]
+        new_private_key: Option<String>,
+        /// Show which secrets would be re-encrypted and which files replaced, changing nothing
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Check key status
     Status,
+    /// Print the SHA-256 fingerprint of the local public key, as shown by `key list`
+    Fingerprint {
+        /// Public key file path (defaults to keys.public_key_path)
+        #[arg(long)]
+        public_key_path: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -149,9 +288,24 @@ enum SecretCommands {
         key: String,
         /// Secret value (read from stdin if not provided)
         value: Option<String>,
+        /// Read the secret value from a file, preserving its content byte for byte
+        #[arg(long, value_name = "PATH", conflicts_with = "value")]
+        from_file: Option<String>,
         /// Time to live in seconds
         #[arg(long)]
         ttl: Option<i64>,
+        /// Tag to attach to the secret (repeatable, e.g. --tag env=prod)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Plaintext note about the secret; kept from the previous version if omitted, "" clears it
+        #[arg(long)]
+        description: Option<String>,
+        /// Compress the value with zstd before it is encrypted
+        #[arg(long)]
+        compress: bool,
+        /// Encrypt the value locally so the server never sees it (also keys.client_encrypt)
+        #[arg(long)]
+        client_encrypt: bool,
     },
     /// Get secret
     Get {
@@ -160,17 +314,114 @@ enum SecretCommands {
         /// Specific version number
         #[arg(long)]
         version: Option<i32>,
+        /// Write the decrypted bytes to stdout as-is, without formatting
+        #[arg(long)]
+        raw: bool,
+        /// Parse the secret as JSON and return only this dotted path, e.g. `credentials.password`
+        #[arg(long)]
+        field: Option<String>,
+        /// Remember the secret's ETag in this file and report "unchanged" if it has not changed since
+        #[arg(long)]
+        etag_file: Option<PathBuf>,
+    },
+    /// Print a secret, then print it again every time a new version is saved
+    Watch {
+        /// Secret key name
+        key: String,
+        /// Seconds between checks when the server cannot hold a watch request open
+        #[arg(long, default_value_t = DEFAULT_WATCH_POLL_SECS)]
+        poll_interval: u64,
+    },
+    /// Show what changed between two versions of a secret
+    Diff {
+        /// Secret key name
+        key: String,
+        /// Version to compare from
+        #[arg(long)]
+        from: i32,
+        /// Version to compare to
+        #[arg(long)]
+        to: i32,
+    },
+    /// Copy the latest version of a secret to a new key
+    Copy {
+        /// Source secret key name
+        source: String,
+        /// Destination secret key name
+        dest: String,
+        /// Give the copy the source's remaining time to live
+        #[arg(long)]
+        keep_ttl: bool,
+    },
+    /// Change when a secret expires without re-encrypting it
+    Touch {
+        /// Secret key name
+        key: String,
+        /// New time to live in seconds, counted from now
+        #[arg(
+            long,
+            required_unless_present = "permanent",
+            conflicts_with = "permanent"
+        )]
+        ttl: Option<i64>,
+        /// Remove the expiry so the secret never expires
+        #[arg(long)]
+        permanent: bool,
+        /// Version to change (default: latest)
+        #[arg(long)]
+        version: Option<i32>,
+    },
+    /// Rename a secret, moving all of its versions to a new key
+    Rename {
+        /// Current secret key name
+        #[arg(
+            required_unless_present = "map",
+            conflicts_with = "map",
+            requires = "new"
+        )]
+        old: Option<String>,
+        /// New secret key name
+        new: Option<String>,
+        /// JSON file mapping old key names to new ones, to rename many secrets at once
+        #[arg(long)]
+        map: Option<String>,
     },
-    /// Delete secret
+    /// Delete secret (moved to the server's recycle bin unless --purge or --shred is given)
     Delete {
         /// Secret key name
         key: String,
         /// Version number
         #[arg(long)]
         version: i32,
+        /// Remove the version permanently instead of moving it to the recycle bin
+        #[arg(long)]
+        purge: bool,
+        /// Remove the version permanently after zeroing its ciphertext and wrapped data key
+        #[arg(long, conflicts_with = "purge")]
+        shred: bool,
+        /// Current code from your authenticator app, for servers requiring TOTP on permanent deletes
+        #[arg(long)]
+        totp: Option<String>,
+    },
+    /// Exit with status 0 if the secret exists and 1 if not, printing nothing by default
+    Exists {
+        /// Secret key name
+        key: String,
+        /// Print the latest version's metadata, or a note that the secret does not exist
+        #[arg(long)]
+        verbose: bool,
+    },
+    /// List secret keys with their latest version
+    List {
+        /// Only list secrets carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+        /// Only list secrets in this namespace, the part of the key before the first `/`
+        #[arg(long)]
+        namespace: Option<String>,
     },
-    /// List all secret keys (requires server support)
-    List,
+    /// List namespaces with the number of secrets in each
+    Namespaces,
     /// View secret version history
     History {
         /// Secret key name
@@ -180,29 +431,109 @@ enum SecretCommands {
     Import {
         /// Input file path
         file: String,
-        /// File format
+        /// File format: json, yaml, toml or csv (with a `key,value` header row)
         #[arg(long, default_value = "json")]
         format: String,
+        /// Abort without sending anything if any entry is not a string or has an invalid key
+        #[arg(long)]
+        strict: bool,
     },
-    /// Export secrets to file
+    /// Export secrets to a file, or to one file per secret with --output-dir
     Export {
         /// Output file path
-        file: String,
-        /// Key pattern matching
+        #[arg(required_unless_present = "output_dir", conflicts_with = "output_dir")]
+        file: Option<String>,
+        /// Write each secret to `<dir>/<key>` instead, creating subdirectories for `/` in keys
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+        /// Shell-style key glob (`*` and `?` within a `/` segment, `**` across, `[abc]`)
         #[arg(long)]
         keys: Option<String>,
-        /// Output format
+        /// Output format: json, yaml, ndjson (one `{"key", "value"}` object per line), toml, csv,
+        /// env (dotenv) or shell (`export` lines to source)
         #[arg(long, default_value = "json")]
         format: String,
+        /// Abort if the decrypted secrets exceed this many bytes in total
+        #[arg(long, default_value_t = DEFAULT_MAX_EXPORT_BYTES)]
+        max_total_bytes: u64,
+        /// Secrets fetched from the server at the same time
+        #[arg(long, default_value_t = DEFAULT_EXPORT_CONCURRENCY)]
+        concurrency: usize,
     },
 }
 
+#[derive(Subcommand)]
+enum AdminCommands {
+    /// Download a consistent copy of the server database
+    Backup {
+        /// Output file path
+        #[arg(long, default_value = "sealbox-backup.db")]
+        out: PathBuf,
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Delete every expired secret version now instead of waiting for the sweeper
+    CleanupExpired {
+        /// Current code from your authenticator app, for servers requiring TOTP
+        #[arg(long)]
+        totp: Option<String>,
+    },
+    /// Check every stored secret version for corrupt ciphertexts or wrapped data keys
+    Scan,
+}
+
+#[derive(Subcommand)]
+enum AuditCommands {
+    /// Export audit entries, oldest first, for ingestion into a SIEM
+    Export {
+        /// Output file path (stdout if omitted)
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Output format: csv or json
+        #[arg(long, default_value = "csv")]
+        format: String,
+        /// Only entries recorded at or after this Unix time
+        #[arg(long)]
+        since: Option<i64>,
+        /// Only entries recorded before this Unix time
+        #[arg(long)]
+        until: Option<i64>,
+        /// Only entries for this action, e.g. `secret.get`
+        #[arg(long)]
+        action: Option<String>,
+        /// Only entries addressing this secret key
+        #[arg(long)]
+        key: Option<String>,
+    },
+}
+
+/// Default seconds between polls of `secret watch` against servers without long-polling
+const DEFAULT_WATCH_POLL_SECS: u64 = 5;
+
+/// Default cap on the total size of decrypted secrets held in memory by `secret export`
+const DEFAULT_MAX_EXPORT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Default number of secrets `secret export` fetches at once
+const DEFAULT_EXPORT_CONCURRENCY: usize = 8;
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // Needs no configuration, so it works before the CLI is set up
+    if let Commands::Completion { shell } = cli.command {
+        clap_complete::generate(
+            shell,
+            &mut Cli::command(),
+            env!("CARGO_BIN_NAME"),
+            &mut std::io::stdout(),
+        );
+        return Ok(());
+    }
+
     // Load configuration
-    let mut config = Config::load()?;
+    let mut config = Config::load(cli.profile.as_deref())?;
 
     // Command line arguments override configuration
     if let Some(url) = cli.url {
@@ -211,6 +542,16 @@ async fn main() -> Result<()> {
     if let Some(token) = cli.token {
         config.server.token = token;
     }
+    if let Some(timeout) = cli.timeout {
+        config.server.timeout = timeout;
+    }
+    if let Some(retries) = cli.retries {
+        config.server.retries = retries;
+    }
+    // Explicit key paths below still win over the identity's
+    if let Some(identity) = cli.identity {
+        config.keys.select_identity(&identity)?;
+    }
     if let Some(public_key) = cli.public_key {
         config.keys.public_key_path = public_key.into();
     }
@@ -220,11 +561,18 @@ async fn main() -> Result<()> {
     if let Some(output) = cli.output {
         config.output.format = output.into();
     }
+    if cli.no_color {
+        config.output.no_color = true;
+    }
+    config.output.quiet = cli.quiet;
 
     // Execute command
     match cli.command {
         Commands::Config { command } => config_commands::handle_command(command, &mut config).await,
         Commands::Key { command } => key_commands::handle_command(command, &config).await,
         Commands::Secret { command } => secret_commands::handle_command(command, &config).await,
+        Commands::Audit { command } => audit_commands::handle_command(command, &config).await,
+        Commands::Admin { command } => admin_commands::handle_command(command, &config).await,
+        Commands::Completion { .. } => unreachable!("handled before loading the configuration"),
     }
 }
@@ -1,15 +1,94 @@
-use crate::config::OutputFormat;
+use crate::{
+    commands::key_commands::{KeyStatus, ServerKeyStatus},
+    config::OutputFormat,
+    diff::DiffLine,
+};
 use anyhow::Result;
-use comfy_table::{Table, presets::UTF8_FULL};
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL};
 use serde_json::{Value, json};
+use std::io::IsTerminal;
+use yaml_rust2::{Yaml, YamlEmitter, yaml::Hash};
 
 pub struct OutputManager {
     format: OutputFormat,
+    color: bool,
+    quiet: bool,
+}
+
+/// Kind of status message, deciding its icon and color
+#[derive(Debug, Clone, Copy)]
+enum MessageLevel {
+    Success,
+    Error,
+    Warning,
+    Info,
+}
+
+impl MessageLevel {
+    fn prefix(self) -> &'static str {
+        match self {
+            MessageLevel::Success => "✅ ",
+            MessageLevel::Error => "❌ ",
+            MessageLevel::Warning => "⚠️  ",
+            MessageLevel::Info => "ℹ️  ",
+        }
+    }
+
+    /// ANSI SGR foreground color code
+    fn color_code(self) -> u8 {
+        match self {
+            MessageLevel::Success => 32,
+            MessageLevel::Error => 31,
+            MessageLevel::Warning => 33,
+            MessageLevel::Info => 36,
+        }
+    }
 }
 
 impl OutputManager {
     pub fn new(format: OutputFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            color: true,
+            quiet: false,
+        }
+    }
+
+    /// Allow or forbid colors; even when allowed they are only used on a terminal
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// In quiet mode only data and errors are printed; success, warning and info messages are not
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Whether messages of this level are printed at all
+    fn shows(&self, level: MessageLevel) -> bool {
+        !self.quiet || matches!(level, MessageLevel::Error)
+    }
+
+    /// A table in the standard style, without colors when they are disabled
+    fn new_table(&self) -> Table {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL);
+        if !self.color {
+            table.force_no_tty();
+        }
+        table
+    }
+
+    /// Status message line, colored only if colors are allowed and it goes to a terminal
+    fn render_message(&self, level: MessageLevel, message: &str, terminal: bool) -> String {
+        let line = format!("{}{message}", level.prefix());
+        if self.color && terminal {
+            format!("\x1b[{}m{line}\x1b[0m", level.color_code())
+        } else {
+            line
+        }
     }
 
     pub fn print_value(&self, value: &Value) -> Result<()> {
@@ -18,9 +97,7 @@ impl OutputManager {
                 println!("{}", serde_json::to_string_pretty(value)?);
             }
             OutputFormat::Yaml => {
-                // Simplified YAML output, using JSON as replacement for now
-                // In actual project, serde_yaml dependency can be added
-                println!("{}", serde_json::to_string_pretty(value)?);
+                println!("{}", to_yaml(value)?);
             }
             OutputFormat::Table => {
                 self.print_as_table(value)?;
@@ -34,8 +111,10 @@ impl OutputManager {
         key: &str,
         value: &str,
         version: Option<i32>,
-        ttl: Option<i64>,
+        expires_at: Option<i64>,
     ) -> Result<()> {
+        let now = unix_now();
+        let ttl = remaining_seconds(expires_at, now);
         match self.format {
             OutputFormat::Json => {
                 let mut obj = json!({
@@ -45,6 +124,9 @@ impl OutputManager {
                 if let Some(v) = version {
                     obj["version"] = json!(v);
                 }
+                if let Some(e) = expires_at {
+                    obj["expires_at"] = json!(e);
+                }
                 if let Some(t) = ttl {
                     obj["ttl"] = json!(t);
                 }
@@ -56,13 +138,15 @@ impl OutputManager {
                 if let Some(v) = version {
                     println!("version: {v}");
                 }
+                if let Some(e) = expires_at {
+                    println!("expires_at: {e}");
+                }
                 if let Some(t) = ttl {
                     println!("ttl: {t}");
                 }
             }
             OutputFormat::Table => {
-                let mut table = Table::new();
-                table.load_preset(UTF8_FULL);
+                let mut table = self.new_table();
                 table.set_header(vec!["Property", "Value"]);
 
                 table.add_row(vec!["Key", key]);
@@ -70,9 +154,7 @@ impl OutputManager {
                 if let Some(v) = version {
                     table.add_row(vec!["Version", &v.to_string()]);
                 }
-                if let Some(t) = ttl {
-                    table.add_row(vec!["TTL", &t.to_string()]);
-                }
+                table.add_row(vec![Cell::new("Expires"), expiry_cell(expires_at, now)]);
 
                 println!("{table}");
             }
@@ -89,11 +171,18 @@ impl OutputManager {
                 for key in keys {
                     println!("- id: {}", key.id);
                     println!("  status: {:?}", key.status);
+                    println!("  algorithm: {}", key.algorithm);
+                    if let Some(fingerprint) = &key.fingerprint {
+                        println!("  fingerprint: {fingerprint}");
+                    }
                     println!("  created_at: {}", key.created_at);
+                    if let Some(last_used_at) = key.last_used_at {
+                        println!("  last_used_at: {last_used_at}");
+                    }
                     println!(
                         "  public_key: {}",
-                        if key.public_key == "[HIDDEN]" {
-                            "[HIDDEN]"
+                        if key.public_key == sealbox_server::repo::HIDDEN_PUBLIC_KEY {
+                            sealbox_server::repo::HIDDEN_PUBLIC_KEY
                         } else {
                             &key.public_key
                         }
@@ -102,27 +191,234 @@ impl OutputManager {
                 }
             }
             OutputFormat::Table => {
-                let mut table = Table::new();
-                table.load_preset(UTF8_FULL);
-                table.set_header(vec!["ID", "Status", "Created At", "Public Key"]);
+                let mut table = self.new_table();
+                table.set_header(vec![
+                    "ID",
+                    "Status",
+                    "Algorithm",
+                    "Created At",
+                    "Last Used",
+                    "Fingerprint",
+                ]);
 
                 for key in keys {
-                    let created_at = time::OffsetDateTime::from_unix_timestamp(key.created_at)
-                        .map(|dt| {
-                            dt.format(&time::format_description::well_known::Rfc2822)
-                                .unwrap_or_else(|_| dt.to_string())
-                        })
-                        .unwrap_or_else(|_| key.created_at.to_string());
-
                     table.add_row(vec![
                         key.id.to_string(),
                         format!("{:?}", key.status),
-                        created_at,
-                        if key.public_key == "[HIDDEN]" {
-                            "[HIDDEN]".to_string()
-                        } else {
-                            format!("{}...", &key.public_key[..20.min(key.public_key.len())])
-                        },
+                        key.algorithm.to_string(),
+                        format_timestamp(key.created_at),
+                        key.last_used_at
+                            .map_or_else(|| "Never".to_string(), format_timestamp),
+                        // The public key itself is hidden in listings; servers predating
+                        // fingerprints report neither
+                        key.fingerprint.clone().unwrap_or_else(|| "-".to_string()),
+                    ]);
+                }
+
+                println!("{table}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn print_key_status(&self, status: &KeyStatus) -> Result<()> {
+        if !matches!(self.format, OutputFormat::Table) {
+            return self.print_value(&serde_json::to_value(status)?);
+        }
+
+        let found = |exists: bool| if exists { "found" } else { "missing" };
+        let local = &status.local_keys;
+        let key_pair = match (local.key_pair_valid, &local.validation_error) {
+            (Some(true), _) => "valid".to_string(),
+            (_, Some(problem)) => format!("invalid: {problem}"),
+            _ => local
+                .public_key_error
+                .iter()
+                .chain(&local.private_key_error)
+                .next()
+                .cloned()
+                .unwrap_or_else(|| "not checked".to_string()),
+        };
+
+        let mut table = self.new_table();
+        table.set_header(vec!["Property", "Value"]);
+        table.add_row(vec![
+            "Public key".to_string(),
+            format!(
+                "{} ({})",
+                local.public_key_path,
+                found(local.public_key_exists)
+            ),
+        ]);
+        table.add_row(vec![
+            "Private key".to_string(),
+            format!(
+                "{} ({})",
+                local.private_key_path,
+                found(local.private_key_exists)
+            ),
+        ]);
+        table.add_row(vec!["Key pair".to_string(), key_pair]);
+        table.add_row(vec![
+            "Server keys".to_string(),
+            match &status.server_keys {
+                ServerKeyStatus::Listed { count, .. } => format!("{count} registered"),
+                ServerKeyStatus::Unavailable { error } => error.clone(),
+            },
+        ]);
+        if let Some(due) = &status.rotation_due {
+            table.add_row(vec![
+                "Rotation due".to_string(),
+                if due.keys.is_empty() {
+                    "none".to_string()
+                } else {
+                    format!("{} ({})", due.count, due.keys.join(", "))
+                },
+            ]);
+        }
+
+        println!("{table}");
+        Ok(())
+    }
+
+    pub fn print_secret_versions(
+        &self,
+        key: &str,
+        versions: &[sealbox_server::repo::SecretInfo],
+    ) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let obj = json!({
+                    "key": key,
+                    "versions": versions,
+                });
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            }
+            OutputFormat::Yaml => {
+                println!("key: {key}");
+                println!("versions:");
+                for version in versions {
+                    println!("  - version: {}", version.version);
+                    println!("    created_at: {}", version.created_at);
+                    println!("    updated_at: {}", version.updated_at);
+                    if let Some(expires_at) = version.expires_at {
+                        println!("    expires_at: {expires_at}");
+                    }
+                    if let Some(user_agent) = &version.user_agent {
+                        println!("    user_agent: {user_agent}");
+                    }
+                }
+            }
+            OutputFormat::Table => {
+                let mut table = self.new_table();
+                table.set_header(vec![
+                    "Version",
+                    "Created At",
+                    "Updated At",
+                    "Expires At",
+                    "Saved By",
+                ]);
+
+                let now = unix_now();
+                for version in versions {
+                    table.add_row(vec![
+                        Cell::new(version.version),
+                        Cell::new(format_timestamp(version.created_at)),
+                        Cell::new(format_timestamp(version.updated_at)),
+                        expiry_cell(version.expires_at, now),
+                        Cell::new(version.user_agent.as_deref().unwrap_or("")),
+                    ]);
+                }
+
+                println!("{table}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn print_secret_list(&self, secrets: &[sealbox_server::repo::SecretInfo]) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let obj = json!({ "secrets": secrets });
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            }
+            OutputFormat::Yaml => {
+                println!("secrets:");
+                for secret in secrets {
+                    println!("  - key: {}", secret.key);
+                    println!("    version: {}", secret.version);
+                    println!("    tags: [{}]", secret.tags.join(", "));
+                    if let Some(description) = &secret.description {
+                        println!("    description: {description}");
+                    }
+                    println!("    updated_at: {}", secret.updated_at);
+                    if let Some(expires_at) = secret.expires_at {
+                        println!("    expires_at: {expires_at}");
+                    }
+                }
+            }
+            OutputFormat::Table => {
+                if secrets.is_empty() {
+                    self.print_info("No secrets found");
+                    return Ok(());
+                }
+
+                let mut table = self.new_table();
+                table.set_header(vec![
+                    "Key",
+                    "Version",
+                    "Tags",
+                    "Description",
+                    "Updated At",
+                    "Expires At",
+                ]);
+
+                let now = unix_now();
+                for secret in secrets {
+                    table.add_row(vec![
+                        Cell::new(&secret.key),
+                        Cell::new(secret.version),
+                        Cell::new(secret.tags.join(", ")),
+                        Cell::new(secret.description.as_deref().unwrap_or("")),
+                        Cell::new(format_timestamp(secret.updated_at)),
+                        expiry_cell(secret.expires_at, now),
+                    ]);
+                }
+
+                println!("{table}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn print_namespaces(
+        &self,
+        namespaces: &[sealbox_server::repo::NamespaceCount],
+    ) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let obj = json!({ "namespaces": namespaces });
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            }
+            OutputFormat::Yaml => {
+                println!("namespaces:");
+                for namespace in namespaces {
+                    println!("  - namespace: {}", namespace.namespace);
+                    println!("    secrets: {}", namespace.secrets);
+                }
+            }
+            OutputFormat::Table => {
+                if namespaces.is_empty() {
+                    self.print_info("No namespaces found");
+                    return Ok(());
+                }
+
+                let mut table = self.new_table();
+                table.set_header(vec!["Namespace", "Secrets"]);
+                for namespace in namespaces {
+                    table.add_row(vec![
+                        Cell::new(&namespace.namespace),
+                        Cell::new(namespace.secrets),
                     ]);
                 }
 
@@ -133,8 +429,7 @@ impl OutputManager {
     }
 
     fn print_as_table(&self, value: &Value) -> Result<()> {
-        let mut table = Table::new();
-        table.load_preset(UTF8_FULL);
+        let mut table = self.new_table();
 
         match value {
             Value::Object(obj) => {
@@ -194,20 +489,165 @@ impl OutputManager {
         }
     }
 
+    /// Print a line diff between two versions of a secret, `-` lines from `from` and `+` lines from `to`
+    pub fn print_diff(&self, key: &str, from: i32, to: i32, lines: &[DiffLine]) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                let lines: Vec<Value> = lines
+                    .iter()
+                    .map(|line| match line {
+                        DiffLine::Same(text) => json!({"change": "same", "line": text}),
+                        DiffLine::Removed(text) => json!({"change": "removed", "line": text}),
+                        DiffLine::Added(text) => json!({"change": "added", "line": text}),
+                    })
+                    .collect();
+                let obj = json!({
+                    "key": key,
+                    "from": from,
+                    "to": to,
+                    "lines": lines,
+                });
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+            }
+            OutputFormat::Yaml | OutputFormat::Table => {
+                let colored = self.color && std::io::stdout().is_terminal();
+                println!("--- {key} (version {from})");
+                println!("+++ {key} (version {to})");
+                for line in lines {
+                    let (prefix, text, color_code) = match line {
+                        DiffLine::Same(text) => (' ', text, None),
+                        DiffLine::Removed(text) => ('-', text, Some(31)),
+                        DiffLine::Added(text) => ('+', text, Some(32)),
+                    };
+                    match color_code {
+                        Some(code) if colored => println!("\x1b[{code}m{prefix}{text}\x1b[0m"),
+                        _ => println!("{prefix}{text}"),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn print_success(&self, message: &str) {
-        println!("✅ {message}");
+        if !self.shows(MessageLevel::Success) {
+            return;
+        }
+        let terminal = std::io::stdout().is_terminal();
+        println!(
+            "{}",
+            self.render_message(MessageLevel::Success, message, terminal)
+        );
     }
 
     pub fn print_error(&self, message: &str) {
-        eprintln!("❌ {message}");
+        let terminal = std::io::stderr().is_terminal();
+        eprintln!(
+            "{}",
+            self.render_message(MessageLevel::Error, message, terminal)
+        );
     }
 
     pub fn print_warning(&self, message: &str) {
-        println!("⚠️  {message}");
+        if !self.shows(MessageLevel::Warning) {
+            return;
+        }
+        let terminal = std::io::stdout().is_terminal();
+        println!(
+            "{}",
+            self.render_message(MessageLevel::Warning, message, terminal)
+        );
     }
 
     pub fn print_info(&self, message: &str) {
-        println!("ℹ️  {message}");
+        if !self.shows(MessageLevel::Info) {
+            return;
+        }
+        let terminal = std::io::stdout().is_terminal();
+        println!(
+            "{}",
+            self.render_message(MessageLevel::Info, message, terminal)
+        );
+    }
+}
+
+/// Format a Unix timestamp for human-readable table output
+fn format_timestamp(timestamp: i64) -> String {
+    time::OffsetDateTime::from_unix_timestamp(timestamp)
+        .map(|dt| {
+            dt.format(&time::format_description::well_known::Rfc2822)
+                .unwrap_or_else(|_| dt.to_string())
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+/// Expiries closer than this are highlighted in tables
+const EXPIRY_WARNING_SECONDS: i64 = 24 * 60 * 60;
+
+fn unix_now() -> i64 {
+    time::OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// Seconds left before `expires_at`, clamped at zero; `None` for secrets that never expire
+fn remaining_seconds(expires_at: Option<i64>, now: i64) -> Option<i64> {
+    expires_at.map(|expires_at| (expires_at - now).max(0))
+}
+
+/// Human-friendly remaining time such as "expires in 3h"
+fn describe_remaining(seconds: i64) -> String {
+    let (amount, unit) = match seconds {
+        ..=0 => return "expired".to_string(),
+        1..60 => (seconds, "s"),
+        60..3600 => (seconds / 60, "m"),
+        3600..86400 => (seconds / 3600, "h"),
+        _ => (seconds / 86400, "d"),
+    };
+    format!("expires in {amount}{unit}")
+}
+
+/// Expiry column showing the absolute time and time left, highlighted within a day of expiring
+fn expiry_cell(expires_at: Option<i64>, now: i64) -> Cell {
+    let Some(expires_at) = expires_at else {
+        return Cell::new("Never");
+    };
+
+    let remaining = expires_at - now;
+    let cell = Cell::new(format!(
+        "{} ({})",
+        format_timestamp(expires_at),
+        describe_remaining(remaining)
+    ));
+    if remaining < EXPIRY_WARNING_SECONDS {
+        cell.fg(Color::Yellow)
+    } else {
+        cell
+    }
+}
+
+/// Render `value` as a YAML document, without the leading `---` line
+fn to_yaml(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out).dump(&yaml_from_json(value))?;
+    Ok(out.trim_start_matches("---\n").to_string())
+}
+
+fn yaml_from_json(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(yaml_from_json).collect()),
+        Value::Object(fields) => {
+            let mut hash = Hash::new();
+            for (key, field) in fields {
+                hash.insert(Yaml::String(key.clone()), yaml_from_json(field));
+            }
+            Yaml::Hash(hash)
+        }
     }
 }
 
@@ -216,6 +656,50 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_messages_colored_only_on_terminal() {
+        let output = OutputManager::new(OutputFormat::Table);
+        assert_eq!(
+            output.render_message(MessageLevel::Success, "done", true),
+            "\x1b[32m✅ done\x1b[0m"
+        );
+        assert_eq!(
+            output.render_message(MessageLevel::Success, "done", false),
+            "✅ done"
+        );
+    }
+
+    #[test]
+    fn test_no_color_disables_ansi_codes() {
+        let output = OutputManager::new(OutputFormat::Table).with_color(false);
+        for level in [
+            MessageLevel::Success,
+            MessageLevel::Error,
+            MessageLevel::Warning,
+            MessageLevel::Info,
+        ] {
+            assert!(!output.render_message(level, "done", true).contains('\x1b'));
+        }
+
+        let mut table = output.new_table();
+        table.add_row(vec![expiry_cell(Some(60), 0)]);
+        assert!(!table.to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_quiet_shows_only_errors() {
+        let output = OutputManager::new(OutputFormat::Table).with_quiet(true);
+        assert!(output.shows(MessageLevel::Error));
+        for level in [
+            MessageLevel::Success,
+            MessageLevel::Warning,
+            MessageLevel::Info,
+        ] {
+            assert!(!output.shows(level));
+            assert!(OutputManager::new(OutputFormat::Table).shows(level));
+        }
+    }
+
     #[test]
     fn test_print_value_json() {
         let output = OutputManager::new(OutputFormat::Json);
@@ -231,11 +715,120 @@ mod tests {
 
         assert!(
             output
-                .print_secret("test-key", "test-value", Some(1), Some(3600))
+                .print_secret("test-key", "test-value", Some(1), Some(unix_now() + 3600))
+                .is_ok()
+        );
+        assert!(
+            output
+                .print_secret("test-key", "test-value", Some(1), None)
                 .is_ok()
         );
     }
 
+    #[test]
+    fn test_remaining_seconds() {
+        assert_eq!(remaining_seconds(None, 1_000), None);
+        assert_eq!(remaining_seconds(Some(4_600), 1_000), Some(3_600));
+        assert_eq!(remaining_seconds(Some(900), 1_000), Some(0));
+    }
+
+    #[test]
+    fn test_describe_remaining() {
+        assert_eq!(describe_remaining(0), "expired");
+        assert_eq!(describe_remaining(45), "expires in 45s");
+        assert_eq!(describe_remaining(90), "expires in 1m");
+        assert_eq!(describe_remaining(3 * 3600 + 59), "expires in 3h");
+        assert_eq!(describe_remaining(2 * 86400), "expires in 2d");
+    }
+
+    #[test]
+    fn test_expiry_cell() {
+        let now = 1_700_000_000;
+        assert_eq!(expiry_cell(None, now).content(), "Never");
+
+        let soon = expiry_cell(Some(now + 3 * 3600), now).content();
+        assert!(soon.ends_with("(expires in 3h)"), "{soon}");
+
+        let later = expiry_cell(Some(now + 3 * 86400), now).content();
+        assert!(later.starts_with(&format_timestamp(now + 3 * 86400)));
+        assert!(later.ends_with("(expires in 3d)"));
+    }
+
+    #[test]
+    fn test_yaml_output() {
+        let value = json!({
+            "server_keys": {"count": 1, "keys": [{"id": "a1", "last_used_at": null}]},
+            "local_keys": {"key_pair_valid": true, "public_key_path": "/tmp/key: public.pem"},
+        });
+        assert_eq!(
+            to_yaml(&value).unwrap(),
+            "local_keys:\n  key_pair_valid: true\n  public_key_path: \"/tmp/key: public.pem\"\n\
+             server_keys:\n  count: 1\n  keys:\n    - id: a1\n      last_used_at: ~"
+        );
+    }
+
+    #[test]
+    fn test_print_secret_versions() {
+        let output = OutputManager::new(OutputFormat::Table);
+        let versions = vec![
+            sealbox_server::repo::SecretInfo {
+                key: "test-key".to_string(),
+                version: 2,
+                created_at: 1_700_000_100,
+                updated_at: 1_700_000_100,
+                expires_at: Some(1_700_003_700),
+                tags: Vec::new(),
+                deleted_at: None,
+                locked: false,
+                rotate_after: None,
+                description: None,
+                user_agent: None,
+                read_count: 0,
+                last_read_at: None,
+            },
+            sealbox_server::repo::SecretInfo {
+                key: "test-key".to_string(),
+                version: 1,
+                created_at: 1_700_000_000,
+                updated_at: 1_700_000_000,
+                expires_at: None,
+                tags: Vec::new(),
+                deleted_at: None,
+                locked: false,
+                rotate_after: None,
+                description: None,
+                user_agent: None,
+                read_count: 0,
+                last_read_at: None,
+            },
+        ];
+
+        assert!(output.print_secret_versions("test-key", &versions).is_ok());
+    }
+
+    #[test]
+    fn test_print_secret_list() {
+        let output = OutputManager::new(OutputFormat::Table);
+        let secrets = vec![sealbox_server::repo::SecretInfo {
+            key: "stripe-key".to_string(),
+            version: 3,
+            created_at: 1_700_000_000,
+            updated_at: 1_700_000_100,
+            expires_at: None,
+            tags: vec!["env=prod".to_string(), "team=payments".to_string()],
+            deleted_at: None,
+            locked: false,
+            rotate_after: None,
+            description: Some("Stripe live secret key".to_string()),
+            user_agent: None,
+            read_count: 0,
+            last_read_at: None,
+        }];
+
+        assert!(output.print_secret_list(&secrets).is_ok());
+        assert!(output.print_secret_list(&[]).is_ok());
+    }
+
     #[test]
     fn test_value_to_string() {
         let output = OutputManager::new(OutputFormat::Json);
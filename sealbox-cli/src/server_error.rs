@@ -0,0 +1,70 @@
+use anyhow::Error;
+use reqwest::Response;
+use serde::Deserialize;
+
+/// Error body sent by the server: `{"error": {"code", "message", "request_id"}}`
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetails,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetails {
+    code: String,
+    message: String,
+    request_id: Option<String>,
+}
+
+/// Human-readable description of an error body, falling back to the raw text
+/// for servers that predate structured errors
+fn describe_error_body(body: &str) -> String {
+    match serde_json::from_str::<ErrorResponse>(body) {
+        Ok(ErrorResponse { error }) => match error.request_id {
+            Some(request_id) => format!(
+                "{} (code: {}, request id: {request_id})",
+                error.message, error.code
+            ),
+            None => format!("{} (code: {})", error.message, error.code),
+        },
+        Err(_) => body.to_string(),
+    }
+}
+
+/// Turn an unsuccessful response into an error carrying the server's message
+pub async fn server_error(response: Response) -> Error {
+    let status = response.status();
+    let message = response
+        .text()
+        .await
+        .map(|body| describe_error_body(&body))
+        .unwrap_or_else(|_| "Unable to get error information".to_string());
+    anyhow::anyhow!("Server returned error (status code: {status}):\n{message}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_structured_error() {
+        let body = r#"{"error":{"code":"secret_not_found","message":"Secret not found: db","request_id":"req-1"}}"#;
+        assert_eq!(
+            describe_error_body(body),
+            "Secret not found: db (code: secret_not_found, request id: req-1)"
+        );
+
+        let body =
+            r#"{"error":{"code":"unauthorized","message":"Unauthorized","request_id":null}}"#;
+        assert_eq!(
+            describe_error_body(body),
+            "Unauthorized (code: unauthorized)"
+        );
+    }
+
+    #[test]
+    fn test_describe_legacy_error_falls_back_to_raw_text() {
+        let body = r#"{"error":"[SealboxError] Unauthorized"}"#;
+        assert_eq!(describe_error_body(body), body);
+        assert_eq!(describe_error_body("Bad Gateway"), "Bad Gateway");
+    }
+}
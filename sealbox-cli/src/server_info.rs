@@ -0,0 +1,183 @@
+use anyhow::{Context, Result};
+use sealbox_server::crypto::master_key::KeyAlgorithm;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+use crate::{
+    config::Config,
+    http::{self, SendWithRetries},
+    server_error::server_error,
+};
+
+/// How long a fetched `/info` response is trusted before asking the server again
+pub const CACHE_TTL_SECONDS: i64 = 300;
+
+const CACHE_FILE_NAME: &str = "server-info.json";
+
+/// Capabilities reported by `GET /v1/info`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub version: String,
+    pub api_versions: Vec<String>,
+    pub key_algorithms: Vec<String>,
+}
+
+impl ServerInfo {
+    pub fn supports_algorithm(&self, algorithm: KeyAlgorithm) -> bool {
+        self.key_algorithms
+            .iter()
+            .any(|supported| supported == algorithm.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedServerInfo {
+    fetched_at: i64,
+    info: ServerInfo,
+}
+
+/// Server capabilities cached on disk, keyed by server URL
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ServerInfoCache {
+    servers: BTreeMap<String, CachedServerInfo>,
+}
+
+impl ServerInfoCache {
+    /// Read the cache file; a missing or unreadable cache is just empty
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)
+            .with_context(|| format!("Failed to write cache file: {}", path.display()))
+    }
+
+    /// Cached info for `url` if it was fetched less than `CACHE_TTL_SECONDS` before `now`
+    pub fn lookup(&self, url: &str, now: i64) -> Option<&ServerInfo> {
+        self.servers
+            .get(url)
+            .filter(|cached| (0..CACHE_TTL_SECONDS).contains(&(now - cached.fetched_at)))
+            .map(|cached| &cached.info)
+    }
+
+    pub fn insert(&mut self, url: &str, info: ServerInfo, now: i64) {
+        self.servers.insert(
+            url.to_string(),
+            CachedServerInfo {
+                fetched_at: now,
+                info,
+            },
+        );
+    }
+}
+
+/// Capabilities of the configured server, from the local cache while it is fresh
+pub async fn server_info(config: &Config) -> Result<ServerInfo> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("System clock is before the Unix epoch")?
+        .as_secs() as i64;
+    let cache_path = Config::config_dir()?.join(CACHE_FILE_NAME);
+    let mut cache = ServerInfoCache::load(&cache_path);
+
+    if let Some(info) = cache.lookup(&config.server.url, now) {
+        return Ok(info.clone());
+    }
+
+    let info = fetch_server_info(config).await?;
+    cache.insert(&config.server.url, info.clone(), now);
+    // The cache only saves round trips, so failing to write it is not an error
+    let _ = cache.save(&cache_path);
+    Ok(info)
+}
+
+async fn fetch_server_info(config: &Config) -> Result<ServerInfo> {
+    let response = http::client(config)
+        .get(format!("{}/v1/info", config.server.url))
+        .bearer_auth(&config.server.token)
+        .send_with_retries(config)
+        .await
+        .context("Failed to request server")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(server_error(response)
+            .await
+            .context("Failed to fetch /v1/info"));
+    }
+    response
+        .json()
+        .await
+        .context("Failed to parse server response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const URL: &str = "http://localhost:8080";
+
+    fn sample_info() -> ServerInfo {
+        ServerInfo {
+            version: "0.1.0".to_string(),
+            api_versions: vec!["v1".to_string()],
+            key_algorithms: vec!["rsa".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_and_miss() {
+        let mut cache = ServerInfoCache::default();
+        assert!(cache.lookup(URL, 1_000).is_none());
+
+        cache.insert(URL, sample_info(), 1_000);
+        assert_eq!(cache.lookup(URL, 1_000), Some(&sample_info()));
+        assert!(cache.lookup("http://other:8080", 1_000).is_none());
+    }
+
+    #[test]
+    fn test_cache_staleness() {
+        let mut cache = ServerInfoCache::default();
+        cache.insert(URL, sample_info(), 1_000);
+
+        assert!(cache.lookup(URL, 1_000 + CACHE_TTL_SECONDS - 1).is_some());
+        assert!(cache.lookup(URL, 1_000 + CACHE_TTL_SECONDS).is_none());
+        // An entry from the future means the clock moved backwards; don't trust it
+        assert!(cache.lookup(URL, 999).is_none());
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cache").join(CACHE_FILE_NAME);
+        assert!(ServerInfoCache::load(&path).lookup(URL, 1_000).is_none());
+
+        let mut cache = ServerInfoCache::default();
+        cache.insert(URL, sample_info(), 1_000);
+        cache.save(&path).unwrap();
+        assert_eq!(
+            ServerInfoCache::load(&path).lookup(URL, 1_010),
+            Some(&sample_info())
+        );
+
+        fs::write(&path, "not json").unwrap();
+        assert!(ServerInfoCache::load(&path).lookup(URL, 1_010).is_none());
+    }
+
+    #[test]
+    fn test_supports_algorithm() {
+        let info = sample_info();
+        assert!(info.supports_algorithm(KeyAlgorithm::Rsa));
+        assert!(!info.supports_algorithm(KeyAlgorithm::X25519));
+    }
+}
@@ -0,0 +1,19 @@
+use std::process::Command;
+
+#[test]
+fn test_completion_covers_the_command_tree() {
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_sealbox-cli"))
+            .env_remove("SEALBOX_CONFIG")
+            .args(["completion", shell])
+            .output()
+            .expect("Should run sealbox-cli");
+        assert!(output.status.success(), "{shell}: {output:?}");
+
+        let script = String::from_utf8(output.stdout).unwrap();
+        assert!(script.contains("sealbox-cli"), "{shell}");
+        for word in ["secret", "namespaces", "client-encrypt", "rotate"] {
+            assert!(script.contains(word), "{shell} completion lacks {word}");
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+/// Run `sealbox-cli key generate` with a throwaway home directory
+fn generate_keys(home: &TempDir, extra_args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sealbox-cli"))
+        .env("HOME", home.path())
+        .env_remove("SEALBOX_CONFIG")
+        .env_remove("SEALBOX_PUBLIC_KEY")
+        .env_remove("SEALBOX_PRIVATE_KEY")
+        .args(["key", "generate", "--force"])
+        .args(extra_args)
+        .output()
+        .expect("Should run sealbox-cli")
+}
+
+#[test]
+fn test_quiet_suppresses_messages_but_not_errors() {
+    let home = TempDir::new().unwrap();
+
+    let output = generate_keys(&home, &["--algorithm", "x25519"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Key pair generated"));
+
+    let output = generate_keys(&home, &["--quiet", "--algorithm", "x25519"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty(), "quiet mode printed: {output:?}");
+    assert!(home.path().join(".config/sealbox/private_key.pem").exists());
+
+    // Errors are still reported
+    let output = generate_keys(&home, &["--quiet", "--bits", "1024"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unsupported RSA key size"));
+}
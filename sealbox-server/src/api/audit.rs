@@ -0,0 +1,143 @@
+use axum::{
+    extract::{FromRequestParts, MatchedPath, Query, RawPathParams, Request, State},
+    http::{Method, header::USER_AGENT},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    api::{REQUEST_ID_HEADER, state::AppState},
+    error::Result,
+    repo::NewAuditEntry,
+};
+
+/// Audit action recorded for a route, or `None` for routes that are not audited.
+///
+/// Listings, history and job polling only reveal metadata and are left out to
+/// keep the log focused on operations that read values or change state.
+fn audited_action(method: &Method, route: &str) -> Option<&'static str> {
+    let action = match (method.as_str(), route) {
+        ("GET", "/{version}/secrets/{secret_key}") => "secret.get",
+        ("PUT", "/{version}/secrets/{secret_key}") => "secret.save",
+        ("PUT", "/{version}/secrets/{secret_key}/encrypted") => "secret.save_encrypted",
+        ("DELETE", "/{version}/secrets/{secret_key}") => "secret.delete",
+        ("PATCH", "/{version}/secrets/{secret_key}") => "secret.set_expiry",
+        ("POST", "/{version}/secrets/{secret_key}/restore") => "secret.restore",
+        ("POST", "/{version}/secrets/{secret_key}/rollback") => "secret.rollback",
+        ("POST", "/{version}/secrets/{secret_key}/rename") => "secret.rename",
+        ("PUT", "/{version}/secrets/{secret_key}/versions/{secret_version}/lock") => "secret.lock",
+        ("POST", "/{version}/secrets:batch") => "secret.batch_save",
+        ("POST", "/{version}/master-key") => "master_key.create",
+        ("PUT", "/{version}/master-key") => "master_key.rotate",
+        ("POST", "/{version}/master-key/rotate") => "master_key.start_rotation",
+        ("DELETE", "/{version}/admin/cleanup-expired") => "admin.cleanup_expired",
+        ("GET", "/{version}/admin/backup") => "admin.backup",
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionQuery {
+    #[serde(alias = "to")] // Rollback names its target version `to`
+    version: Option<i32>,
+}
+
+/// Middleware appending an audit log entry for every audited operation
+///
+/// # Notes
+///
+/// Runs outside authentication, so rejected attempts are recorded with their 401 or 403.
+/// A failure to write the entry is logged but does not fail the request.
+pub(crate) async fn record_audit(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string());
+    let Some(action) = route.and_then(|route| audited_action(request.method(), &route)) else {
+        return next.run(request).await;
+    };
+
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let user_agent = request
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| truncate_user_agent(value).to_string());
+    let target_version = Query::<VersionQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(query)| query.version);
+
+    let (mut parts, body) = request.into_parts();
+    let target_key = RawPathParams::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .and_then(|params| {
+            params
+                .iter()
+                .find(|(name, _)| *name == "secret_key")
+                .map(|(_, value)| value.to_string())
+        });
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let entry = NewAuditEntry {
+        request_id,
+        action,
+        target_key,
+        target_version,
+        status: response.status().as_u16(),
+        user_agent,
+    };
+    if let Err(err) = append_entry(&state, &entry) {
+        error!("Failed to record audit entry {:?}: {}", entry, err);
+    }
+
+    response
+}
+
+/// Longest `User-Agent` kept in the audit log or on a secret version, in bytes
+pub(crate) const MAX_USER_AGENT_LEN: usize = 256;
+
+/// `user_agent` cut to at most `MAX_USER_AGENT_LEN` bytes on a character boundary
+pub(crate) fn truncate_user_agent(user_agent: &str) -> &str {
+    let mut end = user_agent.len().min(MAX_USER_AGENT_LEN);
+    while !user_agent.is_char_boundary(end) {
+        end -= 1;
+    }
+    &user_agent[..end]
+}
+
+fn append_entry(state: &AppState, entry: &NewAuditEntry) -> Result<()> {
+    let conn = state.conn_pool.get()?;
+    state.audit_repo.record(&conn, entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audited_action() {
+        assert_eq!(
+            audited_action(&Method::GET, "/{version}/secrets/{secret_key}"),
+            Some("secret.get")
+        );
+        assert_eq!(
+            audited_action(&Method::PUT, "/{version}/master-key"),
+            Some("master_key.rotate")
+        );
+        assert_eq!(audited_action(&Method::GET, "/{version}/secrets"), None);
+        assert_eq!(audited_action(&Method::GET, "/{version}/master-key"), None);
+    }
+}
@@ -1,34 +1,200 @@
 use axum::{
-    extract::{Request, State},
-    http::HeaderMap,
+    extract::{MatchedPath, Request, State},
+    http::{HeaderMap, Method},
     middleware::Next,
     response::Response,
 };
+use tracing::warn;
 
 use crate::{
     api::state::AppState,
+    config::{Scope, SealboxConfig},
+    crypto::totp,
     error::{Result, SealboxError},
 };
 
+/// Header carrying the TOTP code for destructive operations
+pub(crate) const TOTP_HEADER: &str = "X-TOTP";
+
+/// Token from an `Authorization: Bearer <token>` header
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("Authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+}
+
+/// Whether `token` grants access to the API
+pub(crate) fn is_valid_token(config: &SealboxConfig, token: &str) -> bool {
+    config.scopes_for(token).is_some()
+}
+
+/// Reject a destructive operation without a valid `X-TOTP` code.
+///
+/// Does nothing unless `SEALBOX_TOTP_SECRET` is set.
+pub(crate) fn require_totp(config: &SealboxConfig, headers: &HeaderMap) -> Result<()> {
+    let Some(secret) = &config.totp_secret else {
+        return Ok(());
+    };
+    let code = headers
+        .get(TOTP_HEADER)
+        .and_then(|header| header.to_str().ok())
+        .ok_or(SealboxError::TotpRequired)?;
+    let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+    if !totp::verify(secret, code, now) {
+        warn!("Destructive operation rejected: invalid TOTP code");
+        return Err(SealboxError::TotpRequired);
+    }
+    Ok(())
+}
+
+/// Scope a request needs, by method and route template
+fn required_scope(method: &Method, route: &str) -> Scope {
+    if route.starts_with("/{version}/admin/") {
+        return Scope::Admin;
+    }
+    match (method, route) {
+        (&Method::DELETE, _) => Scope::Admin,
+        (&Method::PUT, "/{version}/master-key") => Scope::Admin,
+        (&Method::POST, "/{version}/master-key/rotate") => Scope::Admin,
+        // Locking is irreversible and blocks later deletes
+        (&Method::PUT, "/{version}/secrets/{secret_key}/versions/{secret_version}/lock") => {
+            Scope::Admin
+        }
+        (&Method::GET, _) => Scope::Read,
+        _ => Scope::Write,
+    }
+}
+
+/// Middleware rejecting requests without a token granting the route's scope
+///
+/// # Notes
+///
+/// Paths listed in `SEALBOX_AUTH_EXEMPT_PATHS` are passed through without a token.
 pub(crate) async fn static_auth(
     State(state): State<AppState>,
     headers: HeaderMap,
     request: Request,
     next: Next,
 ) -> Result<Response> {
-    let auth_header = headers
-        .get("Authorization")
-        .and_then(|header| header.to_str().ok());
-
-    match auth_header {
-        Some(header) if header.starts_with("Bearer ") => {
-            let token = &header[7..];
-            if token == state.config.auth_token {
-                Ok(next.run(request).await)
-            } else {
-                Err(SealboxError::Unauthorized)
-            }
+    let config = state.config.snapshot();
+    if config
+        .auth_exempt_paths
+        .iter()
+        .any(|path| path == request.uri().path())
+    {
+        return Ok(next.run(request).await);
+    }
+
+    let scopes = bearer_token(&headers)
+        .and_then(|token| config.scopes_for(token))
+        .ok_or(SealboxError::Unauthorized)?;
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("", MatchedPath::as_str);
+    let required = required_scope(request.method(), route);
+    // Admin tokens can do everything a narrower token can
+    if !scopes.contains(&required) && !scopes.contains(&Scope::Admin) {
+        warn!(
+            "Token without {:?} scope rejected for {} {}",
+            required,
+            request.method(),
+            route
+        );
+        // The token is valid, so this is a 403 rather than a 401
+        return Err(SealboxError::Forbidden(format!(
+            "token lacks the {required} scope required for {} {}",
+            request.method(),
+            request.uri().path()
+        )));
+    }
+
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        let cases = [
+            (Method::GET, "/{version}/secrets/{secret_key}", Scope::Read),
+            (Method::GET, "/{version}/master-key", Scope::Read),
+            (Method::GET, "/{version}/namespaces", Scope::Read),
+            (
+                Method::GET,
+                "/{version}/secrets/{secret_key}/meta",
+                Scope::Read,
+            ),
+            (
+                Method::GET,
+                "/{version}/master-key/{master_key_id}/public-key",
+                Scope::Read,
+            ),
+            (Method::PUT, "/{version}/secrets/{secret_key}", Scope::Write),
+            (
+                Method::PATCH,
+                "/{version}/secrets/{secret_key}",
+                Scope::Write,
+            ),
+            (Method::POST, "/{version}/secrets:batch", Scope::Write),
+            (Method::POST, "/{version}/master-key", Scope::Write),
+            (
+                Method::POST,
+                "/{version}/master-key/challenge",
+                Scope::Write,
+            ),
+            (
+                Method::DELETE,
+                "/{version}/secrets/{secret_key}",
+                Scope::Admin,
+            ),
+            (Method::PUT, "/{version}/master-key", Scope::Admin),
+            (Method::POST, "/{version}/master-key/rotate", Scope::Admin),
+            (Method::GET, "/{version}/admin/audit", Scope::Admin),
+            (Method::GET, "/{version}/admin/backup", Scope::Admin),
+            (Method::GET, "/{version}/admin/rotation-due", Scope::Admin),
+            (Method::GET, "/{version}/admin/unused-secrets", Scope::Admin),
+            (Method::GET, "/{version}/admin/scan", Scope::Admin),
+        ];
+        for (method, route, expected) in cases {
+            assert_eq!(required_scope(&method, route), expected, "{method} {route}");
+        }
+    }
+
+    #[test]
+    fn test_require_totp() {
+        let secret = b"12345678901234567890".to_vec();
+        let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+        let with_code = |code: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(TOTP_HEADER, code.parse().unwrap());
+            headers
+        };
+
+        // Without a TOTP secret nothing changes
+        let config = SealboxConfig::default();
+        assert!(require_totp(&config, &HeaderMap::new()).is_ok());
+
+        let config = SealboxConfig {
+            totp_secret: Some(secret.clone()),
+            ..SealboxConfig::default()
+        };
+        assert!(matches!(
+            require_totp(&config, &HeaderMap::new()),
+            Err(SealboxError::TotpRequired)
+        ));
+        let valid = totp::code_at(&secret, now);
+        assert!(require_totp(&config, &with_code(&valid)).is_ok());
+        let stale = totp::code_at(&secret, now - 5 * totp::TIME_STEP);
+        if stale != valid {
+            assert!(matches!(
+                require_totp(&config, &with_code(&stale)),
+                Err(SealboxError::TotpRequired)
+            ));
         }
-        _ => Err(SealboxError::Unauthorized),
     }
 }
@@ -1,9 +1,18 @@
-use axum::extract::State;
+use std::{collections::HashMap, str::FromStr};
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use uuid::Uuid;
 
 use crate::{
-    api::{SealboxResponse, state::AppState},
-    error::Result,
+    api::{SealboxResponse, Version, auth::require_totp, path::Path, state::AppState},
+    crypto::{envelope::EnvelopeVersion, master_key::PublicMasterKey},
+    error::{Result, SealboxError},
+    repo::{AuditFilter, Secret},
 };
 
 /// API handler for cleaning up expired secrets
@@ -16,6 +25,10 @@ use crate::{
 ///
 /// Returns JSON response with cleanup statistics
 ///
+/// # Errors
+///
+/// * `SealboxError::TotpRequired` - When TOTP is configured and `X-TOTP` is missing or wrong
+///
 /// # HTTP Route
 ///
 /// `DELETE /v1/admin/cleanup-expired`
@@ -28,8 +41,13 @@ use crate::{
 ///   "cleaned_at": 1703876543
 /// }
 /// ```
-pub(crate) async fn cleanup_expired(State(state): State<AppState>) -> Result<SealboxResponse> {
-    let conn = state.conn_pool.lock()?;
+pub(crate) async fn cleanup_expired(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<SealboxResponse> {
+    require_totp(&state.config.snapshot(), &headers)?;
+
+    let conn = state.conn_pool.get()?;
     let deleted_count = state.secret_repo.cleanup_expired_secrets(&conn)?;
     let cleaned_at = time::OffsetDateTime::now_utc().unix_timestamp();
 
@@ -38,3 +56,948 @@ pub(crate) async fn cleanup_expired(State(state): State<AppState>) -> Result<Sea
         "cleaned_at": cleaned_at
     })))
 }
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct JobPathParams {
+    version: Version,
+    job_id: Uuid,
+}
+
+impl JobPathParams {
+    fn version(&self) -> Version {
+        self.version.clone()
+    }
+}
+
+/// API handler for reporting the progress of a background job
+///
+/// # Arguments
+///
+/// * `state` - Application state containing the job registry
+/// * `params` - Path parameters containing API version and job id
+///
+/// # Returns
+///
+/// Returns the job status, progress counters and the keys of secrets that failed
+///
+/// # Errors
+///
+/// * `SealboxError::JobNotFound` - When no job with the given id exists
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/jobs/{job_id}`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "id": "6f1c0c8e-7d6b-4d3f-9a53-0d1c1e9c2b7a",
+///   "kind": "master_key_rotation",
+///   "status": "completed",
+///   "total": 120,
+///   "processed": 120,
+///   "failed_secret_keys": ["legacy-token"],
+///   "error": null,
+///   "created_at": 1703876543,
+///   "updated_at": 1703876550
+/// }
+/// ```
+pub(crate) async fn get_job(
+    State(state): State<AppState>,
+    Path(params): Path<JobPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let job = state
+                .jobs
+                .get(&params.job_id)
+                .ok_or(SealboxError::JobNotFound(params.job_id))?;
+            Ok(SealboxResponse::Json(json!(job)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct AdminPathParams {
+    version: Version,
+}
+
+impl AdminPathParams {
+    fn version(&self) -> Version {
+        self.version.clone()
+    }
+}
+
+/// Audit entries returned when no `limit` is given
+const DEFAULT_AUDIT_LIMIT: u32 = 100;
+
+/// Largest `limit` accepted when reading the audit log
+const MAX_AUDIT_LIMIT: u32 = 1000;
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct AuditQueryParams {
+    limit: Option<u32>,
+    since: Option<i64>,
+    until: Option<i64>,
+    action: Option<String>,
+    key: Option<String>,
+    cursor: Option<i64>,
+}
+
+/// API handler for reading the audit log
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+/// * `query` - Query parameters with optional `limit`, `since` and `until` (Unix time),
+///   `action`, `key` (secret key) and `cursor` filters
+///
+/// # Returns
+///
+/// Returns audit entries, newest first. `next_cursor` is set when a full page was returned;
+/// passing it as `cursor` fetches the next, older page
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - When `limit` is 0 or larger than `MAX_AUDIT_LIMIT`
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/audit[?limit=N&since=T&until=T&action=A&key=K&cursor=C]`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "entries": [
+///     {
+///       "id": 42,
+///       "timestamp": 1703876543,
+///       "request_id": "0a6b4b1e-3a5c-4f0e-9d7b-2f4c6e8a1b3d",
+///       "action": "secret.get",
+///       "target_key": "db-password",
+///       "target_version": null,
+///       "status": 200
+///     }
+///   ],
+///   "next_cursor": 42
+/// }
+/// ```
+pub(crate) async fn audit(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+    Query(query): Query<AuditQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let limit = query.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+            if limit == 0 || limit > MAX_AUDIT_LIMIT {
+                return Err(SealboxError::InvalidInput(format!(
+                    "limit must be between 1 and {MAX_AUDIT_LIMIT}"
+                )));
+            }
+
+            let conn = state.conn_pool.get()?;
+            let entries = state.audit_repo.list_entries(
+                &conn,
+                &AuditFilter {
+                    since: query.since,
+                    until: query.until,
+                    action: query.action,
+                    target_key: query.key,
+                    before_id: query.cursor,
+                    limit,
+                },
+            )?;
+            let next_cursor = match entries.last() {
+                Some(last) if entries.len() == limit as usize => Some(last.id),
+                _ => None,
+            };
+            Ok(SealboxResponse::Json(json!({
+                "entries": entries,
+                "next_cursor": next_cursor,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler reporting capacity figures for dashboards
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns row counts, the database size and the server version
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/stats`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "secrets": 120,
+///   "master_keys": 2,
+///   "expired_versions": 7,
+///   "database_size_bytes": 1048576,
+///   "version": "0.1.0"
+/// }
+/// ```
+///
+/// # Notes
+///
+/// `database_size_bytes` covers the main database file only, not a pending WAL.
+pub(crate) async fn stats(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let counts = state.secret_repo.count_secrets(&conn)?;
+            let master_keys = state.master_key_repo.count_master_keys(&conn)?;
+            let database_size_bytes = state.health_repo.database_size(&conn)?;
+
+            Ok(SealboxResponse::Json(json!({
+                "secrets": counts.secrets,
+                "master_keys": master_keys,
+                "expired_versions": counts.expired_versions,
+                "database_size_bytes": database_size_bytes,
+                "version": env!("CARGO_PKG_VERSION"),
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Temporary backup file, removed when dropped whether or not the backup succeeded
+struct TempBackupFile(std::path::PathBuf);
+
+impl Drop for TempBackupFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// API handler returning a consistent copy of the database
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns the SQLite database file as an `application/octet-stream` attachment
+///
+/// # Errors
+///
+/// * `SealboxError::DatabaseError` - When the copy cannot be written
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/backup`
+///
+/// # Security Notes
+///
+/// The copy holds every stored ciphertext and wrapped data key, which stay unreadable
+/// without the matching private keys. It is written with `VACUUM INTO` to a temporary
+/// file, read back and deleted before the response is sent.
+pub(crate) async fn backup(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let file = TempBackupFile(
+                std::env::temp_dir().join(format!("sealbox-backup-{}.db", Uuid::new_v4())),
+            );
+            {
+                let conn = state.conn_pool.get()?;
+                state.health_repo.backup_into(&conn, &file.0)?;
+            }
+            let data = tokio::fs::read(&file.0)
+                .await
+                .map_err(|err| SealboxError::DatabaseError(err.to_string()))?;
+
+            let timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
+            Ok(SealboxResponse::Attachment(
+                format!("sealbox-backup-{timestamp}.db"),
+                data,
+            ))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Days without a read after which a master key or secret is reported as stale by default
+const DEFAULT_STALE_DAYS: u32 = 90;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct StaleKeysQueryParams {
+    days: Option<u32>,
+}
+
+/// API handler listing master keys that nothing has read recently
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+/// * `query` - Query parameters with optional `days` threshold
+///
+/// # Returns
+///
+/// Returns master keys that are not disabled and were never used or not used within `days`,
+/// never-used keys first
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - When `days` is 0
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/stale-keys[?days=N]`
+///
+/// # Notes
+///
+/// A key is used whenever a secret wrapped with it is read, so abandoned credentials can be
+/// found and disabled.
+pub(crate) async fn stale_keys(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+    Query(query): Query<StaleKeysQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let days = query.days.unwrap_or(DEFAULT_STALE_DAYS);
+            if days == 0 {
+                return Err(SealboxError::InvalidInput(
+                    "days must be at least 1".to_string(),
+                ));
+            }
+
+            let conn = state.conn_pool.get()?;
+            let master_keys = state.master_key_repo.fetch_stale_master_keys(&conn, days)?;
+            Ok(SealboxResponse::Json(json!(master_keys)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler listing secrets that nothing has read recently
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+/// * `query` - Query parameters with optional `days` threshold
+///
+/// # Returns
+///
+/// Returns the latest version of every secret none of whose unexpired versions was read
+/// within `days`, never-read secrets first. `read_count` and `last_read_at` cover all of
+/// those versions
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - When `days` is 0
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/unused-secrets[?days=N]`
+///
+/// # Notes
+///
+/// Only reads of the value count, so listing or inspecting metadata does not keep a secret
+/// in use. Reads made before read tracking existed are not known.
+pub(crate) async fn unused_secrets(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+    Query(query): Query<StaleKeysQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let days = query.days.unwrap_or(DEFAULT_STALE_DAYS);
+            if days == 0 {
+                return Err(SealboxError::InvalidInput(
+                    "days must be at least 1".to_string(),
+                ));
+            }
+
+            let conn = state.conn_pool.get()?;
+            let secrets = state.secret_repo.list_unused_secrets(&conn, days)?;
+            Ok(SealboxResponse::Json(json!(secrets)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler listing secrets that are past their rotation reminder
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns the latest version of every secret whose `rotate_after` time has passed, most
+/// overdue first
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/rotation-due`
+///
+/// # Notes
+///
+/// Reminders never block reads or writes. Saving a new version rotates the secret, which
+/// clears its reminder unless the save sets a new `rotate_after`.
+pub(crate) async fn rotation_due(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let secrets = state.secret_repo.list_rotation_due(&conn)?;
+            Ok(SealboxResponse::Json(json!(secrets)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler checking every stored secret version for corruption
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns how many versions were scanned and every version that failed a check, with the
+/// problem found
+///
+/// # Errors
+///
+/// * `SealboxError::DatabaseError` - When a row cannot be read at all
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/admin/scan`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "scanned": 120,
+///   "corrupt": [
+///     {
+///       "key": "db-password",
+///       "version": 3,
+///       "master_key_id": "6f1c0c8e-7d6b-4d3f-9a53-0d1c1e9c2b7a",
+///       "problem": "Malformed envelope: wrapped data key is 255 bytes, expected 256"
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Security Notes
+///
+/// The server never holds private keys, so it cannot tell whether a ciphertext decrypts.
+/// Each version is checked for a known envelope version, an existing master key and blobs
+/// of the sizes that key and envelope produce; expired and soft-deleted versions included.
+pub(crate) async fn scan(
+    State(state): State<AppState>,
+    Path(params): Path<AdminPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let secrets = state.secret_repo.fetch_all_secrets(&conn)?;
+            let mut public_keys: HashMap<Uuid, PublicMasterKey> = HashMap::new();
+            for secret in &secrets {
+                if public_keys.contains_key(&secret.master_key_id) {
+                    continue;
+                }
+                let public_key = state
+                    .master_key_repo
+                    .fetch_public_key(&conn, &secret.master_key_id)?
+                    .and_then(|pem| PublicMasterKey::from_str(&pem).ok());
+                if let Some(public_key) = public_key {
+                    public_keys.insert(secret.master_key_id, public_key);
+                }
+            }
+            drop(conn);
+
+            let corrupt: Vec<_> = secrets
+                .iter()
+                .filter_map(|secret| {
+                    let problem = check_secret(&public_keys, secret).err()?;
+                    Some(json!({
+                        "key": secret.key,
+                        "version": secret.version,
+                        "master_key_id": secret.master_key_id,
+                        "problem": problem,
+                    }))
+                })
+                .collect();
+
+            Ok(SealboxResponse::Json(json!({
+                "scanned": secrets.len(),
+                "corrupt": corrupt,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Why a stored version cannot be decrypted, as far as the server can tell without the
+/// private key
+fn check_secret(
+    public_keys: &HashMap<Uuid, PublicMasterKey>,
+    secret: &Secret,
+) -> std::result::Result<(), String> {
+    let envelope =
+        EnvelopeVersion::try_from(secret.envelope_version).map_err(|err| err.to_string())?;
+    let public_key = public_keys.get(&secret.master_key_id).ok_or_else(|| {
+        format!(
+            "Master key {} is missing or has an unreadable public key",
+            secret.master_key_id
+        )
+    })?;
+    envelope
+        .check_structure(
+            public_key,
+            &secret.encrypted_data_key,
+            &secret.encrypted_data,
+        )
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::job::JobStatus,
+        config::{SealboxConfig, SharedConfig},
+        crypto::{envelope::EnvelopeVersion, master_key::generate_key_pair},
+        repo::{
+            MasterKey, MasterKeyStatus, NewAuditEntry, NewSecret, SqliteAuditRepo,
+            SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo, create_db_pool,
+        },
+    };
+    use std::sync::Arc;
+
+    fn setup_test_state() -> AppState {
+        let conn_pool = create_db_pool(":memory:", 1).expect("Should create in-memory DB");
+        let conn = conn_pool.get().expect("Should check out a connection");
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should init master_keys table");
+        SqliteSecretRepo::init_table(&conn).expect("Should init secrets table");
+        SqliteAuditRepo::init_table(&conn).expect("Should init audit_log table");
+
+        AppState {
+            conn_pool,
+            master_key_repo: Arc::new(SqliteMasterKeyRepo),
+            secret_repo: Arc::new(SqliteSecretRepo),
+            health_repo: Arc::new(SqliteHealthRepo),
+            audit_repo: Arc::new(SqliteAuditRepo),
+            config: SharedConfig::new(SealboxConfig::default()),
+            jobs: Default::default(),
+            secret_changes: Default::default(),
+            metrics: Default::default(),
+            rate_limiter: Default::default(),
+            key_challenges: Default::default(),
+            webhooks: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_job_reports_progress() {
+        let state = setup_test_state();
+        let job = state.jobs.create("master_key_rotation");
+        state.jobs.update(&job.id, |job| {
+            job.status = JobStatus::Running;
+            job.total = 250;
+            job.processed = 100;
+        });
+
+        let params = JobPathParams {
+            version: Version::V1,
+            job_id: job.id,
+        };
+        let result = get_job(State(state), Path(params))
+            .await
+            .expect("Should return job");
+
+        match result {
+            SealboxResponse::Json(json_value) => {
+                assert_eq!(json_value["status"], "running");
+                assert_eq!(json_value["total"], 250);
+                assert_eq!(json_value["processed"], 100);
+            }
+            _ => panic!("Expected JSON response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_job_not_found() {
+        let state = setup_test_state();
+        let params = JobPathParams {
+            version: Version::V1,
+            job_id: Uuid::new_v4(),
+        };
+
+        let result = get_job(State(state), Path(params)).await;
+
+        match result.unwrap_err() {
+            SealboxError::JobNotFound(_) => {} // Expected
+            _ => panic!("Expected JobNotFound error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_lists_recorded_entries() {
+        let state = setup_test_state();
+        {
+            let conn = state.conn_pool.get().unwrap();
+            for action in ["secret.save", "secret.get"] {
+                state
+                    .audit_repo
+                    .record(
+                        &conn,
+                        &NewAuditEntry {
+                            request_id: Some("req-1".to_string()),
+                            action,
+                            target_key: Some("db-password".to_string()),
+                            target_version: None,
+                            status: 200,
+                            user_agent: None,
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+
+        let params = AdminPathParams {
+            version: Version::V1,
+        };
+        let response = audit(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(AuditQueryParams {
+                limit: Some(1),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Should list audit entries");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(body["entries"][0]["action"], "secret.get");
+        assert_eq!(body["entries"][0]["target_key"], "db-password");
+
+        // The cursor continues with the older entry, after which there is no next page
+        let response = audit(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(AuditQueryParams {
+                limit: Some(1),
+                cursor: body["next_cursor"].as_i64(),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Should list audit entries");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["entries"][0]["action"], "secret.save");
+
+        let response = audit(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(AuditQueryParams {
+                limit: Some(10),
+                action: Some("secret.save".to_string()),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect("Should list audit entries");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+        assert!(body["next_cursor"].is_null());
+
+        let err = audit(
+            State(state),
+            Path(params),
+            Query(AuditQueryParams {
+                limit: Some(0),
+                ..Default::default()
+            }),
+        )
+        .await
+        .expect_err("Zero limit should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_backup_returns_database_copy() {
+        let state = setup_test_state();
+        let response = backup(
+            State(state),
+            Path(AdminPathParams {
+                version: Version::V1,
+            }),
+        )
+        .await
+        .expect("Backup should succeed");
+
+        let SealboxResponse::Attachment(filename, data) = response else {
+            panic!("Expected attachment response");
+        };
+        assert!(filename.starts_with("sealbox-backup-") && filename.ends_with(".db"));
+        assert!(data.starts_with(b"SQLite format 3\0"));
+    }
+
+    #[tokio::test]
+    async fn test_stats_counts_rows() {
+        let state = setup_test_state();
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            let (_, public_pem) = generate_key_pair().unwrap();
+            let master_key = MasterKey::new(public_pem).unwrap();
+            state
+                .master_key_repo
+                .create_master_key(&conn, &master_key)
+                .unwrap();
+            for (key, ttl) in [
+                ("db-password", None),
+                ("db-password", None),
+                ("old", Some(-60)),
+            ] {
+                state
+                    .secret_repo
+                    .create_new_version(
+                        &mut conn,
+                        master_key.clone(),
+                        NewSecret {
+                            ttl,
+                            ..NewSecret::new(key, "value", EnvelopeVersion::V1)
+                        },
+                    )
+                    .unwrap();
+            }
+        }
+
+        let response = stats(
+            State(state),
+            Path(AdminPathParams {
+                version: Version::V1,
+            }),
+        )
+        .await
+        .expect("Should report stats");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["secrets"], 2);
+        assert_eq!(body["master_keys"], 1);
+        assert_eq!(body["expired_versions"], 1);
+        assert!(body["database_size_bytes"].as_u64().unwrap() > 0);
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[tokio::test]
+    async fn test_scan_flags_corrupt_rows() {
+        let state = setup_test_state();
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            let (_, public_pem) = generate_key_pair().unwrap();
+            let master_key = MasterKey::new(public_pem).unwrap();
+            state
+                .master_key_repo
+                .create_master_key(&conn, &master_key)
+                .unwrap();
+            for key in ["intact", "truncated", "unknown-envelope"] {
+                state
+                    .secret_repo
+                    .create_new_version(
+                        &mut conn,
+                        master_key.clone(),
+                        NewSecret::new(key, "value", EnvelopeVersion::V1),
+                    )
+                    .unwrap();
+            }
+            conn.execute(
+                "UPDATE secrets SET encrypted_data = x'00' WHERE key = 'truncated'",
+                (),
+            )
+            .unwrap();
+            conn.execute(
+                "UPDATE secrets SET envelope_version = 9 WHERE key = 'unknown-envelope'",
+                (),
+            )
+            .unwrap();
+        }
+
+        let response = scan(
+            State(state),
+            Path(AdminPathParams {
+                version: Version::V1,
+            }),
+        )
+        .await
+        .expect("Should scan");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["scanned"], 3);
+        let corrupt = body["corrupt"].as_array().unwrap();
+        let keys: Vec<_> = corrupt
+            .iter()
+            .map(|row| row["key"].as_str().unwrap())
+            .collect();
+        assert_eq!(keys, vec!["truncated", "unknown-envelope"]);
+        assert!(
+            corrupt[0]["problem"]
+                .as_str()
+                .unwrap()
+                .contains("encrypted data is 1 bytes")
+        );
+        assert_eq!(corrupt[1]["problem"], "Unsupported envelope version: 9");
+    }
+
+    #[tokio::test]
+    async fn test_unused_secrets_skips_recently_read_secrets() {
+        let state = setup_test_state();
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            let (_, public_pem) = generate_key_pair().unwrap();
+            let master_key = MasterKey::new(public_pem).unwrap();
+            state
+                .master_key_repo
+                .create_master_key(&conn, &master_key)
+                .unwrap();
+            for key in ["read", "unread"] {
+                state
+                    .secret_repo
+                    .create_new_version(
+                        &mut conn,
+                        master_key.clone(),
+                        NewSecret::new(key, "value", EnvelopeVersion::V1),
+                    )
+                    .unwrap();
+            }
+            state.secret_repo.record_read(&conn, "read", 1).unwrap();
+        }
+
+        let params = AdminPathParams {
+            version: Version::V1,
+        };
+        let response = unused_secrets(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(StaleKeysQueryParams { days: None }),
+        )
+        .await
+        .expect("Should list unused secrets");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        let unused = body.as_array().unwrap();
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0]["key"], "unread");
+        assert_eq!(unused[0]["read_count"], 0);
+
+        let err = unused_secrets(
+            State(state),
+            Path(params),
+            Query(StaleKeysQueryParams { days: Some(0) }),
+        )
+        .await
+        .expect_err("Zero days should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_stale_keys_skips_recently_used_and_disabled_keys() {
+        let state = setup_test_state();
+        let (used, disabled) = {
+            let conn = state.conn_pool.get().unwrap();
+            let mut keys = Vec::new();
+            for status in [
+                MasterKeyStatus::Active,
+                MasterKeyStatus::Retired,
+                MasterKeyStatus::Disabled,
+            ] {
+                let (_, public_pem) = generate_key_pair().unwrap();
+                let mut master_key = MasterKey::new(public_pem).unwrap();
+                master_key.status = status;
+                state
+                    .master_key_repo
+                    .create_master_key(&conn, &master_key)
+                    .unwrap();
+                keys.push(master_key.id);
+            }
+            state.master_key_repo.mark_used(&conn, &keys[0]).unwrap();
+            (keys[0], keys[2])
+        };
+
+        let params = AdminPathParams {
+            version: Version::V1,
+        };
+        let response = stale_keys(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(StaleKeysQueryParams { days: None }),
+        )
+        .await
+        .expect("Should list stale keys");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        let stale = body.as_array().unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_ne!(stale[0]["id"], json!(used));
+        assert_ne!(stale[0]["id"], json!(disabled));
+        assert_eq!(stale[0]["last_used_at"], json!(null));
+
+        let err = stale_keys(
+            State(state),
+            Path(params),
+            Query(StaleKeysQueryParams { days: Some(0) }),
+        )
+        .await
+        .expect_err("Zero days should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+    }
+}
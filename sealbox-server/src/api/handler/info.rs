@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    api::{SealboxResponse, Version, path::Path},
+    crypto::master_key::KeyAlgorithm,
+    error::{Result, SealboxError},
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct InfoPathParams {
+    version: Version,
+}
+
+impl InfoPathParams {
+    fn version(&self) -> Version {
+        self.version.clone()
+    }
+}
+
+/// API handler describing what this server supports
+///
+/// # Arguments
+///
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns the server version, the API versions it serves and the master key algorithms it accepts
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/info`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "version": "0.1.0",
+///   "api_versions": ["v1"],
+///   "key_algorithms": ["rsa", "x25519"]
+/// }
+/// ```
+pub(crate) async fn get(Path(params): Path<InfoPathParams>) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let key_algorithms: Vec<_> = KeyAlgorithm::ALL
+                .iter()
+                .map(|algorithm| algorithm.as_str())
+                .collect();
+            Ok(SealboxResponse::Json(json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "api_versions": ["v1"],
+                "key_algorithms": key_algorithms,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_info_lists_algorithms() {
+        let response = get(Path(InfoPathParams {
+            version: Version::V1,
+        }))
+        .await
+        .expect("Info should succeed");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["api_versions"], json!(["v1"]));
+        assert_eq!(body["key_algorithms"], json!(["rsa", "x25519"]));
+
+        let err = get(Path(InfoPathParams {
+            version: Version::V2,
+        }))
+        .await
+        .expect_err("Unsupported version should be rejected");
+        assert!(matches!(err, SealboxError::InvalidApiVersion));
+    }
+}
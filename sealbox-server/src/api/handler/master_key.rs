@@ -1,13 +1,19 @@
-use axum::extract::{Json, State};
+use axum::extract::{Json, Query, State};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::time::{Duration, Instant};
 use tracing::error;
 use uuid::Uuid;
 
 use crate::{
-    api::{SealboxResponse, Version, path::Path, state::AppState},
+    api::{
+        SealboxResponse, Version, handler::parse_uuid, job::JobStatus, key_proof::CHALLENGE_TTL,
+        path::Path, state::AppState,
+    },
+    crypto::master_key::KeyAlgorithm,
     error::{Result, SealboxError},
-    repo::MasterKey,
+    repo::{HIDDEN_PUBLIC_KEY, MasterKey, MasterKeyFilter, MasterKeyStatus, Secret},
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -23,27 +29,175 @@ impl MasterKeyPathParams {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct RotateMasterKeyPayload {
+    new_master_key_id: String,
+    old_master_key_id: String,
+    old_private_key_pem: String,
+}
+
+/// Rotation parameters with validated master key ids
+#[derive(Debug, Clone)]
+struct RotationRequest {
     new_master_key_id: Uuid,
     old_master_key_id: Uuid,
     old_private_key_pem: String,
 }
 
-// GET /{version}/master-key
+impl TryFrom<RotateMasterKeyPayload> for RotationRequest {
+    type Error = SealboxError;
+
+    fn try_from(payload: RotateMasterKeyPayload) -> Result<Self> {
+        Ok(Self {
+            new_master_key_id: parse_uuid("new_master_key_id", &payload.new_master_key_id)?,
+            old_master_key_id: parse_uuid("old_master_key_id", &payload.old_master_key_id)?,
+            old_private_key_pem: payload.old_private_key_pem,
+        })
+    }
+}
+
+/// Master key as returned by the API.
+///
+/// Handlers never serialize `MasterKey` directly: the PEM is only echoed back
+/// on creation, and replaced by `HIDDEN_PUBLIC_KEY` everywhere else. Clients that need
+/// to check a stored PEM fetch it on its own with `public_key`, or compare `fingerprint`,
+/// which every response carries.
+#[derive(Debug, Serialize)]
+pub(crate) struct MasterKeyResponse {
+    id: Uuid,
+    public_key: String,
+    created_at: i64,
+    status: MasterKeyStatus,
+    description: Option<String>,
+    metadata: Option<String>,
+    algorithm: KeyAlgorithm,
+    default_ttl: Option<i64>,
+    last_used_at: Option<i64>,
+    fingerprint: Option<String>,
+}
+
+impl MasterKeyResponse {
+    /// Response including the full public key PEM
+    fn with_public_key(master_key: MasterKey) -> Self {
+        Self {
+            id: master_key.id,
+            public_key: master_key.public_key,
+            created_at: master_key.created_at,
+            status: master_key.status,
+            description: master_key.description,
+            metadata: master_key.metadata,
+            algorithm: master_key.algorithm,
+            default_ttl: master_key.default_ttl,
+            last_used_at: master_key.last_used_at,
+            fingerprint: master_key.fingerprint,
+        }
+    }
+
+    /// Response with the public key PEM hidden
+    fn redacted(master_key: MasterKey) -> Self {
+        Self {
+            public_key: HIDDEN_PUBLIC_KEY.to_string(),
+            ..Self::with_public_key(master_key)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListMasterKeysQueryParams {
+    limit: Option<u32>,
+    offset: Option<u32>,
+    status: Option<MasterKeyStatus>,
+    description_contains: Option<String>,
+}
+
+// GET /{version}/master-key[?limit=N&offset=N&status=Active&description_contains=text]
 pub(crate) async fn list(
     State(state): State<AppState>,
     Path(params): Path<MasterKeyPathParams>,
+    Query(query): Query<ListMasterKeysQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let page = state.master_key_repo.list_master_keys(
+                &conn,
+                &MasterKeyFilter {
+                    status: query.status,
+                    description_contains: query.description_contains.as_deref(),
+                    limit: query.limit,
+                    offset: query.offset.unwrap_or(0),
+                },
+            )?;
+            let master_keys: Vec<_> = page
+                .master_keys
+                .into_iter()
+                .map(MasterKeyResponse::redacted)
+                .collect();
+            Ok(SealboxResponse::Json(
+                json!({ "total": page.total, "master_keys": master_keys }),
+            ))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct MasterKeyIdPathParams {
+    version: Version,
+    master_key_id: Uuid,
+}
+
+impl MasterKeyIdPathParams {
+    fn version(&self) -> Version {
+        self.version.clone()
+    }
+}
+
+/// API handler function returning the public key PEM stored for one master key
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and master key id
+///
+/// # Returns
+///
+/// Returns `{id, public_key}` with the PEM exactly as stored
+///
+/// # Errors
+///
+/// * `SealboxError::MasterKeyNotFound` - When no master key has this id
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/master-key/{master_key_id}/public-key`
+///
+/// # Notes
+///
+/// Lets a client confirm the server stored the key it uploaded, e.g. before re-encrypting
+/// every secret with it during rotation.
+pub(crate) async fn public_key(
+    State(state): State<AppState>,
+    Path(params): Path<MasterKeyIdPathParams>,
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let conn = state.conn_pool.lock()?;
-            let master_keys = state.master_key_repo.fetch_all_master_keys(&conn)?;
-            Ok(SealboxResponse::Json(json!(master_keys)))
+            let conn = state.conn_pool.get()?;
+            let public_key = state
+                .master_key_repo
+                .fetch_public_key(&conn, &params.master_key_id)?
+                .ok_or(SealboxError::MasterKeyNotFound(params.master_key_id))?;
+            Ok(SealboxResponse::Json(json!({
+                "id": params.master_key_id,
+                "public_key": public_key,
+            })))
         }
         _ => Err(SealboxError::InvalidApiVersion),
     }
 }
 
 // PUT /{version}/master-key
+//
+// Commits in chunks like `start_rotation`; after an error, calling it again resumes.
 pub(crate) async fn rotate(
     State(state): State<AppState>,
     Path(params): Path<MasterKeyPathParams>,
@@ -51,50 +205,31 @@ pub(crate) async fn rotate(
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let new_master_key_id = payload.new_master_key_id;
-            let old_master_key_id = payload.old_master_key_id;
-            let old_private_key_pem = payload.old_private_key_pem;
+            let request = RotationRequest::try_from(payload)?;
+            let new_master_key_id = request.new_master_key_id;
+            let old_master_key_id = request.old_master_key_id;
 
-            let mut conn = state.conn_pool.lock()?;
-
-            let new_public_key_pem = state
-                .master_key_repo
-                .fetch_public_key(&conn, &new_master_key_id)?
-                .ok_or(SealboxError::MasterKeyNotFound(new_master_key_id))?;
-
-            let secrets = state
-                .secret_repo
-                .fetch_secrets_by_master_key(&conn, &old_master_key_id)?;
+            let (new_public_key_pem, secrets) = {
+                let conn = state.conn_pool.get()?;
+                let new_public_key_pem = state
+                    .master_key_repo
+                    .fetch_public_key(&conn, &new_master_key_id)?
+                    .ok_or(SealboxError::MasterKeyNotFound(new_master_key_id))?;
+                let secrets = state
+                    .secret_repo
+                    .fetch_secrets_by_master_key(&conn, &old_master_key_id)?;
+                (new_public_key_pem, secrets)
+            };
 
             let mut failed_secret_keys = Vec::new();
-
-            let tx = conn.transaction()?;
-
-            for secret in secrets {
-                let secret_key = secret.key.clone();
-
-                match secret.rotate_master_key(
-                    &old_master_key_id,
-                    &old_private_key_pem,
-                    &new_master_key_id,
-                    &new_public_key_pem,
-                ) {
-                    Ok(rotated_secret) => {
-                        state
-                            .secret_repo
-                            .update_secret_master_key(&tx, &rotated_secret)?;
-                    }
-                    Err(err) => {
-                        failed_secret_keys.push(secret_key.clone());
-                        error!(
-                            "Failed to rotate master key for secret {}: {}",
-                            secret_key, err
-                        );
-                    }
-                }
-            }
-
-            tx.commit()?;
+            rotate_secrets(
+                &state,
+                secrets,
+                &request,
+                &new_public_key_pem,
+                ROTATION_LIMITS,
+                |_, failed| failed_secret_keys.extend(failed),
+            )?;
 
             if !failed_secret_keys.is_empty() {
                 return Ok(SealboxResponse::Json(json!({
@@ -111,9 +246,276 @@ pub(crate) async fn rotate(
     }
 }
 
+/// API handler function for starting master key rotation as a background job
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool, repository instances and job registry
+/// * `params` - Path parameters containing API version
+/// * `payload` - Old and new master key ids plus the old private key used to unwrap data keys
+///
+/// # Returns
+///
+/// Returns the id of the job, which can be polled via `GET /{version}/admin/jobs/{job_id}`
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - When a master key id is not a valid UUID
+/// * `SealboxError::MasterKeyNotFound` - When the new master key does not exist
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `POST /{version}/master-key/rotate`
+///
+/// # Notes
+///
+/// Secrets are re-encrypted in chunks, each in its own transaction (see `ROTATION_LIMITS`),
+/// so an interrupted job keeps the chunks it already committed. Starting the rotation again
+/// with the same key ids resumes with the secrets still protected by the old master key.
+pub(crate) async fn start_rotation(
+    State(state): State<AppState>,
+    Path(params): Path<MasterKeyPathParams>,
+    Json(payload): Json<RotateMasterKeyPayload>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let request = RotationRequest::try_from(payload)?;
+            let new_public_key_pem = {
+                let conn = state.conn_pool.get()?;
+                state
+                    .master_key_repo
+                    .fetch_public_key(&conn, &request.new_master_key_id)?
+                    .ok_or(SealboxError::MasterKeyNotFound(request.new_master_key_id))?
+            };
+
+            let job = state.jobs.create(MASTER_KEY_ROTATION_JOB);
+            let job_id = job.id;
+
+            let job_state = state.clone();
+            tokio::task::spawn_blocking(move || {
+                if let Err(err) =
+                    run_rotation_job(&job_state, &job_id, &request, &new_public_key_pem)
+                {
+                    error!("Master key rotation job {} failed: {}", job_id, err);
+                    job_state.jobs.update(&job_id, |job| {
+                        job.status = JobStatus::Failed;
+                        job.error = Some(err.to_string());
+                    });
+                }
+            });
+
+            Ok(SealboxResponse::Json(json!({ "job_id": job_id })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Job kind recorded for background master key rotations
+const MASTER_KEY_ROTATION_JOB: &str = "master_key_rotation";
+
+/// Bounds on the work done in a single database transaction
+#[derive(Debug, Clone, Copy)]
+struct ChunkLimits {
+    max_items: usize,      // Items per transaction
+    time_budget: Duration, // Commit once a transaction has run this long, even if short of `max_items`
+}
+
+/// Rotation transactions hold the write lock, and the connection, while RSA operations run
+const ROTATION_LIMITS: ChunkLimits = ChunkLimits {
+    max_items: 100,
+    time_budget: Duration::from_millis(500),
+};
+
+/// Process a rotation job chunk by chunk, recording progress in the job registry
+fn run_rotation_job(
+    state: &AppState,
+    job_id: &Uuid,
+    request: &RotationRequest,
+    new_public_key_pem: &str,
+) -> Result<()> {
+    let secrets = {
+        let conn = state.conn_pool.get()?;
+        state
+            .secret_repo
+            .fetch_secrets_by_master_key(&conn, &request.old_master_key_id)?
+    };
+
+    state.jobs.update(job_id, |job| {
+        job.status = JobStatus::Running;
+        job.total = secrets.len();
+    });
+
+    rotate_secrets(
+        state,
+        secrets,
+        request,
+        new_public_key_pem,
+        ROTATION_LIMITS,
+        |processed, failed_secret_keys| {
+            state.jobs.update(job_id, |job| {
+                job.processed += processed;
+                job.failed_secret_keys.extend(failed_secret_keys);
+            });
+        },
+    )?;
+
+    state
+        .jobs
+        .update(job_id, |job| job.status = JobStatus::Completed);
+    Ok(())
+}
+
+/// Re-wrap the data keys of `secrets` with the new master key, one chunk per transaction
+///
+/// A chunk is committed after `limits.max_items` secrets, or earlier once its transaction has
+/// run for `limits.time_budget`; every chunk holds at least one secret. The connection is
+/// released between chunks so regular requests are not starved.
+///
+/// After each commit `on_commit` receives the number of secrets handled in the chunk and the
+/// keys of those that could not be rotated; those are left untouched.
+///
+/// # Notes
+///
+/// Progress is partial: if a chunk fails, the chunks before it stay committed. Rotating again
+/// with the same key ids is safe and picks up the secrets still under the old master key.
+fn rotate_secrets(
+    state: &AppState,
+    secrets: Vec<Secret>,
+    request: &RotationRequest,
+    new_public_key_pem: &str,
+    limits: ChunkLimits,
+    mut on_commit: impl FnMut(usize, Vec<String>),
+) -> Result<()> {
+    let mut remaining = secrets.into_iter().peekable();
+    while remaining.peek().is_some() {
+        let mut conn = state.conn_pool.get()?;
+        let tx = conn.transaction()?;
+        let started = Instant::now();
+        let mut processed = 0;
+        let mut failed_secret_keys = Vec::new();
+
+        while processed < limits.max_items
+            && (processed == 0 || started.elapsed() < limits.time_budget)
+        {
+            let Some(secret) = remaining.next() else {
+                break;
+            };
+            processed += 1;
+            failed_secret_keys.extend(rotate_secret(
+                state,
+                &tx,
+                secret,
+                request,
+                new_public_key_pem,
+            )?);
+        }
+
+        tx.commit()?;
+        drop(conn);
+        on_commit(processed, failed_secret_keys);
+    }
+    Ok(())
+}
+
+/// Re-wrap the data key of one secret inside `tx`
+///
+/// Returns the secret's key if it could not be decrypted with the old private key.
+fn rotate_secret(
+    state: &AppState,
+    tx: &rusqlite::Transaction,
+    secret: Secret,
+    request: &RotationRequest,
+    new_public_key_pem: &str,
+) -> Result<Option<String>> {
+    let secret_key = secret.key.clone();
+    match secret.rotate_master_key(
+        &request.old_master_key_id,
+        &request.old_private_key_pem,
+        &request.new_master_key_id,
+        new_public_key_pem,
+    ) {
+        Ok(rotated_secret) => {
+            state
+                .secret_repo
+                .update_secret_master_key(tx, &rotated_secret)?;
+            Ok(None)
+        }
+        Err(err) => {
+            error!(
+                "Failed to rotate master key for secret {}: {}",
+                secret_key, err
+            );
+            Ok(Some(secret_key))
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub(crate) struct CreateMasterKeyPayload {
     public_key: String,
+    #[serde(default)]
+    default_ttl: Option<i64>, // TTL for secrets saved without one while this key is active
+    #[serde(default)]
+    proof: Option<KeyProof>, // Answer to a challenge from `POST /{version}/master-key/challenge`
+}
+
+/// Proof that the caller holds the private key of the master key being registered
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct KeyProof {
+    challenge_id: Uuid,
+    response: String, // Base64 of the decrypted challenge
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct KeyChallengePayload {
+    public_key: String,
+}
+
+/// API handler function for issuing a proof-of-possession challenge for a master key
+///
+/// # Arguments
+///
+/// * `state` - Application state holding the outstanding challenges
+/// * `params` - Path parameters containing API version
+/// * `payload` - Public key PEM of the master key about to be registered
+///
+/// # Returns
+///
+/// Returns the challenge id, the challenge encrypted to the public key (base64) and
+/// the seconds left to answer it
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - When the public key is not a valid PEM
+/// * `SealboxError::RateLimited` - When too many challenges are waiting for an answer
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `POST /{version}/master-key/challenge`
+///
+/// # Security Notes
+///
+/// Only the holder of the private key can decrypt the challenge. Sending it back as
+/// `proof` with `POST /{version}/master-key` shows the caller holds the key pair rather
+/// than a copy of someone else's public key. Each challenge can be answered once.
+pub(crate) async fn challenge(
+    State(state): State<AppState>,
+    Path(params): Path<MasterKeyPathParams>,
+    Json(payload): Json<KeyChallengePayload>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let (challenge_id, encrypted) = state.key_challenges.issue(&payload.public_key)?;
+            Ok(SealboxResponse::Json(json!({
+                "challenge_id": challenge_id,
+                "encrypted_challenge": BASE64.encode(encrypted),
+                "expires_in": CHALLENGE_TTL.as_secs(),
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
 }
 
 // POST /{version}/master-key
@@ -124,12 +526,51 @@ pub(crate) async fn create(
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let conn = state.conn_pool.lock()?;
-            let master_key = MasterKey::new(payload.public_key)?;
+            if let Some(default_ttl) = payload.default_ttl {
+                if default_ttl <= 0 {
+                    return Err(SealboxError::InvalidInput(
+                        "Default TTL must be a positive number of seconds".to_string(),
+                    ));
+                }
+                if let Some(max_ttl) = state.config.snapshot().max_ttl_seconds {
+                    if default_ttl > max_ttl {
+                        return Err(SealboxError::InvalidInput(format!(
+                            "Default TTL {default_ttl}s exceeds the maximum of {max_ttl}s"
+                        )));
+                    }
+                }
+            }
+
+            match &payload.proof {
+                Some(proof) => {
+                    let response = BASE64.decode(&proof.response).unwrap_or_default();
+                    if !state.key_challenges.verify(
+                        &proof.challenge_id,
+                        &payload.public_key,
+                        &response,
+                    ) {
+                        return Err(SealboxError::InvalidInput(
+                            "Invalid or expired proof of private key possession".to_string(),
+                        ));
+                    }
+                }
+                None if state.config.snapshot().require_key_proof => {
+                    return Err(SealboxError::InvalidInput(
+                        "Proof of private key possession is required; request a challenge from /master-key/challenge first".to_string(),
+                    ));
+                }
+                None => {}
+            }
+
+            let conn = state.conn_pool.get()?;
+            let mut master_key = MasterKey::new(payload.public_key)?;
+            master_key.default_ttl = payload.default_ttl;
             state
                 .master_key_repo
                 .create_master_key(&conn, &master_key)?;
-            Ok(SealboxResponse::Json(json!(master_key)))
+            Ok(SealboxResponse::Json(json!(
+                MasterKeyResponse::with_public_key(master_key)
+            )))
         }
         _ => Err(SealboxError::InvalidApiVersion),
     }
@@ -140,24 +581,38 @@ mod tests {
     use super::*;
     use crate::{
         api::{Version, path::Path as SealboxPath, state::AppState},
-        config::SealboxConfig,
-        crypto::master_key::generate_key_pair,
-        repo::{SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo},
+        config::{SealboxConfig, SharedConfig},
+        crypto::{
+            envelope::CURRENT_ENVELOPE_VERSION,
+            master_key::{PublicMasterKey, generate_key_pair, generate_key_pair_with_algorithm},
+        },
+        repo::{
+            NewSecret, SqliteAuditRepo, SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo,
+            create_db_pool,
+        },
     };
     use axum::extract::State;
-    use std::sync::{Arc, Mutex};
+    use std::{str::FromStr, sync::Arc};
 
     fn setup_test_state() -> AppState {
-        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+        let conn_pool = create_db_pool(":memory:", 1).expect("Should create in-memory DB");
+        let conn = conn_pool.get().expect("Should check out a connection");
         crate::repo::SqliteMasterKeyRepo::init_table(&conn).expect("Should init master_keys table");
         crate::repo::SqliteSecretRepo::init_table(&conn).expect("Should init secrets table");
 
         AppState {
-            conn_pool: Arc::new(Mutex::new(conn)),
+            conn_pool,
             master_key_repo: Arc::new(SqliteMasterKeyRepo),
             secret_repo: Arc::new(SqliteSecretRepo),
             health_repo: Arc::new(SqliteHealthRepo),
-            config: Arc::new(SealboxConfig::default()),
+            audit_repo: Arc::new(SqliteAuditRepo),
+            config: SharedConfig::new(SealboxConfig::default()),
+            jobs: Default::default(),
+            secret_changes: Default::default(),
+            metrics: Default::default(),
+            rate_limiter: Default::default(),
+            key_challenges: Default::default(),
+            webhooks: Default::default(),
         }
     }
 
@@ -171,6 +626,8 @@ mod tests {
         };
         let payload = CreateMasterKeyPayload {
             public_key: public_pem.clone(),
+            default_ttl: None,
+            proof: None,
         };
 
         let result = create(
@@ -191,6 +648,30 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_create_master_key_rejects_invalid_pem() {
+        let state = setup_test_state();
+        let payload = CreateMasterKeyPayload {
+            public_key: "not a public key".to_string(),
+            default_ttl: None,
+            proof: None,
+        };
+
+        let err = create(
+            State(state.clone()),
+            SealboxPath(MasterKeyPathParams {
+                version: Version::V1,
+            }),
+            Json(payload),
+        )
+        .await
+        .expect_err("Garbage PEM should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+
+        let conn = state.conn_pool.get().unwrap();
+        assert_eq!(state.master_key_repo.count_master_keys(&conn).unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_create_master_key_invalid_version() {
         let state = setup_test_state();
@@ -201,6 +682,8 @@ mod tests {
         }; // Invalid version
         let payload = CreateMasterKeyPayload {
             public_key: public_pem,
+            default_ttl: None,
+            proof: None,
         };
 
         let result = create(State(state), SealboxPath(path_params), Json(payload)).await;
@@ -212,6 +695,15 @@ mod tests {
         }
     }
 
+    fn no_filter() -> Query<ListMasterKeysQueryParams> {
+        Query(ListMasterKeysQueryParams {
+            limit: None,
+            offset: None,
+            status: None,
+            description_contains: None,
+        })
+    }
+
     #[tokio::test]
     async fn test_list_master_keys_empty() {
         let state = setup_test_state();
@@ -219,13 +711,14 @@ mod tests {
             version: Version::V1,
         };
 
-        let result = list(State(state), Path(path_params)).await;
+        let result = list(State(state), Path(path_params), no_filter()).await;
 
         assert!(result.is_ok());
         match result.unwrap() {
             SealboxResponse::Json(json_value) => {
                 let keys: Vec<MasterKey> =
-                    serde_json::from_value(json_value).expect("Should deserialize Vec<MasterKey>");
+                    serde_json::from_value(json_value["master_keys"].clone())
+                        .expect("Should deserialize Vec<MasterKey>");
                 assert_eq!(keys.len(), 0);
             }
             _ => panic!("Expected JSON response"),
@@ -243,6 +736,8 @@ mod tests {
         };
         let payload = CreateMasterKeyPayload {
             public_key: public_pem.clone(),
+            default_ttl: None,
+            proof: None,
         };
 
         let _create_result = create(
@@ -254,20 +749,197 @@ mod tests {
         .expect("Should create master key");
 
         // Then list all master keys
-        let result = list(State(state), Path(path_params)).await;
+        let result = list(State(state), Path(path_params), no_filter()).await;
 
         assert!(result.is_ok());
         match result.unwrap() {
             SealboxResponse::Json(json_value) => {
                 let keys: Vec<MasterKey> =
-                    serde_json::from_value(json_value).expect("Should deserialize Vec<MasterKey>");
+                    serde_json::from_value(json_value["master_keys"].clone())
+                        .expect("Should deserialize Vec<MasterKey>");
                 assert_eq!(keys.len(), 1);
-                assert_eq!(keys[0].public_key, "[HIDDEN]"); // Public key is hidden in list API for security
+                assert_eq!(json_value["total"], 1);
+                assert_eq!(keys[0].public_key, HIDDEN_PUBLIC_KEY); // Public key is hidden in list API for security
+                // The fingerprint still identifies it
+                let fingerprint = PublicMasterKey::from_str(&public_pem)
+                    .unwrap()
+                    .fingerprint()
+                    .unwrap();
+                assert_eq!(keys[0].fingerprint.as_deref(), Some(fingerprint.as_str()));
+            }
+            _ => panic!("Expected JSON response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_duplicate_master_key_conflicts() {
+        let state = setup_test_state();
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+
+        create(
+            State(state.clone()),
+            Path(path_params.clone()),
+            Json(CreateMasterKeyPayload {
+                public_key: public_pem.clone(),
+                default_ttl: None,
+                proof: None,
+            }),
+        )
+        .await
+        .expect("First registration should succeed");
+
+        let err = create(
+            State(state),
+            Path(path_params),
+            Json(CreateMasterKeyPayload {
+                public_key: public_pem,
+                default_ttl: None,
+                proof: None,
+            }),
+        )
+        .await
+        .expect_err("Duplicate registration should fail");
+        assert!(matches!(err, SealboxError::Conflict(_)));
+        assert_eq!(
+            axum::response::IntoResponse::into_response(err).status(),
+            axum::http::StatusCode::CONFLICT
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_master_keys_never_contains_pem() {
+        let state = setup_test_state();
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+
+        for _ in 0..2 {
+            let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+            create(
+                State(state.clone()),
+                Path(path_params.clone()),
+                Json(CreateMasterKeyPayload {
+                    public_key: public_pem,
+                    default_ttl: None,
+                    proof: None,
+                }),
+            )
+            .await
+            .expect("Should create master key");
+        }
+
+        match list(State(state), Path(path_params), no_filter())
+            .await
+            .unwrap()
+        {
+            SealboxResponse::Json(json_value) => {
+                let body = json_value.to_string();
+                assert!(!body.contains("BEGIN"));
+                assert!(!body.contains("END"));
+            }
+            _ => panic!("Expected JSON response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_key_returns_stored_pem() {
+        let state = setup_test_state();
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let SealboxResponse::Json(created) = create(
+            State(state.clone()),
+            Path(MasterKeyPathParams {
+                version: Version::V1,
+            }),
+            Json(CreateMasterKeyPayload {
+                public_key: public_pem.clone(),
+                default_ttl: None,
+                proof: None,
+            }),
+        )
+        .await
+        .expect("Should create master key") else {
+            panic!("Expected JSON response");
+        };
+        let master_key_id: Uuid = serde_json::from_value(created["id"].clone()).unwrap();
+
+        let SealboxResponse::Json(body) = public_key(
+            State(state.clone()),
+            Path(MasterKeyIdPathParams {
+                version: Version::V1,
+                master_key_id,
+            }),
+        )
+        .await
+        .expect("Should return the public key") else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["public_key"], public_pem);
+
+        let err = public_key(
+            State(state),
+            Path(MasterKeyIdPathParams {
+                version: Version::V1,
+                master_key_id: Uuid::new_v4(),
+            }),
+        )
+        .await
+        .expect_err("Unknown master key should not be found");
+        assert!(matches!(err, SealboxError::MasterKeyNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_create_x25519_master_key_records_algorithm() {
+        let state = setup_test_state();
+        let (_, public_pem) =
+            crate::crypto::master_key::generate_key_pair_with_algorithm(KeyAlgorithm::X25519)
+                .expect("Should generate X25519 key pair");
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+
+        create(
+            State(state.clone()),
+            Path(path_params.clone()),
+            Json(CreateMasterKeyPayload {
+                public_key: public_pem,
+                default_ttl: None,
+                proof: None,
+            }),
+        )
+        .await
+        .expect("Should create master key");
+
+        match list(State(state), Path(path_params), no_filter())
+            .await
+            .unwrap()
+        {
+            SealboxResponse::Json(json_value) => {
+                let keys: Vec<MasterKey> =
+                    serde_json::from_value(json_value["master_keys"].clone()).unwrap();
+                assert_eq!(keys[0].algorithm, KeyAlgorithm::X25519);
             }
             _ => panic!("Expected JSON response"),
         }
     }
 
+    #[test]
+    fn test_master_key_response_redacted() {
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let master_key = MasterKey::new(public_pem.clone()).expect("Should build master key");
+
+        let full = serde_json::to_string(&MasterKeyResponse::with_public_key(master_key.clone()))
+            .expect("Should serialize");
+        assert!(full.contains("BEGIN"));
+
+        let redacted = serde_json::to_string(&MasterKeyResponse::redacted(master_key))
+            .expect("Should serialize");
+        assert!(!redacted.contains("BEGIN"));
+        assert!(redacted.contains(HIDDEN_PUBLIC_KEY));
+    }
+
     #[tokio::test]
     async fn test_list_master_keys_invalid_version() {
         let state = setup_test_state();
@@ -275,7 +947,7 @@ mod tests {
             version: Version::V2,
         }; // Invalid version
 
-        let result = list(State(state), Path(path_params)).await;
+        let result = list(State(state), Path(path_params), no_filter()).await;
 
         assert!(result.is_err());
         match result.unwrap_err() {
@@ -295,8 +967,8 @@ mod tests {
             version: Version::V1,
         };
         let payload = RotateMasterKeyPayload {
-            old_master_key_id,
-            new_master_key_id,
+            old_master_key_id: old_master_key_id.to_string(),
+            new_master_key_id: new_master_key_id.to_string(),
             old_private_key_pem: old_private_pem,
         };
 
@@ -309,6 +981,56 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_rotate_master_key_malformed_id() {
+        let state = setup_test_state();
+        let (old_private_pem, _) = generate_key_pair().expect("Should generate old key pair");
+
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+        let payload = RotateMasterKeyPayload {
+            old_master_key_id: "not-a-uuid".to_string(),
+            new_master_key_id: uuid::Uuid::new_v4().to_string(),
+            old_private_key_pem: old_private_pem,
+        };
+
+        let err = rotate(State(state), SealboxPath(path_params), Json(payload))
+            .await
+            .expect_err("Malformed id should be rejected");
+
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+        let response = axum::response::IntoResponse::into_response(err);
+        assert_eq!(response.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_start_rotation_malformed_id() {
+        let state = setup_test_state();
+        let (old_private_pem, _) = generate_key_pair().expect("Should generate old key pair");
+
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+        let payload = RotateMasterKeyPayload {
+            old_master_key_id: uuid::Uuid::new_v4().to_string(),
+            new_master_key_id: "1234".to_string(),
+            old_private_key_pem: old_private_pem,
+        };
+
+        let result = start_rotation(
+            State(state.clone()),
+            SealboxPath(path_params),
+            Json(payload),
+        )
+        .await;
+
+        match result.unwrap_err() {
+            SealboxError::InvalidInput(message) => assert!(message.contains("new_master_key_id")),
+            _ => panic!("Expected InvalidInput error"),
+        }
+    }
+
     #[tokio::test]
     async fn test_rotate_master_key_invalid_version() {
         let state = setup_test_state();
@@ -320,8 +1042,8 @@ mod tests {
             version: Version::V2,
         }; // Invalid version
         let payload = RotateMasterKeyPayload {
-            old_master_key_id,
-            new_master_key_id,
+            old_master_key_id: old_master_key_id.to_string(),
+            new_master_key_id: new_master_key_id.to_string(),
             old_private_key_pem: old_private_pem,
         };
 
@@ -333,4 +1055,405 @@ mod tests {
             _ => panic!("Expected InvalidApiVersion error"),
         }
     }
+
+    /// Poll the job registry until the job leaves the pending/running states
+    async fn wait_for_job(state: &AppState, job_id: &Uuid) -> crate::api::job::Job {
+        for _ in 0..200 {
+            let job = state.jobs.get(job_id).expect("Job should be registered");
+            if matches!(job.status, JobStatus::Completed | JobStatus::Failed) {
+                return job;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("Job {job_id} did not finish in time");
+    }
+
+    #[tokio::test]
+    async fn test_start_rotation_creates_job() {
+        let state = setup_test_state();
+        let (old_private_pem, _) = generate_key_pair().expect("Should generate old key pair");
+        let (_, new_public_pem) = generate_key_pair().expect("Should generate new key pair");
+        let new_master_key = MasterKey::new(new_public_pem).expect("Should create master key");
+        {
+            let conn = state.conn_pool.get().unwrap();
+            state
+                .master_key_repo
+                .create_master_key(&conn, &new_master_key)
+                .expect("Should store new master key");
+        }
+
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+        let payload = RotateMasterKeyPayload {
+            old_master_key_id: Uuid::new_v4().to_string(),
+            new_master_key_id: new_master_key.id.to_string(),
+            old_private_key_pem: old_private_pem,
+        };
+
+        let result = start_rotation(
+            State(state.clone()),
+            SealboxPath(path_params),
+            Json(payload),
+        )
+        .await
+        .expect("Should start rotation job");
+
+        let job_id: Uuid = match result {
+            SealboxResponse::Json(json_value) => {
+                serde_json::from_value(json_value["job_id"].clone()).expect("Should return job id")
+            }
+            _ => panic!("Expected JSON response"),
+        };
+
+        let job = state.jobs.get(&job_id).expect("Job should be registered");
+        assert_eq!(job.kind, MASTER_KEY_ROTATION_JOB);
+
+        // Nothing is protected by the old key, so the job finishes with no work
+        let job = wait_for_job(&state, &job_id).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_start_rotation_new_key_not_found() {
+        let state = setup_test_state();
+        let (old_private_pem, _) = generate_key_pair().expect("Should generate old key pair");
+
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+        let payload = RotateMasterKeyPayload {
+            old_master_key_id: Uuid::new_v4().to_string(),
+            new_master_key_id: Uuid::new_v4().to_string(),
+            old_private_key_pem: old_private_pem,
+        };
+
+        let result = start_rotation(State(state), SealboxPath(path_params), Json(payload)).await;
+
+        match result.unwrap_err() {
+            SealboxError::MasterKeyNotFound(_) => {} // Expected
+            _ => panic!("Expected MasterKeyNotFound error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rotation_job_completes_with_failures() {
+        let state = setup_test_state();
+        let (old_private_pem, old_public_pem) =
+            generate_key_pair().expect("Should generate old key pair");
+        let (_, new_public_pem) = generate_key_pair().expect("Should generate new key pair");
+        let (_, rogue_public_pem) = generate_key_pair().expect("Should generate rogue key pair");
+
+        let old_master_key = MasterKey::new(old_public_pem).expect("Should create old key");
+        let new_master_key = MasterKey::new(new_public_pem).expect("Should create new key");
+        // Claims to be the old key but wraps data keys the old private key cannot open
+        let mut rogue_master_key = MasterKey::new(rogue_public_pem).expect("Should create key");
+        rogue_master_key.id = old_master_key.id;
+
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            for master_key in [&old_master_key, &new_master_key] {
+                state
+                    .master_key_repo
+                    .create_master_key(&conn, master_key)
+                    .expect("Should store master key");
+            }
+            for key in ["db-password", "api-token"] {
+                state
+                    .secret_repo
+                    .create_new_version(
+                        &mut conn,
+                        old_master_key.clone(),
+                        NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION),
+                    )
+                    .expect("Should create secret");
+            }
+            state
+                .secret_repo
+                .create_new_version(
+                    &mut conn,
+                    rogue_master_key,
+                    NewSecret::new("broken", "value", CURRENT_ENVELOPE_VERSION),
+                )
+                .expect("Should create broken secret");
+        }
+
+        let path_params = MasterKeyPathParams {
+            version: Version::V1,
+        };
+        let payload = RotateMasterKeyPayload {
+            old_master_key_id: old_master_key.id.to_string(),
+            new_master_key_id: new_master_key.id.to_string(),
+            old_private_key_pem: old_private_pem,
+        };
+
+        let result = start_rotation(
+            State(state.clone()),
+            SealboxPath(path_params),
+            Json(payload),
+        )
+        .await
+        .expect("Should start rotation job");
+        let job_id: Uuid = match result {
+            SealboxResponse::Json(json_value) => {
+                serde_json::from_value(json_value["job_id"].clone()).expect("Should return job id")
+            }
+            _ => panic!("Expected JSON response"),
+        };
+
+        let job = wait_for_job(&state, &job_id).await;
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.total, 3);
+        assert_eq!(job.processed, 3);
+        assert_eq!(job.failed_secret_keys, vec!["broken".to_string()]);
+        assert!(job.error.is_none());
+
+        let mut conn = state.conn_pool.get().unwrap();
+        let rotated = state
+            .secret_repo
+            .get_secret(&mut conn, "db-password")
+            .expect("Should fetch rotated secret");
+        assert_eq!(rotated.master_key_id, new_master_key.id);
+        let broken = state
+            .secret_repo
+            .get_secret(&mut conn, "broken")
+            .expect("Should fetch broken secret");
+        assert_eq!(broken.master_key_id, old_master_key.id);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_commits_in_chunks() {
+        let state = setup_test_state();
+        let (old_private_pem, old_public_pem) =
+            generate_key_pair_with_algorithm(KeyAlgorithm::X25519).expect("Should generate");
+        let (new_private_pem, new_public_pem) =
+            generate_key_pair_with_algorithm(KeyAlgorithm::X25519).expect("Should generate");
+        let old_master_key = MasterKey::new(old_public_pem.clone()).expect("Should create key");
+        let new_master_key = MasterKey::new(new_public_pem.clone()).expect("Should create key");
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            for master_key in [&old_master_key, &new_master_key] {
+                state
+                    .master_key_repo
+                    .create_master_key(&conn, master_key)
+                    .expect("Should store master key");
+            }
+            for i in 0..5 {
+                state
+                    .secret_repo
+                    .create_new_version(
+                        &mut conn,
+                        old_master_key.clone(),
+                        NewSecret::new(&format!("secret-{i}"), "value", CURRENT_ENVELOPE_VERSION),
+                    )
+                    .expect("Should create secret");
+            }
+        }
+        let secrets_under = |master_key: &MasterKey| {
+            let conn = state.conn_pool.get().unwrap();
+            state
+                .secret_repo
+                .fetch_secrets_by_master_key(&conn, &master_key.id)
+                .expect("Should fetch secrets")
+        };
+
+        // Each chunk is committed, and the connection released, before the next starts
+        let mut chunks = Vec::new();
+        rotate_secrets(
+            &state,
+            secrets_under(&old_master_key),
+            &RotationRequest {
+                new_master_key_id: new_master_key.id,
+                old_master_key_id: old_master_key.id,
+                old_private_key_pem: old_private_pem,
+            },
+            &new_public_pem,
+            ChunkLimits {
+                max_items: 2,
+                time_budget: Duration::from_secs(60),
+            },
+            |processed, failed| {
+                assert!(failed.is_empty());
+                chunks.push(processed);
+                let rotated: usize = chunks.iter().sum();
+                assert_eq!(secrets_under(&new_master_key).len(), rotated);
+            },
+        )
+        .expect("Rotation should succeed");
+        assert_eq!(chunks, [2, 2, 1]);
+
+        // An exhausted time budget still makes progress, one secret per transaction
+        let mut chunks = Vec::new();
+        rotate_secrets(
+            &state,
+            secrets_under(&new_master_key),
+            &RotationRequest {
+                new_master_key_id: old_master_key.id,
+                old_master_key_id: new_master_key.id,
+                old_private_key_pem: new_private_pem,
+            },
+            &old_public_pem,
+            ChunkLimits {
+                max_items: 100,
+                time_budget: Duration::ZERO,
+            },
+            |processed, _| chunks.push(processed),
+        )
+        .expect("Rotation should succeed");
+        assert_eq!(chunks, [1, 1, 1, 1, 1]);
+        assert_eq!(secrets_under(&old_master_key).len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_create_master_key_rejects_invalid_default_ttl() {
+        let state = setup_test_state();
+        state.config.reload(SealboxConfig {
+            max_ttl_seconds: Some(3600),
+            ..SealboxConfig::default()
+        });
+
+        for default_ttl in [0, 7200] {
+            let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+            let err = create(
+                State(state.clone()),
+                SealboxPath(MasterKeyPathParams {
+                    version: Version::V1,
+                }),
+                Json(CreateMasterKeyPayload {
+                    public_key: public_pem,
+                    default_ttl: Some(default_ttl),
+                    proof: None,
+                }),
+            )
+            .await
+            .expect_err("Invalid default TTL should be rejected");
+            assert!(matches!(err, SealboxError::InvalidInput(_)));
+        }
+    }
+
+    /// Request a challenge for `public_pem` and answer it with `private_pem`
+    async fn answer_challenge(state: &AppState, public_pem: &str, private_pem: &str) -> KeyProof {
+        let response = challenge(
+            State(state.clone()),
+            SealboxPath(MasterKeyPathParams {
+                version: Version::V1,
+            }),
+            Json(KeyChallengePayload {
+                public_key: public_pem.to_string(),
+            }),
+        )
+        .await
+        .expect("Should issue challenge");
+        let SealboxResponse::Json(json_value) = response else {
+            panic!("Expected JSON response");
+        };
+        let encrypted = BASE64
+            .decode(json_value["encrypted_challenge"].as_str().unwrap())
+            .unwrap();
+        let nonce = crate::crypto::master_key::PrivateMasterKey::from_str(private_pem)
+            .unwrap()
+            .decrypt(&encrypted)
+            .unwrap();
+        KeyProof {
+            challenge_id: serde_json::from_value(json_value["challenge_id"].clone()).unwrap(),
+            response: BASE64.encode(nonce),
+        }
+    }
+
+    async fn create_with_proof(
+        state: &AppState,
+        public_pem: &str,
+        proof: Option<KeyProof>,
+    ) -> Result<SealboxResponse> {
+        create(
+            State(state.clone()),
+            SealboxPath(MasterKeyPathParams {
+                version: Version::V1,
+            }),
+            Json(CreateMasterKeyPayload {
+                public_key: public_pem.to_string(),
+                default_ttl: None,
+                proof,
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_create_master_key_with_proof_of_possession() {
+        let state = setup_test_state();
+        state.config.reload(SealboxConfig {
+            require_key_proof: true,
+            ..SealboxConfig::default()
+        });
+        let (private_pem, public_pem) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519)
+            .expect("Should generate key pair");
+
+        let err = create_with_proof(&state, &public_pem, None)
+            .await
+            .expect_err("Registration without proof should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+
+        let proof = answer_challenge(&state, &public_pem, &private_pem).await;
+        create_with_proof(&state, &public_pem, Some(proof.clone()))
+            .await
+            .expect("Valid proof should be accepted");
+
+        // A challenge can only be answered once
+        let (_, other_public_pem) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519)
+            .expect("Should generate key pair");
+        let err = create_with_proof(&state, &other_public_pem, Some(proof))
+            .await
+            .expect_err("Reused proof should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_create_master_key_rejects_invalid_proof() {
+        // Proofs are checked whenever sent, even if not required
+        let state = setup_test_state();
+        let (private_pem, public_pem) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519)
+            .expect("Should generate key pair");
+        let (other_private_pem, _) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519)
+            .expect("Should generate key pair");
+
+        let first = answer_challenge(&state, &public_pem, &private_pem).await;
+        let second = answer_challenge(&state, &public_pem, &private_pem).await;
+        let mismatched = KeyProof {
+            challenge_id: second.challenge_id,
+            response: first.response,
+        };
+        let err = create_with_proof(&state, &public_pem, Some(mismatched))
+            .await
+            .expect_err("Answer to another challenge should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+
+        let err = challenge(
+            State(state.clone()),
+            SealboxPath(MasterKeyPathParams {
+                version: Version::V1,
+            }),
+            Json(KeyChallengePayload {
+                public_key: other_private_pem,
+            }),
+        )
+        .await
+        .expect_err("Challenges are only issued for public keys");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+
+        let forged = KeyProof {
+            challenge_id: Uuid::new_v4(),
+            response: BASE64.encode([0u8; 32]),
+        };
+        let err = create_with_proof(&state, &public_pem, Some(forged))
+            .await
+            .expect_err("Unknown challenge should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+
+        // Without a proof the key is still accepted when proofs are optional
+        create_with_proof(&state, &public_pem, None)
+            .await
+            .expect("Proof is optional by default");
+    }
 }
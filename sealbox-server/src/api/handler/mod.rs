@@ -1,3 +1,14 @@
+use uuid::Uuid;
+
+use crate::error::{Result, SealboxError};
+
 pub(crate) mod admin;
+pub(crate) mod info;
 pub(crate) mod master_key;
 pub(crate) mod secret;
+
+/// Parse a UUID supplied by the client, rejecting malformed values with `400 Bad Request`
+pub(crate) fn parse_uuid(field: &str, value: &str) -> Result<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|_| SealboxError::InvalidInput(format!("'{field}' is not a valid UUID: {value}")))
+}
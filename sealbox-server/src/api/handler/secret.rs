@@ -1,10 +1,26 @@
-use axum::extract::{Json, Query, State};
+use axum::{
+    extract::{Json, Query, State},
+    http::{HeaderMap, StatusCode, header},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use tracing::warn;
 
 use crate::{
-    api::{SealboxResponse, Version, path::Path, state::AppState},
+    api::{
+        SealboxResponse, Version, audit::truncate_user_agent, auth::require_totp, path::Path,
+        state::AppState, validation::Validator,
+    },
+    config::SealboxConfig,
+    crypto::{
+        data_key::{AES_GCM_NONCE_LEN, AES_GCM_TAG_LEN},
+        envelope::EnvelopeVersion,
+        master_key::{PrivateMasterKey, PublicMasterKey},
+    },
     error::{Result, SealboxError},
+    repo::{EncryptedSecret, NewSecret, Secret, SecretFilter, SecretValue},
 };
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -22,6 +38,13 @@ impl SecretPathParams {
     }
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct SecretVersionPathParams {
+    version: Version,
+    secret_key: String,
+    secret_version: i32,
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct GetSecretQueryParams {
     version: Option<i32>,
@@ -34,10 +57,12 @@ pub(crate) struct GetSecretQueryParams {
 /// * `state` - Application state containing database connection pool and repository instances
 /// * `params` - Path parameters containing API version and secret key name
 /// * `query` - Query parameters with optional version number for retrieving specific version
+/// * `headers` - Request headers; `If-None-Match` is compared with the version's ETag
 ///
 /// # Returns
 ///
-/// Returns encrypted secret data containing encrypted content and encrypted data key
+/// Returns encrypted secret data containing encrypted content and encrypted data key, with an
+/// `ETag` header. Returns `304 Not Modified` without a body when `If-None-Match` matches it
 ///
 /// # Errors
 ///
@@ -51,14 +76,18 @@ pub(crate) struct GetSecretQueryParams {
 /// # Security Notes
 ///
 /// If no version number is specified, returns the latest version. The returned data is still encrypted and requires the client to decrypt it using the corresponding private key.
+/// Each read updates `last_used_at` of the master key the secret is wrapped with, and counts
+/// towards the version's `read_count` and `last_read_at`. Failing to count a read is logged
+/// and does not fail the request.
 pub(crate) async fn get(
     State(state): State<AppState>,
     Path(params): Path<SecretPathParams>,
     Query(query): Query<GetSecretQueryParams>,
+    headers: HeaderMap,
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let mut conn = state.conn_pool.lock()?;
+            let mut conn = state.conn_pool.get()?;
 
             let secret = match query.version {
                 Some(version) => state.secret_repo.get_secret_by_version(
@@ -70,8 +99,218 @@ pub(crate) async fn get(
                     .secret_repo
                     .get_secret(&mut conn, &params.secret_key())?,
             };
+            // Lets operators find master keys nothing reads any more
+            state
+                .master_key_repo
+                .mark_used(&conn, &secret.master_key_id)?;
+            // Lets operators find secrets nothing reads any more
+            if let Err(err) = state
+                .secret_repo
+                .record_read(&conn, &secret.key, secret.version)
+            {
+                warn!("Failed to record a read of {}: {}", secret.key, err);
+            }
 
-            Ok(SealboxResponse::Json(json!(secret)))
+            let etag = secret_etag(&secret);
+            let unchanged = headers
+                .get(header::IF_NONE_MATCH)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|if_none_match| etag_matches(if_none_match, &etag));
+            if unchanged {
+                return Ok(SealboxResponse::NotModified(Some(etag)));
+            }
+            Ok(SealboxResponse::JsonWithEtag(etag, json!(secret)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Strong ETag of a stored secret version
+///
+/// Covers the wrapped data key as well as the version and `updated_at`, so re-wrapping during
+/// key rotation changes the ETag even within the same second.
+fn secret_etag(secret: &Secret) -> String {
+    let digest = Sha256::new()
+        .chain_update(secret.version.to_be_bytes())
+        .chain_update(secret.updated_at.to_be_bytes())
+        .chain_update(&secret.encrypted_data_key)
+        .finalize();
+    let hex: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("\"{hex}\"")
+}
+
+/// Whether an `If-None-Match` header value lists `etag` (weak comparison, RFC 9110)
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// API handler function for listing all versions of a secret
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+///
+/// # Returns
+///
+/// Returns the metadata of every unexpired version of the secret, newest version first
+///
+/// # Errors
+///
+/// * `SealboxError::SecretNotFound` - When the secret has no unexpired versions
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/secrets/{secret_key}/versions`
+///
+/// # Security Notes
+///
+/// Returns only metadata about each version, not the encrypted content.
+pub(crate) async fn versions(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let key = params.secret_key();
+            let versions = state.secret_repo.list_secret_versions(&conn, &key)?;
+            Ok(SealboxResponse::Json(
+                json!({ "key": key, "versions": versions }),
+            ))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler function for checking whether a secret exists
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+///
+/// # Returns
+///
+/// Returns the `version`, `created_at`, `updated_at` and `expires_at` of the latest
+/// unexpired version, with the `read_count` and `last_read_at` of all unexpired versions
+///
+/// # Errors
+///
+/// * `SealboxError::SecretNotFound` - When the secret has no unexpired versions
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/secrets/{secret_key}/meta`
+///
+/// # Security Notes
+///
+/// Returns no ciphertext, so nothing is sent that could be decrypted and no read of the
+/// value is audited.
+pub(crate) async fn meta(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let key = params.secret_key();
+            let versions = state.secret_repo.list_secret_versions(&conn, &key)?;
+            let latest = versions
+                .first()
+                .ok_or_else(|| SealboxError::SecretNotFound(key.clone()))?;
+            Ok(SealboxResponse::Json(json!({
+                "version": latest.version,
+                "created_at": latest.created_at,
+                "updated_at": latest.updated_at,
+                "expires_at": latest.expires_at,
+                "read_count": versions.iter().map(|v| v.read_count).sum::<u64>(),
+                "last_read_at": versions.iter().filter_map(|v| v.last_read_at).max(),
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// Default and upper bound for how long a watch request may wait, in seconds
+const DEFAULT_WATCH_TIMEOUT_SECS: u64 = 30;
+const MAX_WATCH_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct WatchSecretQueryParams {
+    #[serde(default)]
+    since_version: i32,
+    timeout: Option<u64>,
+}
+
+/// Latest version of `key` if it is newer than `since_version`
+fn newer_version(state: &AppState, key: &str, since_version: i32) -> Result<Option<Secret>> {
+    let mut conn = state.conn_pool.get()?;
+    match state.secret_repo.get_secret(&mut conn, key) {
+        Ok(secret) if secret.version > since_version => Ok(Some(secret)),
+        Ok(_) | Err(SealboxError::SecretNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// API handler function for long-polling a secret until a new version appears
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `query` - The last version the client has seen and an optional timeout in seconds
+///
+/// # Returns
+///
+/// Returns the latest encrypted secret, same as `get`, as soon as its version is newer than
+/// `since_version`. Returns `304 Not Modified` with an empty body if no newer version shows up
+/// before the timeout (default 30 seconds, at most 60).
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/secrets/{secret_key}/watch?since_version=N[&timeout=S]`
+///
+/// # Notes
+///
+/// Watching a key that does not exist yet waits for its first version.
+pub(crate) async fn watch(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+    Query(query): Query<WatchSecretQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let key = params.secret_key();
+            let timeout = query
+                .timeout
+                .unwrap_or(DEFAULT_WATCH_TIMEOUT_SECS)
+                .min(MAX_WATCH_TIMEOUT_SECS);
+            let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout);
+
+            // Subscribe before the first check so a save in between is not missed
+            let mut changes = state.secret_changes.subscribe();
+            loop {
+                if let Some(secret) = newer_version(&state, &key, query.since_version)? {
+                    return Ok(SealboxResponse::Json(json!(secret)));
+                }
+
+                match tokio::time::timeout_at(deadline, changes.changed()).await {
+                    Ok(Ok(())) => continue,
+                    Ok(Err(_)) | Err(_) => return Ok(SealboxResponse::NotModified(None)),
+                }
+            }
         }
         _ => Err(SealboxError::InvalidApiVersion),
     }
@@ -81,26 +320,59 @@ pub(crate) async fn get(
 pub(crate) struct SaveSecretPayload {
     secret: String, // Now receives plaintext instead of encrypted data
     ttl: Option<i64>,
+    #[serde(default)]
+    tags: Vec<String>,
+    compress: Option<bool>, // Compress before encrypting, defaults to SEALBOX_COMPRESS_SECRETS
+    rotate_after: Option<i64>, // When the new version is due for rotation (Unix time)
+    description: Option<String>, // Plaintext note, kept from the previous version if omitted
 }
 
 // PUT /{version}/secrets/{secret_key}
 pub(crate) async fn save(
     State(state): State<AppState>,
     Path(params): Path<SecretPathParams>,
+    headers: HeaderMap,
     Json(payload): Json<SaveSecretPayload>,
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let mut conn = state.conn_pool.lock()?;
+            let mut conn = state.conn_pool.get()?;
             let master_key = state.master_key_repo.get_valid_master_key(&conn)?;
 
+            let config = state.config.snapshot();
+            check_secret_size(&config, payload.secret.len())?;
+            // Without an explicit TTL the secret inherits the master key's default
+            let ttl = payload.ttl.or(master_key.default_ttl);
+            validate_new_secret(
+                &config,
+                &params.secret_key(),
+                ttl,
+                &payload.tags,
+                payload.rotate_after,
+                payload.description.as_deref(),
+            )?;
+
+            let compress = payload.compress.unwrap_or(config.compress_secrets);
             let secret = state.secret_repo.create_new_version(
                 &mut conn,
-                &params.secret_key(),
-                &payload.secret,
                 master_key,
-                payload.ttl,
+                NewSecret {
+                    key: params.secret_key(),
+                    value: SecretValue::Plaintext {
+                        data: payload.secret,
+                        envelope: EnvelopeVersion::for_new_secret(compress),
+                    },
+                    ttl,
+                    tags: payload.tags,
+                    rotate_after: payload.rotate_after,
+                    description: payload.description.map(|d| d.trim().to_string()),
+                    user_agent: user_agent(&headers),
+                },
             )?;
+            state.secret_changes.notify();
+            state
+                .webhooks
+                .notify(&config, "secret.save", &secret.key, secret.version);
 
             Ok(SealboxResponse::Json(json!(secret)))
         }
@@ -108,74 +380,2112 @@ pub(crate) async fn save(
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub(crate) struct DeleteSecretQueryParams {
-    version: i32,
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct SaveEncryptedSecretPayload {
+    encrypted_data: Vec<u8>, // Payload encrypted by the client with a fresh data key
+    encrypted_data_key: Vec<u8>, // That data key, wrapped with the active master key
+    master_key_id: uuid::Uuid, // Master key the data key was wrapped with
+    envelope_version: Option<i32>, // Envelope format the client used, defaults to 1
+    ttl: Option<i64>,
+    #[serde(default)]
+    tags: Vec<String>,
+    rotate_after: Option<i64>, // When the new version is due for rotation (Unix time)
+    description: Option<String>, // Plaintext note, kept from the previous version if omitted
 }
 
-// DELETE /{version}/secrets/{secret_key}
-pub(crate) async fn delete(
+/// API handler function for saving a secret the client already encrypted
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `payload` - Request body with the encrypted payload, wrapped data key, TTL, tags,
+///   rotation reminder and description
+///
+/// # Returns
+///
+/// Returns the stored secret, like `PUT /{version}/secrets/{secret_key}`
+///
+/// # Errors
+///
+/// * `SealboxError::MissingValidMasterKey` - When no master key is active
+/// * `SealboxError::ValidationFailed` - When the data key was not wrapped with the active
+///   master key, the envelope version is unknown, a blob is empty, or TTL, tags, rotation
+///   reminder and description are invalid
+/// * `SealboxError::InvalidInput` - When the wrapped data key does not match the active
+///   master key's size, the encrypted data is too short for its nonce and tag, or its
+///   ciphertext is larger than `SEALBOX_MAX_SECRET_BYTES`
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `PUT /{version}/secrets/{secret_key}/encrypted`
+///
+/// # Security Notes
+///
+/// The blobs are stored verbatim, so the server never holds the plaintext or the data key.
+/// It only checks their structure, not that they decrypt, which is the client's
+/// responsibility.
+pub(crate) async fn save_encrypted(
     State(state): State<AppState>,
     Path(params): Path<SecretPathParams>,
-    Query(query): Query<DeleteSecretQueryParams>,
+    headers: HeaderMap,
+    Json(payload): Json<SaveEncryptedSecretPayload>,
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let conn = state.conn_pool.lock()?;
-            state.secret_repo.delete_secret_by_version(
-                &conn,
+            let mut conn = state.conn_pool.get()?;
+            let master_key = state.master_key_repo.get_valid_master_key(&conn)?;
+
+            let config = state.config.snapshot();
+            let ttl = payload.ttl.or(master_key.default_ttl);
+            validate_new_secret(
+                &config,
                 &params.secret_key(),
-                query.version,
+                ttl,
+                &payload.tags,
+                payload.rotate_after,
+                payload.description.as_deref(),
             )?;
-            Ok(SealboxResponse::Ok)
+
+            let mut validator = Validator::default();
+            validator.check(payload.master_key_id == master_key.id, || {
+                format!(
+                    "Data key must be wrapped with the active master key {}, got {}",
+                    master_key.id, payload.master_key_id
+                )
+            });
+            let envelope = EnvelopeVersion::try_from(payload.envelope_version.unwrap_or(1));
+            match &envelope {
+                // The version number is assigned here, so the client cannot bind to it
+                Ok(envelope) => validator.check(!envelope.params().aad, || {
+                    format!(
+                        "Envelope version {} binds the payload to the secret version, which \
+                         clients cannot know in advance; use version 1 or 2",
+                        envelope.as_i32()
+                    )
+                }),
+                Err(err) => validator.check(false, || err.to_string()),
+            }
+            validator.check(!payload.encrypted_data.is_empty(), || {
+                "Encrypted data must not be empty".to_string()
+            });
+            validator.check(!payload.encrypted_data_key.is_empty(), || {
+                "Encrypted data key must not be empty".to_string()
+            });
+            validator.finish()?;
+
+            // Blobs too short for their nonce and tag would only fail later, for every reader
+            let envelope = envelope?;
+            let public_key = PublicMasterKey::from_str(&master_key.public_key)?;
+            envelope
+                .check_structure(
+                    &public_key,
+                    &payload.encrypted_data_key,
+                    &payload.encrypted_data,
+                )
+                .map_err(|err| SealboxError::InvalidInput(err.to_string()))?;
+            // The ciphertext without its nonce and tag is as long as the value it seals
+            check_secret_size(
+                &config,
+                payload.encrypted_data.len() - AES_GCM_NONCE_LEN - AES_GCM_TAG_LEN,
+            )?;
+
+            let secret = state.secret_repo.create_new_version(
+                &mut conn,
+                master_key,
+                NewSecret {
+                    key: params.secret_key(),
+                    value: SecretValue::Encrypted(EncryptedSecret {
+                        encrypted_data: payload.encrypted_data,
+                        encrypted_data_key: payload.encrypted_data_key,
+                        master_key_id: payload.master_key_id,
+                        envelope,
+                    }),
+                    ttl,
+                    tags: payload.tags,
+                    rotate_after: payload.rotate_after,
+                    description: payload.description.map(|d| d.trim().to_string()),
+                    user_agent: user_agent(&headers),
+                },
+            )?;
+            state.secret_changes.notify();
+            state
+                .webhooks
+                .notify(&config, "secret.save", &secret.key, secret.version);
+
+            Ok(SealboxResponse::Json(json!(secret)))
         }
         _ => Err(SealboxError::InvalidApiVersion),
     }
 }
 
+/// Check a secret about to be saved against the configured limits, reporting every problem
+fn validate_new_secret(
+    config: &SealboxConfig,
+    key: &str,
+    ttl: Option<i64>,
+    tags: &[String],
+    rotate_after: Option<i64>,
+    description: Option<&str>,
+) -> Result<()> {
+    let mut validator = Validator::default();
+
+    validator.check(!key.trim().is_empty(), || {
+        "Secret key must not be empty".to_string()
+    });
+    validator.check(tags.iter().all(|tag| !tag.trim().is_empty()), || {
+        "Tags must not be empty".to_string()
+    });
+    if let Some(ttl) = ttl {
+        validator.check(ttl > 0, || format!("TTL must be positive, got {ttl}s"));
+        if let Some(max_ttl) = config.max_ttl_seconds {
+            validator.check(ttl <= max_ttl, || {
+                format!("TTL {ttl}s exceeds the maximum of {max_ttl}s")
+            });
+        }
+    }
+
+    if let Some(rotate_after) = rotate_after {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        validator.check(rotate_after > now, || {
+            format!("Rotation reminder must be in the future, got {rotate_after}")
+        });
+    }
+    if let Some(description) = description {
+        validator.check(description.chars().count() <= MAX_DESCRIPTION_LEN, || {
+            format!("Description must be at most {MAX_DESCRIPTION_LEN} characters")
+        });
+    }
+
+    validator.finish()
+}
+
+/// `User-Agent` of the client making the request, if it named itself
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(truncate_user_agent)
+        .filter(|user_agent| !user_agent.is_empty())
+        .map(str::to_string)
+}
+
+/// Reject values larger than `SEALBOX_MAX_SECRET_BYTES`, given their length in bytes
+fn check_secret_size(config: &SealboxConfig, len: usize) -> Result<()> {
+    if len > config.max_secret_bytes {
+        return Err(SealboxError::InvalidInput(format!(
+            "Secret is {len} bytes, the maximum is {} bytes (SEALBOX_MAX_SECRET_BYTES)",
+            config.max_secret_bytes
+        )));
+    }
+    Ok(())
+}
+
+/// Longest description accepted for a secret, in characters
+const MAX_DESCRIPTION_LEN: usize = 1024;
+
+/// Largest number of secrets accepted by one batch request
+const MAX_BATCH_SIZE: usize = 1000;
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
-pub(crate) struct ListSecretsPathParams {
+pub(crate) struct BatchPathParams {
     version: Version,
 }
 
-impl ListSecretsPathParams {
+impl BatchPathParams {
     fn version(&self) -> Version {
         self.version.clone()
     }
 }
 
-/// API handler function for listing all secrets
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct BatchSecretEntry {
+    key: String,
+    secret: String,
+    ttl: Option<i64>,
+    #[serde(default)]
+    tags: Vec<String>,
+    compress: Option<bool>,
+    rotate_after: Option<i64>, // When the new version is due for rotation (Unix time)
+    description: Option<String>, // Plaintext note, kept from the previous version if omitted
+}
+
+impl From<BatchSecretEntry> for NewSecret {
+    fn from(entry: BatchSecretEntry) -> Self {
+        NewSecret {
+            ttl: entry.ttl,
+            tags: entry.tags,
+            rotate_after: entry.rotate_after,
+            description: entry.description.map(|d| d.trim().to_string()),
+            ..NewSecret::new(
+                &entry.key,
+                &entry.secret,
+                EnvelopeVersion::for_new_secret(entry.compress.unwrap_or(false)),
+            )
+        }
+    }
+}
+
+/// Save many secrets atomically
+///
+/// Every entry gets a new version inside one transaction. If any entry is invalid
+/// or fails to be written, nothing is saved.
 ///
 /// # Arguments
 ///
-/// * `state` - Application state containing database connection pool and repository instances
-/// * `params` - Path parameters containing API version
+/// * `headers` - Request headers; the `User-Agent` is recorded with every new version
+/// * `payload` - Array of `{key, secret, ttl, tags, rotate_after, description}` objects
 ///
 /// # Returns
 ///
-/// Returns a list of secrets with basic information (key, version, timestamps)
+/// `{committed, results}` where `results` holds one `{key, ok, version | error}` object
+/// per entry, in request order. Responds with `422 Unprocessable Entity` when nothing
+/// was committed.
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidInput` - The batch is empty or larger than `MAX_BATCH_SIZE`
+///
+/// An entry whose secret is larger than `SEALBOX_MAX_SECRET_BYTES` fails like an invalid one.
+///
+/// # HTTP Route
+///
+/// `POST /{version}/secrets:batch`
+pub(crate) async fn batch_save(
+    State(state): State<AppState>,
+    Path(params): Path<BatchPathParams>,
+    headers: HeaderMap,
+    Json(entries): Json<Vec<BatchSecretEntry>>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            if entries.is_empty() {
+                return Err(SealboxError::InvalidInput(
+                    "Batch must contain at least one secret".to_string(),
+                ));
+            }
+            if entries.len() > MAX_BATCH_SIZE {
+                return Err(SealboxError::InvalidInput(format!(
+                    "Batch contains {} secrets, the maximum is {MAX_BATCH_SIZE}",
+                    entries.len()
+                )));
+            }
+
+            let mut conn = state.conn_pool.get()?;
+            let master_key = state.master_key_repo.get_valid_master_key(&conn)?;
+
+            let config = state.config.snapshot();
+            // Entries without an explicit TTL or compression setting inherit the defaults
+            let entries: Vec<_> = entries
+                .into_iter()
+                .map(|entry| BatchSecretEntry {
+                    ttl: entry.ttl.or(master_key.default_ttl),
+                    compress: entry.compress.or(Some(config.compress_secrets)),
+                    ..entry
+                })
+                .collect();
+
+            let validation: Vec<(String, Result<Option<i32>>)> = entries
+                .iter()
+                .map(|entry| {
+                    let result = check_secret_size(&config, entry.secret.len()).and_then(|_| {
+                        validate_new_secret(
+                            &config,
+                            &entry.key,
+                            entry.ttl,
+                            &entry.tags,
+                            entry.rotate_after,
+                            entry.description.as_deref(),
+                        )
+                    });
+                    (entry.key.clone(), result.map(|_| None))
+                })
+                .collect();
+
+            let (committed, results) = if validation.iter().all(|(_, result)| result.is_ok()) {
+                let user_agent = user_agent(&headers);
+                let outcome = state.secret_repo.create_new_versions_batch(
+                    &mut conn,
+                    &master_key,
+                    entries
+                        .into_iter()
+                        .map(|entry| NewSecret {
+                            user_agent: user_agent.clone(),
+                            ..entry.into()
+                        })
+                        .collect(),
+                )?;
+
+                // Versions of rolled back entries were never stored, so only report them on commit
+                let committed = outcome.committed;
+                if committed {
+                    state.secret_changes.notify();
+                    for (key, result) in &outcome.results {
+                        if let Ok(secret) = result {
+                            state
+                                .webhooks
+                                .notify(&config, "secret.save", key, secret.version);
+                        }
+                    }
+                }
+                let results = outcome
+                    .results
+                    .into_iter()
+                    .map(|(key, result)| {
+                        (
+                            key,
+                            result.map(|secret| committed.then_some(secret.version)),
+                        )
+                    })
+                    .collect();
+                (committed, results)
+            } else {
+                (false, validation)
+            };
+
+            let results: Vec<_> = results
+                .into_iter()
+                .map(|(key, result)| match result {
+                    Ok(Some(version)) => json!({ "key": key, "ok": true, "version": version }),
+                    Ok(None) => json!({ "key": key, "ok": true }),
+                    Err(err) => json!({ "key": key, "ok": false, "error": err.to_string() }),
+                })
+                .collect();
+            let body = json!({ "committed": committed, "results": results });
+
+            if committed {
+                Ok(SealboxResponse::Json(body))
+            } else {
+                Ok(SealboxResponse::JsonWithStatus(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    body,
+                ))
+            }
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DeleteSecretQueryParams {
+    version: i32,
+    #[serde(default)]
+    purge: bool,
+    #[serde(default)]
+    shred: bool,
+}
+
+/// API handler function for deleting one version of a secret
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `query` - The version to delete, and whether to remove or shred it permanently
 ///
 /// # Errors
 ///
+/// * `SealboxError::SecretNotFound` - When the version does not exist or is already in the recycle bin
+/// * `SealboxError::TotpRequired` - When TOTP is configured and a permanent delete has no valid `X-TOTP` code
 /// * `SealboxError::InvalidApiVersion` - When the API version is not supported
 ///
 /// # HTTP Route
 ///
-/// `GET /{version}/secrets`
+/// `DELETE /{version}/secrets/{secret_key}?version=N[&purge=true|&shred=true]`
 ///
-/// # Security Notes
+/// # Notes
 ///
-/// Returns only metadata about secrets, not the encrypted content. Automatically filters out expired secrets.
-pub(crate) async fn list(
+/// By default the version is moved to the recycle bin and can be brought back with `restore`.
+/// With `purge=true` it is removed permanently, whether or not it was soft-deleted first.
+/// `shred=true` also removes it permanently, but first overwrites the ciphertext and wrapped
+/// data key with zeros so leftover copies in the WAL or backups of free pages are useless.
+pub(crate) async fn delete(
     State(state): State<AppState>,
-    Path(params): Path<ListSecretsPathParams>,
+    Path(params): Path<SecretPathParams>,
+    Query(query): Query<DeleteSecretQueryParams>,
+    headers: HeaderMap,
 ) -> Result<SealboxResponse> {
     match params.version() {
         Version::V1 => {
-            let conn = state.conn_pool.lock()?;
-            let secrets = state.secret_repo.list_secrets(&conn)?;
-            Ok(SealboxResponse::Json(json!({ "secrets": secrets })))
+            // Soft deletes can be undone, so only permanent ones need a second factor
+            if query.purge || query.shred {
+                require_totp(&state.config.snapshot(), &headers)?;
+            }
+
+            let mut conn = state.conn_pool.get()?;
+            if query.shred {
+                state.secret_repo.shred_secret_by_version(
+                    &mut conn,
+                    &params.secret_key(),
+                    query.version,
+                )?;
+            } else if query.purge {
+                state.secret_repo.delete_secret_by_version(
+                    &conn,
+                    &params.secret_key(),
+                    query.version,
+                )?;
+            } else {
+                state
+                    .secret_repo
+                    .soft_delete(&conn, &params.secret_key(), query.version)?;
+            }
+            state.webhooks.notify(
+                &state.config.snapshot(),
+                "secret.delete",
+                &params.secret_key(),
+                query.version,
+            );
+            Ok(SealboxResponse::Ok)
         }
         _ => Err(SealboxError::InvalidApiVersion),
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RestoreSecretQueryParams {
+    version: Option<i32>,
+}
+
+/// API handler function for taking soft-deleted versions out of the recycle bin
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `query` - Query parameters with optional version number; all deleted versions if omitted
+///
+/// # Returns
+///
+/// Returns the key and the number of restored versions
+///
+/// # Errors
+///
+/// * `SealboxError::SecretNotFound` - When no matching version is in the recycle bin
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `POST /{version}/secrets/{secret_key}/restore[?version=N]`
+pub(crate) async fn restore(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+    Query(query): Query<RestoreSecretQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let key = params.secret_key();
+            let restored = state
+                .secret_repo
+                .restore_secret(&conn, &key, query.version)?;
+            // A restored version may be newer than what watchers have seen
+            state.secret_changes.notify();
+            Ok(SealboxResponse::Json(
+                json!({ "key": key, "restored": restored }),
+            ))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RollbackSecretQueryParams {
+    to: i32,
+}
+
+/// API handler function for making an older version the latest again
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `query` - Query parameters with the version to roll back to
+///
+/// # Returns
+///
+/// Returns the new latest version, a copy of the requested one
+///
+/// # Errors
+///
+/// * `SealboxError::SecretNotFound` - When the version does not exist, has expired or is in the recycle bin
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `POST /{version}/secrets/{secret_key}/rollback?to=N`
+///
+/// # Notes
+///
+/// History is never rewritten: the copy gets the next version number, and the versions after `N` stay readable.
+pub(crate) async fn rollback(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+    Query(query): Query<RollbackSecretQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let mut conn = state.conn_pool.get()?;
+            let secret =
+                state
+                    .secret_repo
+                    .promote_version(&mut conn, &params.secret_key(), query.to)?;
+            state.secret_changes.notify();
+            state.webhooks.notify(
+                &state.config.snapshot(),
+                "secret.save",
+                &secret.key,
+                secret.version,
+            );
+            Ok(SealboxResponse::Json(json!(secret)))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler function for locking one version as an immutable snapshot
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version, secret key name and version number
+///
+/// # Returns
+///
+/// Returns the key and the locked version
+///
+/// # Errors
+///
+/// * `SealboxError::SecretNotFound` - When the version does not exist or is in the recycle bin
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `PUT /{version}/secrets/{secret_key}/versions/{secret_version}/lock`
+///
+/// # Notes
+///
+/// Locking is permanent and idempotent. Deleting, shredding or soft-deleting a locked version
+/// fails with a conflict, and the expiry sweeper skips it, independent of any other version.
+pub(crate) async fn lock(
+    State(state): State<AppState>,
+    Path(params): Path<SecretVersionPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            state
+                .secret_repo
+                .lock_version(&conn, &params.secret_key, params.secret_version)?;
+            state.webhooks.notify(
+                &state.config.snapshot(),
+                "secret.lock",
+                &params.secret_key,
+                params.secret_version,
+            );
+            Ok(SealboxResponse::Json(json!({
+                "key": params.secret_key,
+                "version": params.secret_version,
+                "locked": true,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct SetExpiryPayload {
+    // Outer None: field missing; inner None: `null`, clear the expiry
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    ttl: Option<Option<i64>>,
+}
+
+/// API handler function for changing when a secret expires, without re-encrypting it
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and secret key name
+/// * `query` - Query parameters with optional version number; the latest version if omitted
+/// * `payload` - Request body with the new TTL in seconds from now, or `null` to never expire
+///
+/// # Returns
+///
+/// Returns the key, version and new `expires_at`
+///
+/// # Errors
+///
+/// * `SealboxError::ValidationFailed` - When `ttl` is missing, not positive or above `MAX_TTL_SECONDS`
+/// * `SealboxError::SecretNotFound` - When the version does not exist, is in the recycle bin or
+///   has already expired
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `PATCH /{version}/secrets/{secret_key}[?version=N]`
+///
+/// # Notes
+///
+/// An expired version is gone even if lazy cleanup has not removed it yet, so it cannot be
+/// brought back by extending its TTL.
+pub(crate) async fn set_expiry(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+    Query(query): Query<GetSecretQueryParams>,
+    Json(payload): Json<SetExpiryPayload>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let Some(ttl) = payload.ttl else {
+                return Err(SealboxError::ValidationFailed(vec![
+                    "TTL is required; use null to remove the expiry".to_string(),
+                ]));
+            };
+            validate_new_secret(
+                &state.config.snapshot(),
+                &params.secret_key(),
+                ttl,
+                &[],
+                None,
+                None,
+            )?;
+
+            let mut conn = state.conn_pool.get()?;
+            let version = match query.version {
+                Some(version) => version,
+                // Also cleans up the latest version if it expired
+                None => {
+                    state
+                        .secret_repo
+                        .get_secret(&mut conn, &params.secret_key())?
+                        .version
+                }
+            };
+            let expires_at = ttl.map(|ttl| time::OffsetDateTime::now_utc().unix_timestamp() + ttl);
+            state
+                .secret_repo
+                .set_expiry(&conn, &params.secret_key(), version, expires_at)?;
+            Ok(SealboxResponse::Json(json!({
+                "key": params.secret_key(),
+                "version": version,
+                "expires_at": expires_at,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct RenameSecretPayload {
+    new_key: String,
+    /// Needed to re-seal payloads bound to their key, like `old_private_key_pem` in a rotation
+    private_key_pem: Option<String>,
+}
+
+/// API handler function for moving a secret, with all of its versions, to a new key
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version and current secret key name
+/// * `payload` - Request body with the new key name and, for secrets bound to their key,
+///   the private master key
+///
+/// # Returns
+///
+/// Returns the old and new key and the number of versions moved
+///
+/// # Errors
+///
+/// * `SealboxError::ValidationFailed` - When the new key is empty or equal to the old key
+/// * `SealboxError::SecretNotFound` - When the secret does not exist
+/// * `SealboxError::Conflict` - When a secret already exists under the new key, or a
+///   version is bound to its key and no private key was given
+/// * `SealboxError::InvalidInput` - When the private key cannot re-seal a bound version
+/// * `SealboxError::CryptoError` - When the private key cannot be parsed
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `POST /{version}/secrets/{secret_key}/rename`
+///
+/// # Notes
+///
+/// Versions sealed without a key binding move as they are, so nothing is decrypted. Versions
+/// bound to their key are re-encrypted for the new key with their existing data key, in the
+/// same transaction as the move; the private key is used for that and not stored.
+/// Versions in the recycle bin move too.
+pub(crate) async fn rename(
+    State(state): State<AppState>,
+    Path(params): Path<SecretPathParams>,
+    Json(payload): Json<RenameSecretPayload>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let old_key = params.secret_key();
+            let mut validator = Validator::default();
+            validator.check(!payload.new_key.trim().is_empty(), || {
+                "New secret key must not be empty".to_string()
+            });
+            validator.check(payload.new_key != old_key, || {
+                "New secret key must differ from the current one".to_string()
+            });
+            validator.finish()?;
+
+            let private_key = payload
+                .private_key_pem
+                .as_deref()
+                .map(PrivateMasterKey::from_str)
+                .transpose()?;
+
+            let mut conn = state.conn_pool.get()?;
+            let renamed = state.secret_repo.rename_secret(
+                &mut conn,
+                &old_key,
+                &payload.new_key,
+                private_key.as_ref(),
+            )?;
+            state.secret_changes.notify();
+            Ok(SealboxResponse::Json(json!({
+                "key": old_key,
+                "new_key": payload.new_key,
+                "renamed": renamed,
+            })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub(crate) struct ListSecretsPathParams {
+    version: Version,
+}
+
+impl ListSecretsPathParams {
+    fn version(&self) -> Version {
+        self.version.clone()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ListSecretsQueryParams {
+    namespace: Option<String>,
+    prefix: Option<String>,
+    tag: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    #[serde(default)]
+    deleted: bool,
+}
+
+/// API handler function for listing all secrets
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+/// * `query` - Query parameters with optional namespace, key prefix and tag filters,
+///   `limit`/`offset` paging, and `deleted=true` to list the recycle bin. A `prefix` given
+///   with a `namespace` is matched within that namespace
+///
+/// # Returns
+///
+/// Returns the total number of matching secrets and the requested page of them
+/// with basic information (key, version, timestamps, tags). When listing the recycle bin,
+/// every soft-deleted version is returned along with its `deleted_at`
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+/// * `SealboxError::ValidationFailed` - When the namespace is empty or contains `/`
+///
+/// # HTTP Route
+///
+/// `GET /{version}/secrets[?namespace=NS&prefix=P&tag=T&limit=N&offset=M&deleted=true]`
+///
+/// # Security Notes
+///
+/// Returns only metadata about secrets, not the encrypted content. Automatically filters out expired secrets.
+pub(crate) async fn list(
+    State(state): State<AppState>,
+    Path(params): Path<ListSecretsPathParams>,
+    Query(query): Query<ListSecretsQueryParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let prefix = match &query.namespace {
+                Some(namespace) => {
+                    let mut validator = Validator::default();
+                    validator.check(!namespace.is_empty() && !namespace.contains('/'), || {
+                        "Namespace must be non-empty and must not contain '/'".to_string()
+                    });
+                    validator.finish()?;
+                    Some(format!(
+                        "{namespace}/{}",
+                        query.prefix.as_deref().unwrap_or_default()
+                    ))
+                }
+                None => query.prefix,
+            };
+
+            let conn = state.conn_pool.get()?;
+            let page = state.secret_repo.list_secrets(
+                &conn,
+                &SecretFilter {
+                    prefix: prefix.as_deref(),
+                    tag: query.tag.as_deref(),
+                    limit: query.limit,
+                    offset: query.offset.unwrap_or(0),
+                    deleted: query.deleted,
+                },
+            )?;
+            Ok(SealboxResponse::Json(
+                json!({ "total": page.total, "secrets": page.secrets }),
+            ))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+/// API handler function for listing secret namespaces
+///
+/// # Arguments
+///
+/// * `state` - Application state containing database connection pool and repository instances
+/// * `params` - Path parameters containing API version
+///
+/// # Returns
+///
+/// Returns every namespace, the part of a key before its first `/`, with the number of
+/// live secrets in it, sorted by name
+///
+/// # Errors
+///
+/// * `SealboxError::InvalidApiVersion` - When the API version is not supported
+///
+/// # HTTP Route
+///
+/// `GET /{version}/namespaces`
+///
+/// # Security Notes
+///
+/// Namespaces are derived from existing keys, so this reveals no more than listing secrets.
+/// Expired and soft-deleted secrets are not counted.
+pub(crate) async fn namespaces(
+    State(state): State<AppState>,
+    Path(params): Path<ListSecretsPathParams>,
+) -> Result<SealboxResponse> {
+    match params.version() {
+        Version::V1 => {
+            let conn = state.conn_pool.get()?;
+            let namespaces = state.secret_repo.list_namespaces(&conn)?;
+            Ok(SealboxResponse::Json(json!({ "namespaces": namespaces })))
+        }
+        _ => Err(SealboxError::InvalidApiVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::SharedConfig,
+        crypto::master_key::generate_key_pair,
+        repo::{
+            MasterKey, MasterKeyRepo, SqliteAuditRepo, SqliteHealthRepo, SqliteMasterKeyRepo,
+            SqliteSecretRepo, create_db_pool,
+        },
+    };
+    use std::sync::Arc;
+
+    fn setup_test_state() -> AppState {
+        setup_test_state_with_default_ttl(None)
+    }
+
+    fn setup_test_state_with_default_ttl(default_ttl: Option<i64>) -> AppState {
+        let conn_pool = create_db_pool(":memory:", 1).expect("Should create in-memory DB");
+        let conn = conn_pool.get().expect("Should check out a connection");
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should init master_keys table");
+        SqliteSecretRepo::init_table(&conn).expect("Should init secrets table");
+
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let mut master_key = MasterKey::new(public_pem).expect("Should create master key");
+        master_key.default_ttl = default_ttl;
+        SqliteMasterKeyRepo
+            .create_master_key(&conn, &master_key)
+            .expect("Should store master key");
+
+        AppState {
+            conn_pool,
+            master_key_repo: Arc::new(SqliteMasterKeyRepo),
+            secret_repo: Arc::new(SqliteSecretRepo),
+            health_repo: Arc::new(SqliteHealthRepo),
+            audit_repo: Arc::new(SqliteAuditRepo),
+            config: SharedConfig::new(SealboxConfig::default()),
+            jobs: Default::default(),
+            secret_changes: Default::default(),
+            metrics: Default::default(),
+            rate_limiter: Default::default(),
+            key_challenges: Default::default(),
+            webhooks: Default::default(),
+        }
+    }
+
+    fn save_payload(ttl: Option<i64>) -> SaveSecretPayload {
+        SaveSecretPayload {
+            secret: "value".to_string(),
+            ttl,
+            tags: Vec::new(),
+            compress: None,
+            rotate_after: None,
+            description: None,
+        }
+    }
+
+    fn path_params(key: &str) -> SecretPathParams {
+        SecretPathParams {
+            version: Version::V1,
+            secret_key: key.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_respects_reloaded_ttl_bound() {
+        let state = setup_test_state();
+
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(Some(3600))),
+        )
+        .await
+        .expect("Unbounded TTL should be accepted");
+
+        state.config.reload(SealboxConfig {
+            max_ttl_seconds: Some(60),
+            ..SealboxConfig::default()
+        });
+
+        let err = save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(Some(3600))),
+        )
+        .await
+        .expect_err("TTL above the reloaded bound should be rejected");
+        assert!(matches!(err, SealboxError::ValidationFailed(_)));
+
+        save(
+            State(state),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(Some(30))),
+        )
+        .await
+        .expect("TTL within the bound should be accepted");
+    }
+
+    #[tokio::test]
+    async fn test_save_and_delete_queue_webhook_events() {
+        let state = setup_test_state();
+        let mut events = state.webhooks.take_receiver().unwrap();
+        state.config.reload(SealboxConfig {
+            webhook_urls: vec!["http://127.0.0.1:9/hook".to_string()],
+            webhook_secret: Some("hook-secret".to_string()),
+            ..SealboxConfig::default()
+        });
+
+        save(
+            State(state.clone()),
+            Path(path_params("db")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Should save secret");
+        delete(
+            State(state),
+            Path(path_params("db")),
+            Query(DeleteSecretQueryParams {
+                version: 1,
+                purge: false,
+                shred: false,
+            }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Should delete secret");
+
+        let saved = events.try_recv().expect("Save should queue an event");
+        assert_eq!(
+            (saved.event, saved.key.as_str(), saved.version),
+            ("secret.save", "db", 1)
+        );
+        let deleted = events.try_recv().expect("Delete should queue an event");
+        assert_eq!(
+            (deleted.event, deleted.key.as_str(), deleted.version),
+            ("secret.delete", "db", 1)
+        );
+        // The value never leaves the server
+        assert!(!serde_json::to_string(&saved).unwrap().contains("value"));
+    }
+
+    #[tokio::test]
+    async fn test_save_keeps_description_unless_given() {
+        let state = setup_test_state();
+        let save_with = |description: Option<&str>| {
+            let state = state.clone();
+            let payload = SaveSecretPayload {
+                description: description.map(str::to_string),
+                ..save_payload(None)
+            };
+            async move {
+                let SealboxResponse::Json(secret) = save(
+                    State(state),
+                    Path(path_params("db")),
+                    HeaderMap::new(),
+                    Json(payload),
+                )
+                .await
+                .unwrap() else {
+                    panic!("Expected a JSON response");
+                };
+                secret["description"].as_str().map(str::to_string)
+            }
+        };
+
+        assert_eq!(
+            save_with(Some("replica password")).await.as_deref(),
+            Some("replica password")
+        );
+        // Omitted: inherited from the previous version
+        assert_eq!(save_with(None).await.as_deref(), Some("replica password"));
+        assert_eq!(
+            save_with(Some("owned by DBA")).await.as_deref(),
+            Some("owned by DBA")
+        );
+        // Empty: cleared
+        assert_eq!(save_with(Some("")).await, None);
+        assert_eq!(save_with(None).await, None);
+
+        let conn = state.conn_pool.get().unwrap();
+        let versions = state.secret_repo.list_secret_versions(&conn, "db").unwrap();
+        let descriptions: Vec<_> = versions
+            .iter()
+            .map(|version| version.description.as_deref())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                None,
+                None,
+                Some("owned by DBA"),
+                Some("replica password"),
+                Some("replica password"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_records_user_agent_in_history() {
+        let state = setup_test_state();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, "sealbox-cli/1.2.3".parse().unwrap());
+
+        save(
+            State(state.clone()),
+            Path(path_params("db")),
+            headers,
+            Json(save_payload(None)),
+        )
+        .await
+        .unwrap();
+        save(
+            State(state.clone()),
+            Path(path_params("db")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .unwrap();
+
+        let SealboxResponse::Json(history) = versions(State(state), Path(path_params("db")))
+            .await
+            .unwrap()
+        else {
+            panic!("Expected a JSON response");
+        };
+        // Newest first; the second save sent no User-Agent
+        assert!(history["versions"][0].get("user_agent").is_none());
+        assert_eq!(history["versions"][1]["user_agent"], "sealbox-cli/1.2.3");
+    }
+
+    #[tokio::test]
+    async fn test_meta_omits_ciphertext() {
+        let state = setup_test_state();
+        for _ in 0..2 {
+            save(
+                State(state.clone()),
+                Path(path_params("db")),
+                HeaderMap::new(),
+                Json(save_payload(Some(60))),
+            )
+            .await
+            .unwrap();
+        }
+
+        let SealboxResponse::Json(body) = meta(State(state.clone()), Path(path_params("db")))
+            .await
+            .unwrap()
+        else {
+            panic!("Expected a JSON response");
+        };
+        let fields: Vec<&str> = body
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "created_at",
+                "expires_at",
+                "last_read_at",
+                "read_count",
+                "updated_at",
+                "version"
+            ]
+        );
+        assert_eq!(body["version"], 2);
+        assert!(body["expires_at"].is_i64());
+        assert_eq!(body["read_count"], 0);
+        assert!(body["last_read_at"].is_null());
+
+        // Reads of any version count towards the secret
+        for version in [Some(1), None] {
+            get(
+                State(state.clone()),
+                Path(path_params("db")),
+                Query(GetSecretQueryParams { version }),
+                HeaderMap::new(),
+            )
+            .await
+            .unwrap();
+        }
+        let SealboxResponse::Json(body) = meta(State(state.clone()), Path(path_params("db")))
+            .await
+            .unwrap()
+        else {
+            panic!("Expected a JSON response");
+        };
+        assert_eq!(body["read_count"], 2);
+        assert!(body["last_read_at"].is_i64());
+
+        let err = meta(State(state), Path(path_params("missing")))
+            .await
+            .expect_err("Missing secret should not be found");
+        assert!(matches!(err, SealboxError::SecretNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_save_reports_every_validation_error() {
+        let state = setup_test_state();
+
+        let err = save(
+            State(state.clone()),
+            Path(path_params(" ")),
+            HeaderMap::new(),
+            Json(SaveSecretPayload {
+                secret: "value".to_string(),
+                ttl: Some(-5),
+                tags: vec!["".to_string()],
+                compress: None,
+                rotate_after: Some(1),
+                description: Some("d".repeat(MAX_DESCRIPTION_LEN + 1)),
+            }),
+        )
+        .await
+        .expect_err("Invalid payload should be rejected");
+        let SealboxError::ValidationFailed(errors) = err else {
+            panic!("Expected validation failure, got {err:?}");
+        };
+        assert_eq!(
+            errors,
+            vec![
+                "Secret key must not be empty",
+                "Tags must not be empty",
+                "TTL must be positive, got -5s",
+                "Rotation reminder must be in the future, got 1",
+                "Description must be at most 1024 characters",
+            ]
+        );
+
+        let conn = state.conn_pool.get().unwrap();
+        let page = state
+            .secret_repo
+            .list_secrets(&conn, &SecretFilter::default())
+            .unwrap();
+        assert_eq!(page.total, 0);
+    }
+
+    fn batch_entry(key: &str, ttl: Option<i64>) -> BatchSecretEntry {
+        BatchSecretEntry {
+            key: key.to_string(),
+            secret: "value".to_string(),
+            ttl,
+            tags: Vec::new(),
+            compress: None,
+            rotate_after: None,
+            description: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_commits_all() {
+        let state = setup_test_state();
+        let path_params = BatchPathParams {
+            version: Version::V1,
+        };
+
+        let response = batch_save(
+            State(state.clone()),
+            Path(path_params),
+            HeaderMap::new(),
+            Json(vec![batch_entry("a", None), batch_entry("b", Some(60))]),
+        )
+        .await
+        .expect("Batch should succeed");
+
+        match response {
+            SealboxResponse::Json(body) => {
+                assert_eq!(body["committed"], true);
+                assert_eq!(body["results"][0]["version"], 1);
+                assert_eq!(body["results"][1]["key"], "b");
+            }
+            _ => panic!("Expected JSON response"),
+        }
+
+        let conn = state.conn_pool.get().unwrap();
+        let page = state
+            .secret_repo
+            .list_secrets(&conn, &SecretFilter::default())
+            .unwrap();
+        assert_eq!(page.total, 2);
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_invalid_entry_writes_nothing() {
+        let state = setup_test_state();
+        state.config.reload(SealboxConfig {
+            max_ttl_seconds: Some(60),
+            ..SealboxConfig::default()
+        });
+        let path_params = BatchPathParams {
+            version: Version::V1,
+        };
+
+        let response = batch_save(
+            State(state.clone()),
+            Path(path_params),
+            HeaderMap::new(),
+            Json(vec![batch_entry("a", None), batch_entry("b", Some(3600))]),
+        )
+        .await
+        .expect("Batch should return per-key results");
+
+        match response {
+            SealboxResponse::JsonWithStatus(status, body) => {
+                assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+                assert_eq!(body["committed"], false);
+                assert_eq!(body["results"][0]["ok"], true);
+                assert_eq!(body["results"][1]["ok"], false);
+            }
+            _ => panic!("Expected JSON response with status"),
+        }
+
+        let conn = state.conn_pool.get().unwrap();
+        let page = state
+            .secret_repo
+            .list_secrets(&conn, &SecretFilter::default())
+            .unwrap();
+        assert_eq!(page.total, 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_empty() {
+        let state = setup_test_state();
+        let path_params = BatchPathParams {
+            version: Version::V1,
+        };
+
+        let err = batch_save(
+            State(state),
+            Path(path_params),
+            HeaderMap::new(),
+            Json(Vec::new()),
+        )
+        .await
+        .expect_err("Empty batch should be rejected");
+        assert!(matches!(err, SealboxError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_records_description_and_user_agent() {
+        let state = setup_test_state();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, "sealbox-cli/1.2.3".parse().unwrap());
+
+        batch_save(
+            State(state.clone()),
+            Path(BatchPathParams {
+                version: Version::V1,
+            }),
+            headers,
+            Json(vec![
+                BatchSecretEntry {
+                    description: Some(" Primary database ".to_string()),
+                    ..batch_entry("db", None)
+                },
+                batch_entry("cache", None),
+            ]),
+        )
+        .await
+        .expect("Batch should succeed");
+
+        let mut conn = state.conn_pool.get().unwrap();
+        let db = state.secret_repo.get_secret(&mut conn, "db").unwrap();
+        assert_eq!(db.description.as_deref(), Some("Primary database"));
+        assert_eq!(db.user_agent.as_deref(), Some("sealbox-cli/1.2.3"));
+        let cache = state.secret_repo.get_secret(&mut conn, "cache").unwrap();
+        assert_eq!(cache.description, None);
+        assert_eq!(cache.user_agent.as_deref(), Some("sealbox-cli/1.2.3"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_records_rotate_after() {
+        let state = setup_test_state();
+        let path_params = BatchPathParams {
+            version: Version::V1,
+        };
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        batch_save(
+            State(state.clone()),
+            Path(path_params.clone()),
+            HeaderMap::new(),
+            Json(vec![
+                BatchSecretEntry {
+                    rotate_after: Some(now + 3600),
+                    ..batch_entry("db", None)
+                },
+                batch_entry("cache", None),
+            ]),
+        )
+        .await
+        .expect("Batch should succeed");
+
+        {
+            let mut conn = state.conn_pool.get().unwrap();
+            let db = state.secret_repo.get_secret(&mut conn, "db").unwrap();
+            assert_eq!(db.rotate_after, Some(now + 3600));
+            let cache = state.secret_repo.get_secret(&mut conn, "cache").unwrap();
+            assert_eq!(cache.rotate_after, None);
+        }
+
+        // A reminder in the past fails the entry like any other invalid input
+        let response = batch_save(
+            State(state),
+            Path(path_params),
+            HeaderMap::new(),
+            Json(vec![BatchSecretEntry {
+                rotate_after: Some(now - 60),
+                ..batch_entry("db", None)
+            }]),
+        )
+        .await
+        .expect("Batch should return per-key results");
+        let SealboxResponse::JsonWithStatus(status, body) = response else {
+            panic!("Expected JSON response with status");
+        };
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["results"][0]["ok"], false);
+    }
+
+    fn watch_query(since_version: i32, timeout: u64) -> WatchSecretQueryParams {
+        WatchSecretQueryParams {
+            since_version,
+            timeout: Some(timeout),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_woken_by_save() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("First version should be saved");
+
+        let watcher = tokio::spawn(watch(
+            State(state.clone()),
+            Path(path_params("token")),
+            Query(watch_query(1, 10)),
+        ));
+
+        // Give the watcher time to find nothing newer and start waiting
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!watcher.is_finished());
+
+        save(
+            State(state),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Second version should be saved");
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), watcher)
+            .await
+            .expect("Save should wake the watcher")
+            .unwrap()
+            .expect("Watch should succeed");
+        match response {
+            SealboxResponse::Json(body) => assert_eq!(body["version"], 2),
+            _ => panic!("Expected JSON response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_returns_immediately_when_behind() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("First version should be saved");
+
+        let response = watch(
+            State(state),
+            Path(path_params("token")),
+            Query(watch_query(0, 10)),
+        )
+        .await
+        .expect("Watch should succeed");
+        assert!(matches!(response, SealboxResponse::Json(_)));
+    }
+
+    #[tokio::test]
+    async fn test_watch_timeout_not_modified() {
+        let state = setup_test_state();
+
+        let response = watch(
+            State(state),
+            Path(path_params("missing")),
+            Query(watch_query(0, 1)),
+        )
+        .await
+        .expect("Watch should succeed");
+        assert!(matches!(response, SealboxResponse::NotModified(None)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_soft_by_default_and_purge() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+
+        delete(
+            State(state.clone()),
+            Path(path_params("token")),
+            Query(DeleteSecretQueryParams {
+                version: 1,
+                purge: false,
+                shred: false,
+            }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Soft delete should succeed");
+
+        let response = restore(
+            State(state.clone()),
+            Path(path_params("token")),
+            Query(RestoreSecretQueryParams { version: None }),
+        )
+        .await
+        .expect("Restore should succeed");
+        match response {
+            SealboxResponse::Json(body) => assert_eq!(body["restored"], 1),
+            _ => panic!("Expected JSON response"),
+        }
+
+        delete(
+            State(state.clone()),
+            Path(path_params("token")),
+            Query(DeleteSecretQueryParams {
+                version: 1,
+                purge: true,
+                shred: false,
+            }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Purge should succeed");
+
+        let err = restore(
+            State(state),
+            Path(path_params("token")),
+            Query(RestoreSecretQueryParams { version: None }),
+        )
+        .await
+        .expect_err("Purged secret cannot be restored");
+        assert!(matches!(err, SealboxError::SecretNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_set_expiry_extends_and_clears_ttl() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(Some(60))),
+        )
+        .await
+        .expect("Secret should be saved");
+
+        let set = |body: serde_json::Value| {
+            set_expiry(
+                State(state.clone()),
+                Path(path_params("token")),
+                Query(GetSecretQueryParams { version: None }),
+                Json(serde_json::from_value(body).unwrap()),
+            )
+        };
+
+        let before = time::OffsetDateTime::now_utc().unix_timestamp();
+        match set(json!({ "ttl": 3600 })).await.unwrap() {
+            SealboxResponse::Json(body) => {
+                assert_eq!(body["version"], 1);
+                assert!(body["expires_at"].as_i64().unwrap() >= before + 3600);
+            }
+            _ => panic!("Expected JSON response"),
+        }
+
+        set(json!({ "ttl": null }))
+            .await
+            .expect("null should clear the expiry");
+        let versions = state
+            .secret_repo
+            .list_secret_versions(&state.conn_pool.get().unwrap(), "token")
+            .unwrap();
+        assert_eq!(versions[0].expires_at, None);
+
+        for body in [json!({}), json!({ "ttl": 0 })] {
+            let err = set(body).await.expect_err("Invalid TTL should be rejected");
+            assert!(matches!(err, SealboxError::ValidationFailed(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permanent_delete_requires_totp_when_configured() {
+        let state = setup_test_state();
+        let secret = b"12345678901234567890".to_vec();
+        state.config.reload(SealboxConfig {
+            totp_secret: Some(secret.clone()),
+            ..SealboxConfig::default()
+        });
+        save(
+            State(state.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+
+        let delete_version = |purge: bool, headers: HeaderMap| {
+            delete(
+                State(state.clone()),
+                Path(path_params("token")),
+                Query(DeleteSecretQueryParams {
+                    version: 1,
+                    purge,
+                    shred: false,
+                }),
+                headers,
+            )
+        };
+
+        // Soft deletes can be restored, so they need no code
+        delete_version(false, HeaderMap::new())
+            .await
+            .expect("Soft delete should succeed");
+
+        let err = delete_version(true, HeaderMap::new())
+            .await
+            .expect_err("Purge without a TOTP code should be rejected");
+        assert!(matches!(err, SealboxError::TotpRequired));
+
+        let now = time::OffsetDateTime::now_utc().unix_timestamp() as u64;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            crate::api::auth::TOTP_HEADER,
+            crate::crypto::totp::code_at(&secret, now).parse().unwrap(),
+        );
+        delete_version(true, headers)
+            .await
+            .expect("Purge with a valid TOTP code should succeed");
+    }
+
+    #[tokio::test]
+    async fn test_save_compression_opt_in() {
+        let state = setup_test_state();
+
+        let response = save(
+            State(state.clone()),
+            Path(path_params("plain")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["envelope_version"], 3);
+
+        let response = save(
+            State(state),
+            Path(path_params("compressed")),
+            HeaderMap::new(),
+            Json(SaveSecretPayload {
+                compress: Some(true),
+                ..save_payload(None)
+            }),
+        )
+        .await
+        .expect("Compressed secret should be saved");
+        let SealboxResponse::Json(body) = response else {
+            panic!("Expected JSON response");
+        };
+        assert_eq!(body["envelope_version"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_rollback_creates_new_latest_version() {
+        let state = setup_test_state();
+        for _ in 0..2 {
+            save(
+                State(state.clone()),
+                Path(path_params("token")),
+                HeaderMap::new(),
+                Json(save_payload(None)),
+            )
+            .await
+            .expect("Secret should be saved");
+        }
+
+        let response = rollback(
+            State(state.clone()),
+            Path(path_params("token")),
+            Query(RollbackSecretQueryParams { to: 1 }),
+        )
+        .await
+        .expect("Rollback should succeed");
+        match response {
+            SealboxResponse::Json(body) => assert_eq!(body["version"], 3),
+            _ => panic!("Expected JSON response"),
+        }
+
+        let err = rollback(
+            State(state),
+            Path(path_params("token")),
+            Query(RollbackSecretQueryParams { to: 7 }),
+        )
+        .await
+        .expect_err("Unknown version cannot be promoted");
+        assert!(matches!(err, SealboxError::SecretNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_locked_version_cannot_be_deleted() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("audit-snapshot")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+
+        lock(
+            State(state.clone()),
+            Path(SecretVersionPathParams {
+                version: Version::V1,
+                secret_key: "audit-snapshot".to_string(),
+                secret_version: 1,
+            }),
+        )
+        .await
+        .expect("Version should be locked");
+
+        for (purge, shred) in [(false, false), (true, false), (false, true)] {
+            let err = delete(
+                State(state.clone()),
+                Path(path_params("audit-snapshot")),
+                Query(DeleteSecretQueryParams {
+                    version: 1,
+                    purge,
+                    shred,
+                }),
+                HeaderMap::new(),
+            )
+            .await
+            .expect_err("Locked version should not be deleted");
+            assert!(matches!(err, SealboxError::Conflict(_)));
+        }
+
+        get(
+            State(state),
+            Path(path_params("audit-snapshot")),
+            Query(GetSecretQueryParams { version: Some(1) }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Locked version should still be readable");
+    }
+
+    #[tokio::test]
+    async fn test_rename_moves_secret() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("taken")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+        {
+            // Payloads not bound to their key move without the private key
+            let mut conn = state.conn_pool.get().unwrap();
+            let master_key = state.master_key_repo.get_valid_master_key(&conn).unwrap();
+            state
+                .secret_repo
+                .create_new_version(
+                    &mut conn,
+                    master_key,
+                    NewSecret::new("old", "legacy", EnvelopeVersion::V1),
+                )
+                .unwrap();
+        }
+
+        let rename_to = |new_key: &str| {
+            Json(RenameSecretPayload {
+                new_key: new_key.to_string(),
+                private_key_pem: None,
+            })
+        };
+
+        let err = rename(
+            State(state.clone()),
+            Path(path_params("old")),
+            rename_to("taken"),
+        )
+        .await
+        .expect_err("Existing key should conflict");
+        assert!(matches!(err, SealboxError::Conflict(_)));
+
+        let err = rename(
+            State(state.clone()),
+            Path(path_params("old")),
+            rename_to(" "),
+        )
+        .await
+        .expect_err("Empty key should be rejected");
+        assert!(matches!(err, SealboxError::ValidationFailed(_)));
+
+        let response = rename(
+            State(state.clone()),
+            Path(path_params("old")),
+            rename_to("new"),
+        )
+        .await
+        .expect("Rename should succeed");
+        match response {
+            SealboxResponse::Json(body) => {
+                assert_eq!(body["new_key"], "new");
+                assert_eq!(body["renamed"], 1);
+            }
+            _ => panic!("Expected JSON response"),
+        }
+
+        get(
+            State(state.clone()),
+            Path(path_params("new")),
+            Query(GetSecretQueryParams { version: None }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Secret should be readable under the new key");
+        let err = get(
+            State(state.clone()),
+            Path(path_params("old")),
+            Query(GetSecretQueryParams { version: None }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect_err("Old key should be gone");
+        assert!(matches!(err, SealboxError::SecretNotFound(_)));
+
+        let err = rename(State(state), Path(path_params("taken")), rename_to("moved"))
+            .await
+            .expect_err("Secret bound to its key should need the private key");
+        assert!(matches!(err, SealboxError::Conflict(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_honors_if_none_match() {
+        let state = setup_test_state();
+        let save_version = || {
+            save(
+                State(state.clone()),
+                Path(path_params("db")),
+                HeaderMap::new(),
+                Json(save_payload(None)),
+            )
+        };
+        save_version().await.expect("Secret should be saved");
+
+        let get_with = |if_none_match: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = if_none_match {
+                headers.insert(header::IF_NONE_MATCH, value.parse().unwrap());
+            }
+            get(
+                State(state.clone()),
+                Path(path_params("db")),
+                Query(GetSecretQueryParams { version: None }),
+                headers,
+            )
+        };
+
+        let SealboxResponse::JsonWithEtag(etag, _) = get_with(None).await.unwrap() else {
+            panic!("Expected JSON response with an ETag");
+        };
+        let response = get_with(Some(&format!("\"other\", W/{etag}")))
+            .await
+            .unwrap();
+        assert!(matches!(response, SealboxResponse::NotModified(Some(ref tag)) if *tag == etag));
+
+        // A new version changes the ETag, so the old one no longer matches
+        save_version().await.expect("Secret should be saved");
+        match get_with(Some(&etag)).await.unwrap() {
+            SealboxResponse::JsonWithEtag(new_etag, body) => {
+                assert_ne!(new_etag, etag);
+                assert_eq!(body["version"], 2);
+            }
+            _ => panic!("Expected JSON response with an ETag"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_marks_master_key_used() {
+        let state = setup_test_state();
+        save(
+            State(state.clone()),
+            Path(path_params("db")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+        let last_used = |state: &AppState| {
+            let conn = state.conn_pool.get().unwrap();
+            state.master_key_repo.fetch_all_master_keys(&conn).unwrap()[0].last_used_at
+        };
+        assert_eq!(last_used(&state), None);
+
+        get(
+            State(state.clone()),
+            Path(path_params("db")),
+            Query(GetSecretQueryParams { version: None }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("Secret should be readable");
+        assert!(last_used(&state).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_save_inherits_master_key_default_ttl() {
+        let with_default = setup_test_state_with_default_ttl(Some(600));
+        let without_default = setup_test_state();
+
+        let response = save(
+            State(with_default.clone()),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+        let SealboxResponse::Json(inherited) = response else {
+            panic!("Expected JSON response");
+        };
+        let created_at = inherited["created_at"].as_i64().unwrap();
+        assert_eq!(inherited["expires_at"].as_i64(), Some(created_at + 600));
+
+        // An explicit TTL wins over the default
+        let response = save(
+            State(with_default),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(Some(30))),
+        )
+        .await
+        .expect("Secret should be saved");
+        let SealboxResponse::Json(explicit) = response else {
+            panic!("Expected JSON response");
+        };
+        let created_at = explicit["created_at"].as_i64().unwrap();
+        assert_eq!(explicit["expires_at"].as_i64(), Some(created_at + 30));
+
+        let response = save(
+            State(without_default),
+            Path(path_params("token")),
+            HeaderMap::new(),
+            Json(save_payload(None)),
+        )
+        .await
+        .expect("Secret should be saved");
+        let SealboxResponse::Json(never_expires) = response else {
+            panic!("Expected JSON response");
+        };
+        assert!(never_expires["expires_at"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_batch_save_inherits_master_key_default_ttl() {
+        let state = setup_test_state_with_default_ttl(Some(600));
+        let path_params = BatchPathParams {
+            version: Version::V1,
+        };
+
+        batch_save(
+            State(state.clone()),
+            Path(path_params),
+            HeaderMap::new(),
+            Json(vec![
+                batch_entry("inherits", None),
+                batch_entry("explicit", Some(60)),
+            ]),
+        )
+        .await
+        .expect("Batch should succeed");
+
+        let mut conn = state.conn_pool.get().unwrap();
+        let inherits = state.secret_repo.get_secret(&mut conn, "inherits").unwrap();
+        assert_eq!(inherits.expires_at, Some(inherits.created_at + 600));
+        let explicit = state.secret_repo.get_secret(&mut conn, "explicit").unwrap();
+        assert_eq!(explicit.expires_at, Some(explicit.created_at + 60));
+    }
+
+    #[tokio::test]
+    async fn test_list_by_namespace() {
+        let state = setup_test_state();
+        for key in ["team-a/db", "team-a/api", "team-ab/db", "team-b/db"] {
+            save(
+                State(state.clone()),
+                Path(path_params(key)),
+                HeaderMap::new(),
+                Json(save_payload(None)),
+            )
+            .await
+            .expect("Secret should be saved");
+        }
+        let list_params = || {
+            Path(ListSecretsPathParams {
+                version: Version::V1,
+            })
+        };
+        let query = |namespace: &str, prefix: Option<&str>| {
+            Query(ListSecretsQueryParams {
+                namespace: Some(namespace.to_string()),
+                prefix: prefix.map(str::to_string),
+                tag: None,
+                limit: None,
+                offset: None,
+                deleted: false,
+            })
+        };
+
+        let response = list(State(state.clone()), list_params(), query("team-a", None))
+            .await
+            .expect("List should succeed");
+        match response {
+            SealboxResponse::Json(body) => assert_eq!(body["total"], 2),
+            _ => panic!("Expected JSON response"),
+        }
+
+        // A prefix narrows the listing within the namespace
+        let response = list(
+            State(state.clone()),
+            list_params(),
+            query("team-a", Some("d")),
+        )
+        .await
+        .expect("List should succeed");
+        match response {
+            SealboxResponse::Json(body) => {
+                assert_eq!(body["total"], 1);
+                assert_eq!(body["secrets"][0]["key"], "team-a/db");
+            }
+            _ => panic!("Expected JSON response"),
+        }
+
+        let err = list(
+            State(state.clone()),
+            list_params(),
+            query("team-a/db", None),
+        )
+        .await
+        .expect_err("Nested namespace should be rejected");
+        assert!(matches!(err, SealboxError::ValidationFailed(_)));
+
+        let response = namespaces(State(state), list_params())
+            .await
+            .expect("Namespaces should be listed");
+        match response {
+            SealboxResponse::Json(body) => assert_eq!(
+                body["namespaces"],
+                json!([
+                    {"namespace": "team-a", "secrets": 2},
+                    {"namespace": "team-ab", "secrets": 1},
+                    {"namespace": "team-b", "secrets": 1},
+                ])
+            ),
+            _ => panic!("Expected JSON response"),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, Uri, header},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    api::state::AppState,
+    error::{Result, SealboxError},
+};
+
+/// Middleware rejecting writes made over plain HTTP with `426 Upgrade Required` while
+/// `SEALBOX_REQUIRE_HTTPS` is set.
+///
+/// Every method other than `GET`, `HEAD` and `OPTIONS` is checked, since secret values and
+/// private keys (master key rotation, renames of bound secrets) only travel in request
+/// bodies. Sealbox does not terminate TLS itself, so a request counts as HTTPS when the
+/// proxy in front of it says so.
+///
+/// # Security Notes
+///
+/// The proxy headers are not authenticated. This guards against clients sending secrets
+/// over plain HTTP by mistake, and a client that forges them only exposes its own request.
+pub(crate) async fn require_https(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    if !request.method().is_safe()
+        && state.config.snapshot().require_https
+        && !arrived_over_https(request.uri(), request.headers())
+    {
+        return Err(SealboxError::HttpsRequired);
+    }
+    Ok(next.run(request).await)
+}
+
+/// Whether the client used HTTPS, judged by an absolute `https` request URI, then
+/// `X-Forwarded-Proto`, then the `proto` of RFC 7239 `Forwarded`.
+///
+/// Proxies append to these headers, so the first entry comes from the proxy the client
+/// connected to.
+fn arrived_over_https(uri: &Uri, headers: &HeaderMap) -> bool {
+    if let Some(scheme) = uri.scheme_str() {
+        return scheme.eq_ignore_ascii_case("https");
+    }
+    if let Some(proto) = headers.get("x-forwarded-proto") {
+        return proto
+            .to_str()
+            .ok()
+            .and_then(|proto| proto.split(',').next())
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"));
+    }
+    headers
+        .get(header::FORWARDED)
+        .and_then(|forwarded| forwarded.to_str().ok())
+        .and_then(|forwarded| forwarded.split(',').next())
+        .is_some_and(|first| {
+            first.split(';').any(|pair| {
+                pair.split_once('=').is_some_and(|(name, value)| {
+                    name.trim().eq_ignore_ascii_case("proto")
+                        && value.trim().trim_matches('"').eq_ignore_ascii_case("https")
+                })
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_arrived_over_https() {
+        let path: Uri = "/v1/secrets/db".parse().unwrap();
+        assert!(!arrived_over_https(&path, &HeaderMap::new()));
+        assert!(arrived_over_https(
+            &"https://vault.example/v1/secrets/db".parse().unwrap(),
+            &HeaderMap::new()
+        ));
+
+        assert!(arrived_over_https(
+            &path,
+            &headers(&[("x-forwarded-proto", "HTTPS")])
+        ));
+        assert!(arrived_over_https(
+            &path,
+            &headers(&[("x-forwarded-proto", "https, http")])
+        ));
+        // The proxy the client reached spoke plain HTTP
+        assert!(!arrived_over_https(
+            &path,
+            &headers(&[("x-forwarded-proto", "http, https")])
+        ));
+
+        assert!(arrived_over_https(
+            &path,
+            &headers(&[(
+                "forwarded",
+                r#"for=192.0.2.60;proto="https";by=203.0.113.43"#
+            )])
+        ));
+        assert!(!arrived_over_https(
+            &path,
+            &headers(&[("forwarded", "for=192.0.2.60;proto=http, proto=https")])
+        ));
+    }
+}
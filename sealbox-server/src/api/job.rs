@@ -0,0 +1,121 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Progress of a long-running background operation such as master key rotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Job {
+    pub(crate) id: Uuid,
+    pub(crate) kind: String,
+    pub(crate) status: JobStatus,
+    pub(crate) total: usize,                    // Items to process
+    pub(crate) processed: usize,                // Items handled so far
+    pub(crate) failed_secret_keys: Vec<String>, // Secrets that failed
+    pub(crate) error: Option<String>,           // Why the job failed
+    pub(crate) created_at: i64,
+    pub(crate) updated_at: i64,
+}
+
+/// In-memory registry of background jobs.
+///
+/// Jobs are not persisted: they only live as long as the server process.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<Uuid, Job>>>,
+}
+
+impl JobRegistry {
+    /// Register a new pending job and return a snapshot of it
+    pub(crate) fn create(&self, kind: &str) -> Job {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let job = Job {
+            id: Uuid::new_v4(),
+            kind: kind.to_string(),
+            status: JobStatus::Pending,
+            total: 0,
+            processed: 0,
+            failed_secret_keys: Vec::new(),
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(job.id, job.clone());
+        job
+    }
+
+    /// Get a snapshot of the job with the given id
+    pub(crate) fn get(&self, id: &Uuid) -> Option<Job> {
+        self.jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(id)
+            .cloned()
+    }
+
+    /// Apply `f` to the job with the given id and bump its `updated_at`
+    pub(crate) fn update(&self, id: &Uuid, f: impl FnOnce(&mut Job)) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(job) = jobs.get_mut(id) {
+            f(job);
+            job.updated_at = time::OffsetDateTime::now_utc().unix_timestamp();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_job_is_pending() {
+        let registry = JobRegistry::default();
+        let job = registry.create("master_key_rotation");
+
+        let stored = registry.get(&job.id).expect("Job should be registered");
+        assert_eq!(stored.status, JobStatus::Pending);
+        assert_eq!(stored.kind, "master_key_rotation");
+        assert_eq!(stored.processed, 0);
+    }
+
+    #[test]
+    fn test_update_job_progress() {
+        let registry = JobRegistry::default();
+        let job = registry.create("master_key_rotation");
+
+        registry.update(&job.id, |job| {
+            job.status = JobStatus::Running;
+            job.total = 10;
+            job.processed = 4;
+            job.failed_secret_keys.push("broken".to_string());
+        });
+
+        let stored = registry.get(&job.id).expect("Job should be registered");
+        assert_eq!(stored.status, JobStatus::Running);
+        assert_eq!(stored.total, 10);
+        assert_eq!(stored.processed, 4);
+        assert_eq!(stored.failed_secret_keys, vec!["broken".to_string()]);
+    }
+
+    #[test]
+    fn test_get_unknown_job() {
+        let registry = JobRegistry::default();
+        assert!(registry.get(&Uuid::new_v4()).is_none());
+    }
+}
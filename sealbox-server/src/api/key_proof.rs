@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use rand::RngCore;
+use uuid::Uuid;
+
+use crate::{
+    crypto::master_key::PublicMasterKey,
+    error::{Result, SealboxError},
+};
+
+/// How long a registration challenge can be answered
+pub(crate) const CHALLENGE_TTL: Duration = Duration::from_secs(300);
+
+/// Outstanding challenges kept before expired ones are pruned
+const MAX_PENDING_CHALLENGES: usize = 10_000;
+
+const NONCE_LEN: usize = 32;
+
+struct Challenge {
+    public_key: String,
+    nonce: [u8; NONCE_LEN],
+    expires_at: Instant,
+}
+
+/// In-memory proof-of-possession challenges for master keys about to be registered.
+///
+/// The server encrypts a random nonce to the public key; only the holder of the matching
+/// private key can send it back. Challenges only live as long as the server process.
+#[derive(Clone, Default)]
+pub(crate) struct KeyChallenges {
+    challenges: Arc<Mutex<HashMap<Uuid, Challenge>>>,
+}
+
+impl KeyChallenges {
+    /// Issue a challenge for `public_key` and return its id and the nonce encrypted to the key
+    ///
+    /// # Errors
+    ///
+    /// * `SealboxError::InvalidInput` - When `public_key` is not a well-formed public key PEM
+    /// * `SealboxError::RateLimited` - When too many challenges are still waiting for an answer
+    pub(crate) fn issue(&self, public_key: &str) -> Result<(Uuid, Vec<u8>)> {
+        let key = PublicMasterKey::from_str(public_key).map_err(|err| {
+            SealboxError::InvalidInput(format!("Invalid master public key: {err}"))
+        })?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted = key.encrypt(&nonce)?;
+
+        let now = Instant::now();
+        let mut challenges = self
+            .challenges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if challenges.len() >= MAX_PENDING_CHALLENGES {
+            challenges.retain(|_, challenge| challenge.expires_at > now);
+            if challenges.len() >= MAX_PENDING_CHALLENGES {
+                return Err(SealboxError::RateLimited(CHALLENGE_TTL.as_secs()));
+            }
+        }
+
+        let id = Uuid::new_v4();
+        challenges.insert(
+            id,
+            Challenge {
+                public_key: public_key.to_string(),
+                nonce,
+                expires_at: now + CHALLENGE_TTL,
+            },
+        );
+        Ok((id, encrypted))
+    }
+
+    /// Whether `response` is the nonce of challenge `id`, issued for `public_key` and not
+    /// yet expired.
+    ///
+    /// A challenge is removed on its first answer, right or wrong, so it cannot be
+    /// guessed at repeatedly.
+    pub(crate) fn verify(&self, id: &Uuid, public_key: &str, response: &[u8]) -> bool {
+        let challenge = self
+            .challenges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+        challenge.is_some_and(|challenge| {
+            challenge.expires_at > Instant::now()
+                && challenge.public_key == public_key
+                && challenge.nonce == response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::master_key::{
+        KeyAlgorithm, PrivateMasterKey, generate_key_pair_with_algorithm,
+    };
+
+    #[test]
+    fn test_challenge_answered_once_with_matching_key() {
+        let challenges = KeyChallenges::default();
+        let (private_pem, public_pem) =
+            generate_key_pair_with_algorithm(KeyAlgorithm::X25519).unwrap();
+        let private_key = PrivateMasterKey::from_str(&private_pem).unwrap();
+
+        let (id, encrypted) = challenges.issue(&public_pem).unwrap();
+        let nonce = private_key.decrypt(&encrypted).unwrap();
+        assert!(challenges.verify(&id, &public_pem, &nonce));
+        // Already used
+        assert!(!challenges.verify(&id, &public_pem, &nonce));
+
+        // A wrong answer burns the challenge
+        let (id, encrypted) = challenges.issue(&public_pem).unwrap();
+        let nonce = private_key.decrypt(&encrypted).unwrap();
+        assert!(!challenges.verify(&id, &public_pem, b"guess"));
+        assert!(!challenges.verify(&id, &public_pem, &nonce));
+
+        // The answer only proves possession of the key it was issued for
+        let (_, other_public_pem) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519).unwrap();
+        let (id, encrypted) = challenges.issue(&public_pem).unwrap();
+        let nonce = private_key.decrypt(&encrypted).unwrap();
+        assert!(!challenges.verify(&id, &other_public_pem, &nonce));
+
+        assert!(matches!(
+            challenges.issue("not a key"),
+            Err(SealboxError::InvalidInput(_))
+        ));
+    }
+}
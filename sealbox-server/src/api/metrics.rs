@@ -0,0 +1,254 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+
+use crate::{
+    api::{SealboxResponse, state::AppState},
+    error::{Result, SealboxError},
+    repo::{MasterKeyStatus, SecretFilter},
+};
+
+/// Prometheus metrics exported at `/metrics`.
+///
+/// Request metrics are recorded by [`track_metrics`]; the secret and master key
+/// gauges are refreshed from the database on every scrape.
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    durations: HistogramVec,
+    secrets: IntGauge,
+    master_keys: IntGaugeVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let registry = Registry::new();
+
+        let requests = IntCounterVec::new(
+            Opts::new("sealbox_http_requests_total", "HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .expect("Valid metric definition");
+        let durations = HistogramVec::new(
+            HistogramOpts::new(
+                "sealbox_http_request_duration_seconds",
+                "HTTP request handling time in seconds",
+            ),
+            &["method", "route"],
+        )
+        .expect("Valid metric definition");
+        let secrets = IntGauge::new("sealbox_secrets", "Secrets with an unexpired version")
+            .expect("Valid metric definition");
+        let master_keys = IntGaugeVec::new(
+            Opts::new("sealbox_master_keys", "Registered master keys by status"),
+            &["status"],
+        )
+        .expect("Valid metric definition");
+
+        registry
+            .register(Box::new(requests.clone()))
+            .expect("Metric registered once");
+        registry
+            .register(Box::new(durations.clone()))
+            .expect("Metric registered once");
+        registry
+            .register(Box::new(secrets.clone()))
+            .expect("Metric registered once");
+        registry
+            .register(Box::new(master_keys.clone()))
+            .expect("Metric registered once");
+
+        Self {
+            registry,
+            requests,
+            durations,
+            secrets,
+            master_keys,
+        }
+    }
+}
+
+impl Metrics {
+    fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.durations
+            .with_label_values(&[method, route])
+            .observe(seconds);
+    }
+
+    /// Refresh the gauges backed by the database and encode everything in text format
+    fn render(&self, state: &AppState) -> Result<String> {
+        let conn = state.conn_pool.get()?;
+
+        let page = state.secret_repo.list_secrets(
+            &conn,
+            &SecretFilter {
+                limit: Some(0),
+                ..SecretFilter::default()
+            },
+        )?;
+        self.secrets.set(page.total as i64);
+
+        let keys = state.master_key_repo.fetch_all_master_keys(&conn)?;
+        for status in [
+            MasterKeyStatus::Active,
+            MasterKeyStatus::Retired,
+            MasterKeyStatus::Disabled,
+        ] {
+            let count = keys.iter().filter(|key| key.status == status).count();
+            self.master_keys
+                .with_label_values(&[&format!("{status:?}").to_lowercase()])
+                .set(count as i64);
+        }
+        drop(conn);
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|err| SealboxError::ResponseBuildFailed(err.to_string()))?;
+        String::from_utf8(buffer).map_err(|err| SealboxError::ResponseBuildFailed(err.to_string()))
+    }
+}
+
+/// Middleware counting requests and timing handlers per matched route
+pub(crate) async fn track_metrics(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    // Label by route template, not the concrete path, to keep label cardinality bounded
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    state.metrics.observe_request(
+        &method,
+        &route,
+        response.status().as_u16(),
+        start.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// API handler exporting metrics in Prometheus text format
+///
+/// # Errors
+///
+/// * `SealboxError::Unauthorized` - When `METRICS_TOKEN` is set and the request does not carry it
+///
+/// # HTTP Route
+///
+/// `GET /metrics`
+///
+/// # Security Notes
+///
+/// This route is not behind `AUTH_TOKEN`, so scrapers never hold a token that can read secrets.
+/// Set `METRICS_TOKEN` to require `Authorization: Bearer <token>` for it.
+pub(crate) async fn export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<SealboxResponse> {
+    if let Some(expected) = state.config.snapshot().metrics_token {
+        let token = headers
+            .get("Authorization")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.strip_prefix("Bearer "));
+        if token != Some(expected.as_str()) {
+            return Err(SealboxError::Unauthorized);
+        }
+    }
+
+    Ok(SealboxResponse::Text(state.metrics.render(&state)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{SealboxConfig, SharedConfig},
+        repo::{
+            SqliteAuditRepo, SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo,
+            create_db_pool,
+        },
+    };
+    use std::sync::Arc;
+
+    fn setup_test_state(config: SealboxConfig) -> AppState {
+        let conn_pool = create_db_pool(":memory:", 1).expect("Should create in-memory DB");
+        let conn = conn_pool.get().expect("Should check out a connection");
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should init master_keys table");
+        SqliteSecretRepo::init_table(&conn).expect("Should init secrets table");
+
+        AppState {
+            conn_pool,
+            master_key_repo: Arc::new(SqliteMasterKeyRepo),
+            secret_repo: Arc::new(SqliteSecretRepo),
+            health_repo: Arc::new(SqliteHealthRepo),
+            audit_repo: Arc::new(SqliteAuditRepo),
+            config: SharedConfig::new(config),
+            jobs: Default::default(),
+            secret_changes: Default::default(),
+            metrics: Default::default(),
+            rate_limiter: Default::default(),
+            key_challenges: Default::default(),
+            webhooks: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_renders_text_format() {
+        let state = setup_test_state(SealboxConfig::default());
+        state
+            .metrics
+            .observe_request("GET", "/{version}/secrets", 200, 0.01);
+
+        let response = export(State(state), HeaderMap::new())
+            .await
+            .expect("Export should succeed");
+        let SealboxResponse::Text(body) = response else {
+            panic!("Expected text response");
+        };
+        assert!(body.contains(
+            r#"sealbox_http_requests_total{method="GET",route="/{version}/secrets",status="200"} 1"#
+        ));
+        assert!(body.contains("sealbox_http_request_duration_seconds_bucket"));
+        assert!(body.contains("sealbox_secrets 0"));
+        assert!(body.contains(r#"sealbox_master_keys{status="active"} 0"#));
+    }
+
+    #[tokio::test]
+    async fn test_export_requires_metrics_token_when_set() {
+        let state = setup_test_state(SealboxConfig {
+            metrics_token: Some("scrape-token".to_string()),
+            ..SealboxConfig::default()
+        });
+
+        let err = export(State(state.clone()), HeaderMap::new())
+            .await
+            .expect_err("Missing token should be rejected");
+        assert!(matches!(err, SealboxError::Unauthorized));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", "Bearer scrape-token".parse().unwrap());
+        export(State(state), headers)
+            .await
+            .expect("Matching token should be accepted");
+    }
+}
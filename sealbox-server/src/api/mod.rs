@@ -1,8 +1,8 @@
 use axum::{
     Router,
-    extract::State,
-    http::{HeaderName, Request},
-    middleware::from_fn_with_state,
+    extract::{DefaultBodyLimit, State},
+    http::{HeaderName, Request, header},
+    middleware::{Next, from_fn_with_state},
     response::{IntoResponse, Response},
     routing::get,
 };
@@ -20,21 +20,45 @@ use tracing::{error, info_span};
 use crate::{
     api::{
         auth::static_auth,
-        handler::{admin, master_key, secret},
+        handler::{admin, info, master_key, secret},
         state::AppState,
     },
-    config::SealboxConfig,
-    error::{Result, SealboxError},
+    config::SharedConfig,
+    error::{ErrorBody, Result, SealboxError},
 };
 
+mod audit;
 mod auth;
 mod handler;
+mod https;
+mod job;
+mod key_proof;
+mod metrics;
+mod negotiate;
 mod path;
+mod rate_limit;
 mod state;
+mod sweeper;
+mod validation;
+mod watch;
+mod webhook;
 
 const REQUEST_ID_HEADER: &str = "x-request-id";
 
-pub fn create_app(config: &SealboxConfig) -> Result<Router> {
+/// Room in a request body for everything but the secret value: key, tags, description
+const BODY_OVERHEAD_BYTES: usize = 64 * 1024;
+
+/// Largest request body accepted when secrets may hold `max_secret_bytes`.
+///
+/// Client-encrypted secrets arrive as JSON arrays of byte values, up to four characters
+/// per byte, so the limit leaves room for that encoding of the largest value.
+fn body_limit(max_secret_bytes: usize) -> usize {
+    max_secret_bytes
+        .saturating_mul(4)
+        .saturating_add(BODY_OVERHEAD_BYTES)
+}
+
+pub fn create_app(config: &SharedConfig) -> Result<Router> {
     tracing::info!("Initializing API routes");
     let x_request_id = HeaderName::from_static(REQUEST_ID_HEADER);
     let request_id_middleware = ServiceBuilder::new()
@@ -63,6 +87,9 @@ pub fn create_app(config: &SealboxConfig) -> Result<Router> {
         .layer(PropagateRequestIdLayer::new(x_request_id));
 
     let state = AppState::new(config)?;
+    let max_body_bytes = body_limit(config.snapshot().max_secret_bytes);
+    sweeper::spawn_expiry_sweeper(&state);
+    webhook::spawn_webhook_dispatcher(&state);
 
     // CORS configuration - allow cross-origin requests in development mode
     let cors_layer = if cfg!(debug_assertions) || std::env::var("SEALBOX_ALLOW_CORS").is_ok() {
@@ -71,21 +98,63 @@ pub fn create_app(config: &SealboxConfig) -> Result<Router> {
             .allow_origin(Any)
             .allow_methods(Any)
             .allow_headers(Any)
+            .expose_headers([header::ETAG])
     } else {
         tracing::info!("CORS disabled for production");
         CorsLayer::new().allow_origin([])
     };
 
-    Ok(Router::new()
-        // Health check endpoints without authentication (Kubernetes standard)
+    // Health check endpoints (Kubernetes standard) and metrics are neither audited, rate
+    // limited nor instrumented. They skip authentication while in SEALBOX_AUTH_EXEMPT_PATHS,
+    // which lists them by default
+    let public_routes = Router::new()
         .route("/", get(root))
         .route("/healthz/live", get(liveness_probe))
         .route("/healthz/ready", get(readiness_probe))
-        // Business endpoints requiring authentication
+        .route("/metrics", get(metrics::export))
+        .route_layer(from_fn_with_state(state.clone(), static_auth));
+
+    Ok(Router::new()
+        // Business endpoints requiring authentication unless exempted
+        .route("/{version}/info", get(info::get))
         .route("/{version}/secrets", get(secret::list))
+        .route("/{version}/namespaces", get(secret::namespaces))
+        .route(
+            "/{version}/secrets:batch",
+            axum::routing::post(secret::batch_save),
+        )
         .route(
             "/{version}/secrets/{secret_key}",
-            get(secret::get).put(secret::save).delete(secret::delete),
+            get(secret::get)
+                .put(secret::save)
+                .patch(secret::set_expiry)
+                .delete(secret::delete),
+        )
+        .route(
+            "/{version}/secrets/{secret_key}/versions",
+            get(secret::versions),
+        )
+        .route("/{version}/secrets/{secret_key}/meta", get(secret::meta))
+        .route(
+            "/{version}/secrets/{secret_key}/encrypted",
+            axum::routing::put(secret::save_encrypted),
+        )
+        .route(
+            "/{version}/secrets/{secret_key}/versions/{secret_version}/lock",
+            axum::routing::put(secret::lock),
+        )
+        .route("/{version}/secrets/{secret_key}/watch", get(secret::watch))
+        .route(
+            "/{version}/secrets/{secret_key}/restore",
+            axum::routing::post(secret::restore),
+        )
+        .route(
+            "/{version}/secrets/{secret_key}/rollback",
+            axum::routing::post(secret::rollback),
+        )
+        .route(
+            "/{version}/secrets/{secret_key}/rename",
+            axum::routing::post(secret::rename),
         )
         .route(
             "/{version}/master-key",
@@ -93,16 +162,78 @@ pub fn create_app(config: &SealboxConfig) -> Result<Router> {
                 .put(master_key::rotate)
                 .post(master_key::create),
         )
+        .route(
+            "/{version}/master-key/{master_key_id}/public-key",
+            get(master_key::public_key),
+        )
+        .route(
+            "/{version}/master-key/challenge",
+            axum::routing::post(master_key::challenge),
+        )
+        .route(
+            "/{version}/master-key/rotate",
+            axum::routing::post(master_key::start_rotation),
+        )
         .route(
             "/{version}/admin/cleanup-expired",
             axum::routing::delete(admin::cleanup_expired),
         )
+        .route("/{version}/admin/jobs/{job_id}", get(admin::get_job))
+        .route("/{version}/admin/audit", get(admin::audit))
+        .route("/{version}/admin/stats", get(admin::stats))
+        .route("/{version}/admin/backup", get(admin::backup))
+        .route("/{version}/admin/stale-keys", get(admin::stale_keys))
+        .route(
+            "/{version}/admin/unused-secrets",
+            get(admin::unused_secrets),
+        )
+        .route("/{version}/admin/rotation-due", get(admin::rotation_due))
+        .route("/{version}/admin/scan", get(admin::scan))
         .route_layer(from_fn_with_state(state.clone(), static_auth))
+        // Outside authentication, so rejected attempts are audited too
+        .route_layer(from_fn_with_state(state.clone(), audit::record_audit))
+        // Throttles before the audit log, so rejected floods never reach the database
+        .route_layer(from_fn_with_state(state.clone(), rate_limit::rate_limit))
+        // Refuses plain HTTP writes before anything else looks at them
+        .route_layer(from_fn_with_state(state.clone(), https::require_https))
+        // Wraps authentication too, so rejected requests are counted
+        .route_layer(from_fn_with_state(state.clone(), metrics::track_metrics))
+        .merge(public_routes)
         .with_state(state)
+        // Refuses oversized bodies before they are buffered
+        .layer(DefaultBodyLimit::max(max_body_bytes))
         .layer(cors_layer)
+        .layer(axum::middleware::from_fn(attach_request_id))
+        // Outside `attach_request_id`, so error bodies are re-encoded with their request id
+        .layer(axum::middleware::from_fn(negotiate::encode_response))
         .layer(request_id_middleware))
 }
 
+/// Middleware copying the request id into error bodies, so a failure reported by a
+/// client can be matched with the server logs
+async fn attach_request_id(request: Request<axum::body::Body>, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(request).await;
+    match (response.extensions().get::<ErrorBody>(), request_id) {
+        (Some(body), Some(request_id)) => {
+            let body = ErrorBody {
+                request_id: Some(request_id),
+                ..body.clone()
+            };
+            let (mut parts, _) = response.into_parts();
+            parts.headers.remove(header::CONTENT_LENGTH);
+            let (_, body) = body.into_response().into_parts();
+            Response::from_parts(parts, body)
+        }
+        _ => response,
+    }
+}
+
 async fn root() -> &'static str {
     "Hello, Sealbox!"
 }
@@ -116,7 +247,7 @@ async fn liveness_probe() -> SealboxResponse {
 /// Readiness probe - check if service is ready to receive traffic
 /// Checks database connection and other critical dependencies for Kubernetes readiness probe
 async fn readiness_probe(State(state): State<AppState>) -> Result<SealboxResponse> {
-    let conn = state.conn_pool.lock().map_err(|e| {
+    let conn = state.conn_pool.get().map_err(|e| {
         error!("{}", e);
         SealboxError::DatabaseError("Database connection unavailable".to_string())
     })?;
@@ -141,6 +272,13 @@ enum Version {
 pub enum SealboxResponse {
     Ok,
     Json(serde_json::Value),
+    JsonWithStatus(StatusCode, serde_json::Value),
+    /// JSON body with an `ETag` header, for reads a client may revalidate with `If-None-Match`
+    JsonWithEtag(String, serde_json::Value),
+    /// `304 Not Modified`, with the current `ETag` when the request was a revalidation
+    NotModified(Option<String>),
+    /// File download: the suggested file name and its bytes
+    Attachment(String, Vec<u8>),
     Text(String),
 }
 impl IntoResponse for SealboxResponse {
@@ -151,6 +289,27 @@ impl IntoResponse for SealboxResponse {
                 axum::Json(json!({"result": "Ok","timestamp": now})).into_response()
             }
             SealboxResponse::Json(data) => axum::Json(data).into_response(),
+            SealboxResponse::JsonWithStatus(status, data) => {
+                (status, axum::Json(data)).into_response()
+            }
+            SealboxResponse::JsonWithEtag(etag, data) => {
+                ([(header::ETAG, etag)], axum::Json(data)).into_response()
+            }
+            SealboxResponse::NotModified(None) => StatusCode::NOT_MODIFIED.into_response(),
+            SealboxResponse::NotModified(Some(etag)) => {
+                (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+            }
+            SealboxResponse::Attachment(filename, data) => (
+                [
+                    (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"{filename}\""),
+                    ),
+                ],
+                data,
+            )
+                .into_response(),
             SealboxResponse::Text(data) => axum::response::Response::builder()
                 .status(StatusCode::OK)
                 .header("Content-Type", "text/plain; charset=utf-8")
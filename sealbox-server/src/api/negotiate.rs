@@ -0,0 +1,198 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use yaml_rust2::{Yaml, YamlEmitter, yaml::Hash};
+
+use crate::error::SealboxError;
+
+/// Encodings a client can ask for with `Accept`; every API body is built as JSON first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Yaml,
+    MsgPack,
+}
+
+impl Format {
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" | "application/*" | "*/*" => Some(Format::Json),
+            "application/yaml" | "application/x-yaml" | "text/yaml" => Some(Format::Yaml),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Format::MsgPack)
+            }
+            _ => None,
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Json => "application/json",
+            Format::Yaml => "application/yaml",
+            Format::MsgPack => "application/msgpack",
+        }
+    }
+}
+
+/// The supported format the client prefers, by `q` weight and then by order.
+///
+/// JSON is the answer whenever `Accept` is missing or names nothing supported.
+fn preferred_format(accept: Option<&str>) -> Format {
+    let mut best = (Format::Json, 0.0);
+    for range in accept.unwrap_or_default().split(',') {
+        let mut params = range.split(';').map(str::trim);
+        let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+        let Some(format) = Format::from_media_type(&media_type) else {
+            continue;
+        };
+        let weight = params
+            .filter_map(|param| param.strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if weight > best.1 {
+            best = (format, weight);
+        }
+    }
+    best.0
+}
+
+/// Middleware re-encoding JSON responses as YAML or MessagePack when the client's
+/// `Accept` header prefers them, for clients that cannot easily parse JSON.
+///
+/// Error bodies are re-encoded too. Responses that are not JSON, such as backups and
+/// metrics, pass through unchanged.
+pub(crate) async fn encode_response(request: Request, next: Next) -> Response {
+    let format = preferred_format(
+        request
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok()),
+    );
+
+    let mut response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        return response;
+    }
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    if format == Format::Json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let encoded = axum::body::to_bytes(body, usize::MAX)
+        .await
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).map_err(|err| err.to_string()))
+        .and_then(|value| match format {
+            Format::Json => unreachable!("JSON responses are returned as they are"),
+            Format::Yaml => to_yaml(&value),
+            Format::MsgPack => rmp_serde::to_vec_named(&value).map_err(|err| err.to_string()),
+        });
+    match encoded {
+        Ok(encoded) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            );
+            Response::from_parts(parts, Body::from(encoded))
+        }
+        Err(err) => SealboxError::ResponseBuildFailed(err).into_response(),
+    }
+}
+
+/// Render `value` as a YAML document
+fn to_yaml(value: &Value) -> Result<Vec<u8>, String> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out)
+        .dump(&yaml_from_json(value))
+        .map_err(|err| err.to_string())?;
+    out.push('\n');
+    Ok(out.into_bytes())
+}
+
+fn yaml_from_json(value: &Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(b) => Yaml::Boolean(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Yaml::Integer(i),
+            None => Yaml::Real(n.to_string()),
+        },
+        Value::String(s) => Yaml::String(s.clone()),
+        Value::Array(items) => Yaml::Array(items.iter().map(yaml_from_json).collect()),
+        Value::Object(fields) => {
+            let mut hash = Hash::new();
+            for (key, field) in fields {
+                hash.insert(Yaml::String(key.clone()), yaml_from_json(field));
+            }
+            Yaml::Hash(hash)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_preferred_format() {
+        assert_eq!(preferred_format(None), Format::Json);
+        assert_eq!(preferred_format(Some("text/html")), Format::Json);
+        assert_eq!(preferred_format(Some("application/yaml")), Format::Yaml);
+        assert_eq!(
+            preferred_format(Some("application/json;q=0.5, application/msgpack")),
+            Format::MsgPack
+        );
+        assert_eq!(
+            preferred_format(Some("application/x-yaml, */*;q=0.1")),
+            Format::Yaml
+        );
+        assert_eq!(
+            preferred_format(Some("application/json, application/yaml")),
+            Format::Json
+        );
+    }
+
+    #[test]
+    fn test_msgpack_encoding() {
+        let value = json!({
+            "key": "db",
+            "version": 2,
+            "expires_at": null,
+            "offset": -200,
+            "ratio": 0.5,
+            "tags": ["env=prod"],
+            "created_at": 1_700_000_000,
+        });
+        let encoded = rmp_serde::to_vec_named(&value).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Value>(&encoded).unwrap(), value);
+
+        // Expected bytes from the MessagePack specification's format table
+        assert_eq!(
+            rmp_serde::to_vec_named(&json!({"compact": true, "schema": 0})).unwrap(),
+            b"\x82\xa7compact\xc3\xa6schema\x00"
+        );
+    }
+
+    #[test]
+    fn test_yaml_encoding() {
+        let yaml = to_yaml(&json!({"key": "db", "version": 2, "tags": ["env=prod"]})).unwrap();
+        assert_eq!(
+            String::from_utf8(yaml).unwrap(),
+            "---\nkey: db\ntags:\n  - env=prod\nversion: 2\n"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    api::{
+        auth::{bearer_token, is_valid_token},
+        state::AppState,
+    },
+    error::{Result, SealboxError},
+};
+
+/// Callers tracked before idle buckets are pruned
+const MAX_TRACKED_CALLERS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// In-memory token buckets, one per caller.
+///
+/// Callers presenting a valid token are tracked per token; everyone else, including
+/// clients guessing tokens, is tracked per IP address so guesses share one bucket.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RateLimiter {
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    /// Take one token from `caller`'s bucket, or return the seconds until one is available
+    fn acquire(&self, caller: &str, rps: u32, burst: u32, now: Instant) -> Result<(), u64> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let rps = f64::from(rps);
+        let burst = f64::from(burst);
+
+        if buckets.len() >= MAX_TRACKED_CALLERS && !buckets.contains_key(caller) {
+            // Buckets that have refilled completely carry no state worth keeping
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rps < burst
+            });
+        }
+
+        let bucket = buckets.entry(caller.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated_at: now,
+        });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rps).min(burst);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / rps).ceil().max(1.0) as u64)
+        }
+    }
+}
+
+/// Middleware rejecting callers over `SEALBOX_RATE_LIMIT_RPS` with `429 Too Many Requests`
+pub(crate) async fn rate_limit(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response> {
+    let config = state.config.snapshot();
+    let Some(rps) = config.rate_limit_rps else {
+        return Ok(next.run(request).await);
+    };
+    let burst = config.rate_limit_burst.unwrap_or(rps);
+
+    let caller = match bearer_token(request.headers()) {
+        Some(token) if is_valid_token(&config, token) => format!("token:{token}"),
+        _ => match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => format!("ip:{}", addr.ip()),
+            None => "ip:unknown".to_string(),
+        },
+    };
+
+    state
+        .rate_limiter
+        .acquire(&caller, rps, burst, Instant::now())
+        .map_err(SealboxError::RateLimited)?;
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.acquire("token:a", 1, 3, start).is_ok());
+        }
+        assert_eq!(limiter.acquire("token:a", 1, 3, start), Err(1));
+        // Other callers have their own bucket
+        assert!(limiter.acquire("ip:127.0.0.1", 1, 3, start).is_ok());
+
+        let later = start + Duration::from_millis(1500);
+        assert!(limiter.acquire("token:a", 1, 3, later).is_ok());
+        assert!(limiter.acquire("token:a", 1, 3, later).is_err());
+    }
+
+    #[test]
+    fn test_retry_after_reflects_refill_rate() {
+        let limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert!(limiter.acquire("ip:10.0.0.1", 1, 1, now).is_ok());
+        assert_eq!(limiter.acquire("ip:10.0.0.1", 1, 1, now), Err(1));
+
+        let limiter = RateLimiter::default();
+        assert!(limiter.acquire("ip:10.0.0.1", 100, 1, now).is_ok());
+        // Sub-second waits are rounded up to the smallest Retry-After value
+        assert_eq!(limiter.acquire("ip:10.0.0.1", 100, 1, now), Err(1));
+    }
+}
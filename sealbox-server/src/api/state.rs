@@ -1,37 +1,60 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 use tracing::info;
 
 use crate::{
-    config::SealboxConfig,
+    api::{
+        job::JobRegistry, key_proof::KeyChallenges, metrics::Metrics, rate_limit::RateLimiter,
+        watch::SecretChanges, webhook::Webhooks,
+    },
+    config::SharedConfig,
     error::Result,
     repo::{
-        HealthRepo, MasterKeyRepo, SecretRepo, SqliteHealthRepo, SqliteMasterKeyRepo,
-        SqliteSecretRepo, create_db_connection,
+        AuditRepo, DbPool, HealthRepo, MasterKeyRepo, QueryTimer, SecretRepo, SqliteAuditRepo,
+        SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo, Timed, create_db_pool,
     },
 };
 
 #[derive(Clone)]
 pub(crate) struct AppState {
-    pub(crate) config: Arc<SealboxConfig>,
-    pub(crate) conn_pool: Arc<Mutex<rusqlite::Connection>>,
+    pub(crate) config: SharedConfig,
+    pub(crate) conn_pool: DbPool,
     pub(crate) health_repo: Arc<dyn HealthRepo>,
     pub(crate) secret_repo: Arc<dyn SecretRepo>,
     pub(crate) master_key_repo: Arc<dyn MasterKeyRepo>,
+    pub(crate) audit_repo: Arc<dyn AuditRepo>,
+    pub(crate) jobs: JobRegistry,
+    pub(crate) secret_changes: SecretChanges,
+    pub(crate) metrics: Metrics,
+    pub(crate) rate_limiter: RateLimiter,
+    pub(crate) key_challenges: KeyChallenges,
+    pub(crate) webhooks: Webhooks,
 }
 
 impl AppState {
-    pub fn new(config: &SealboxConfig) -> Result<Self> {
-        let conn = create_db_connection(&config.store_path)?;
+    pub fn new(config: &SharedConfig) -> Result<Self> {
+        let snapshot = config.snapshot();
+        let conn_pool = create_db_pool(&snapshot.store_path, snapshot.db_pool_size)?;
 
+        let conn = conn_pool.get()?;
         SqliteSecretRepo::init_table(&conn)?;
         SqliteMasterKeyRepo::init_table(&conn)?;
+        SqliteAuditRepo::init_table(&conn)?;
+        drop(conn);
 
+        let timer = QueryTimer::new(config.clone());
         let state = Self {
-            config: Arc::new(config.clone()),
-            conn_pool: Arc::new(Mutex::new(conn)),
-            health_repo: Arc::new(SqliteHealthRepo {}),
-            secret_repo: Arc::new(SqliteSecretRepo {}),
-            master_key_repo: Arc::new(SqliteMasterKeyRepo {}),
+            config: config.clone(),
+            conn_pool,
+            health_repo: Arc::new(Timed::new(SqliteHealthRepo {}, timer.clone())),
+            secret_repo: Arc::new(Timed::new(SqliteSecretRepo {}, timer.clone())),
+            master_key_repo: Arc::new(Timed::new(SqliteMasterKeyRepo {}, timer.clone())),
+            audit_repo: Arc::new(Timed::new(SqliteAuditRepo {}, timer)),
+            jobs: JobRegistry::default(),
+            secret_changes: SecretChanges::default(),
+            metrics: Metrics::default(),
+            rate_limiter: RateLimiter::default(),
+            key_challenges: KeyChallenges::default(),
+            webhooks: Webhooks::default(),
         };
 
         // Perform startup cleanup of expired secrets
@@ -43,7 +66,7 @@ impl AppState {
     /// Clean up expired secrets during application startup
     fn startup_cleanup(&self) -> Result<()> {
         info!("Performing startup cleanup of expired secrets...");
-        let conn = self.conn_pool.lock()?;
+        let conn = self.conn_pool.get()?;
         let deleted_count = self.secret_repo.cleanup_expired_secrets(&conn)?;
         if deleted_count > 0 {
             info!(
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info};
+
+use crate::{api::state::AppState, error::Result};
+
+/// Start the background task deleting expired secrets every `cleanup_interval_secs`.
+///
+/// Reads only clean up the key they touch, so secrets that are never read again
+/// would otherwise stay in the database until the next restart. An interval of 0
+/// disables the sweeper.
+pub(crate) fn spawn_expiry_sweeper(state: &AppState) {
+    let interval_secs = state.config.snapshot().cleanup_interval_secs;
+    if interval_secs == 0 {
+        info!("Expired secret sweeper disabled");
+        return;
+    }
+
+    info!("Sweeping expired secrets every {}s", interval_secs);
+    spawn_with_interval(state.clone(), Duration::from_secs(interval_secs));
+}
+
+fn spawn_with_interval(state: AppState, period: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(period);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately, and startup already cleaned up
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            // SQLite calls block, so keep them off the async workers
+            let sweep_state = state.clone();
+            match tokio::task::spawn_blocking(move || sweep_expired(&sweep_state)).await {
+                Ok(Ok(deleted_count)) => {
+                    info!("Expiry sweep removed {} expired secrets", deleted_count)
+                }
+                Ok(Err(err)) => error!("Expiry sweep failed: {}", err),
+                Err(err) => error!("Expiry sweep task panicked: {}", err),
+            }
+        }
+    });
+}
+
+fn sweep_expired(state: &AppState) -> Result<usize> {
+    let conn = state.conn_pool.get()?;
+    state.secret_repo.cleanup_expired_secrets(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::{SealboxConfig, SharedConfig},
+        crypto::{envelope::CURRENT_ENVELOPE_VERSION, master_key::generate_key_pair},
+        repo::{
+            MasterKey, NewSecret, SqliteAuditRepo, SqliteHealthRepo, SqliteMasterKeyRepo,
+            SqliteSecretRepo, create_db_pool,
+        },
+    };
+    use std::sync::Arc;
+
+    fn setup_test_state() -> AppState {
+        let conn_pool = create_db_pool(":memory:", 1).expect("Should create in-memory DB");
+        let conn = conn_pool.get().expect("Should check out a connection");
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should init master_keys table");
+        SqliteSecretRepo::init_table(&conn).expect("Should init secrets table");
+
+        AppState {
+            conn_pool,
+            master_key_repo: Arc::new(SqliteMasterKeyRepo),
+            secret_repo: Arc::new(SqliteSecretRepo),
+            health_repo: Arc::new(SqliteHealthRepo),
+            audit_repo: Arc::new(SqliteAuditRepo),
+            config: SharedConfig::new(SealboxConfig::default()),
+            jobs: Default::default(),
+            secret_changes: Default::default(),
+            metrics: Default::default(),
+            rate_limiter: Default::default(),
+            key_challenges: Default::default(),
+            webhooks: Default::default(),
+        }
+    }
+
+    /// Store `key` with a one hour TTL, then move its expiry into the past
+    fn insert_expired_secret(state: &AppState, key: &str) {
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let master_key = MasterKey::new(public_pem).unwrap();
+        let mut conn = state.conn_pool.get().unwrap();
+        state
+            .secret_repo
+            .create_new_version(
+                &mut conn,
+                master_key,
+                NewSecret {
+                    ttl: Some(3600),
+                    ..NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .expect("Should create secret");
+        conn.execute("UPDATE secrets SET expires_at = 1 WHERE key = ?1", [key])
+            .expect("Should expire secret");
+    }
+
+    fn row_count(state: &AppState) -> i64 {
+        let conn = state.conn_pool.get().unwrap();
+        conn.query_row("SELECT COUNT(*) FROM secrets", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sweeper_removes_expired_secrets() {
+        let state = setup_test_state();
+        insert_expired_secret(&state, "stale");
+        assert_eq!(row_count(&state), 1);
+
+        spawn_with_interval(state.clone(), Duration::from_millis(20));
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while row_count(&state) > 0 {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "Sweeper should have removed the expired secret"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::{Result, SealboxError};
+
+/// Collects every problem with a request so they can be reported together
+/// instead of failing on the first one.
+#[derive(Debug, Default)]
+pub(crate) struct Validator {
+    errors: Vec<String>,
+}
+
+impl Validator {
+    /// Record `message` unless `condition` holds
+    pub(crate) fn check(&mut self, condition: bool, message: impl FnOnce() -> String) {
+        if !condition {
+            self.errors.push(message());
+        }
+    }
+
+    /// `Ok` if every check passed, otherwise all recorded problems in request order
+    pub(crate) fn finish(self) -> Result<()> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(SealboxError::ValidationFailed(self.errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validator_collects_all_errors() {
+        let mut validator = Validator::default();
+        validator.check(false, || "first".to_string());
+        validator.check(true, || "skipped".to_string());
+        validator.check(false, || "second".to_string());
+
+        match validator.finish() {
+            Err(SealboxError::ValidationFailed(errors)) => {
+                assert_eq!(errors, vec!["first", "second"]);
+            }
+            other => panic!("Expected validation failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validator_passes_without_errors() {
+        let mut validator = Validator::default();
+        validator.check(true, || "unused".to_string());
+        assert!(validator.finish().is_ok());
+    }
+}
@@ -0,0 +1,48 @@
+use tokio::sync::watch;
+
+/// Broadcasts that some secret has gained a new version.
+///
+/// Watchers subscribe before reading the current version from the database, so a
+/// save that lands in between still wakes them. The counter carries no
+/// information about which key changed: woken watchers re-check their own key.
+#[derive(Debug, Clone)]
+pub(crate) struct SecretChanges {
+    tx: watch::Sender<u64>,
+}
+
+impl Default for SecretChanges {
+    fn default() -> Self {
+        Self {
+            tx: watch::Sender::new(0),
+        }
+    }
+}
+
+impl SecretChanges {
+    /// Wake every watcher, called after new secret versions are committed
+    pub(crate) fn notify(&self) {
+        self.tx
+            .send_modify(|generation| *generation = generation.wrapping_add(1));
+    }
+
+    /// Subscribe to changes made after this call
+    pub(crate) fn subscribe(&self) -> watch::Receiver<u64> {
+        self.tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notify_wakes_subscriber() {
+        let changes = SecretChanges::default();
+        let mut rx = changes.subscribe();
+        assert!(!rx.has_changed().unwrap());
+
+        changes.notify();
+        rx.changed().await.expect("Sender should still be alive");
+        assert_eq!(*rx.borrow_and_update(), 1);
+    }
+}
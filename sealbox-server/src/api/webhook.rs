@@ -0,0 +1,384 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use hmac::{Hmac, Mac, digest::KeyInit};
+use serde::Serialize;
+use sha2::Sha256;
+use tokio::sync::{
+    Semaphore,
+    mpsc::{self, error::TrySendError},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    api::state::AppState,
+    config::{SealboxConfig, SharedConfig},
+};
+
+/// Events waiting for delivery before new ones are dropped
+const QUEUE_CAPACITY: usize = 1024;
+
+/// Attempts per endpoint, including the first
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, doubled after every further failure
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Time allowed for an endpoint to answer a single attempt
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Deliveries in flight at once, across all endpoints
+const MAX_CONCURRENT_DELIVERIES: usize = 16;
+
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`, keyed with `SEALBOX_WEBHOOK_SECRET`
+pub(crate) const SIGNATURE_HEADER: &str = "x-sealbox-signature";
+
+/// Body POSTed to every webhook endpoint. Never carries the secret value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct WebhookEvent {
+    pub(crate) event: &'static str, // `secret.save`, `secret.delete` or `secret.lock`
+    pub(crate) key: String,
+    pub(crate) version: i32,
+    pub(crate) timestamp: i64, // When the change was committed (Unix time)
+}
+
+/// Bounded queue of secret changes for the webhook dispatcher.
+///
+/// Handlers enqueue without waiting, so slow or failing endpoints never hold up a
+/// request. When the queue is full the event is dropped and logged.
+#[derive(Debug, Clone)]
+pub(crate) struct Webhooks {
+    tx: mpsc::Sender<WebhookEvent>,
+    rx: Arc<Mutex<Option<mpsc::Receiver<WebhookEvent>>>>, // Taken by the dispatcher
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel(QUEUE_CAPACITY);
+        Self {
+            tx,
+            rx: Arc::new(Mutex::new(Some(rx))),
+        }
+    }
+}
+
+impl Webhooks {
+    /// Queue `event` for `key` at `version`, called after the change is committed.
+    /// Does nothing unless webhook URLs are configured.
+    pub(crate) fn notify(
+        &self,
+        config: &SealboxConfig,
+        event: &'static str,
+        key: &str,
+        version: i32,
+    ) {
+        if config.webhook_urls.is_empty() {
+            return;
+        }
+        let event = WebhookEvent {
+            event,
+            key: key.to_string(),
+            version,
+            timestamp: time::OffsetDateTime::now_utc().unix_timestamp(),
+        };
+        match self.tx.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(event)) => {
+                warn!("Webhook queue is full, dropping {:?}", event)
+            }
+            Err(TrySendError::Closed(event)) => {
+                warn!("Webhook dispatcher is not running, dropping {:?}", event)
+            }
+        }
+    }
+
+    pub(crate) fn take_receiver(&self) -> Option<mpsc::Receiver<WebhookEvent>> {
+        self.rx
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+    }
+}
+
+/// Start the background task delivering queued events to `SEALBOX_WEBHOOK_URL`.
+///
+/// Endpoints are read from the configuration for every event, so reloading it takes effect
+/// for queued events too.
+pub(crate) fn spawn_webhook_dispatcher(state: &AppState) {
+    let Some(rx) = state.webhooks.take_receiver() else {
+        return;
+    };
+    let config = state.config.clone();
+    let client = match reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("sealbox-server/", env!("CARGO_PKG_VERSION")))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            error!(
+                "Failed to create webhook client, webhooks disabled: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    let urls = config.snapshot().webhook_urls.len();
+    if urls > 0 {
+        info!("Delivering secret change webhooks to {} endpoints", urls);
+    }
+    tokio::spawn(dispatch(rx, config, client, INITIAL_BACKOFF));
+}
+
+/// Hand every queued event to one task per endpoint, so a slow or failing endpoint never
+/// delays the others.
+///
+/// At most `MAX_CONCURRENT_DELIVERIES` deliveries run at once; once they are all busy the
+/// dispatcher waits and new events pile up in the queue. Deliveries to one endpoint may
+/// therefore arrive out of commit order.
+async fn dispatch(
+    mut rx: mpsc::Receiver<WebhookEvent>,
+    config: SharedConfig,
+    client: reqwest::Client,
+    initial_backoff: Duration,
+) {
+    let deliveries = Arc::new(Semaphore::new(MAX_CONCURRENT_DELIVERIES));
+    while let Some(event) = rx.recv().await {
+        let config = config.snapshot();
+        let Some(secret) = config.webhook_secret else {
+            continue;
+        };
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => Arc::new(body),
+            Err(err) => {
+                error!("Failed to serialize webhook event {:?}: {}", event, err);
+                continue;
+            }
+        };
+        for url in config.webhook_urls {
+            let Ok(permit) = deliveries.clone().acquire_owned().await else {
+                return;
+            };
+            let (client, secret, body) = (client.clone(), secret.clone(), body.clone());
+            tokio::spawn(async move {
+                deliver(&client, &url, &secret, &body, initial_backoff).await;
+                drop(permit);
+            });
+        }
+    }
+}
+
+/// POST `body` to `url`, retrying with exponential backoff up to `MAX_ATTEMPTS` times
+///
+/// Returns whether the endpoint answered with a success status.
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    body: &[u8],
+    initial_backoff: Duration,
+) -> bool {
+    let signature = format!("sha256={}", sign(secret.as_bytes(), body));
+    let mut backoff = initial_backoff;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.to_vec())
+            .send()
+            .await;
+        match result {
+            Ok(response) if response.status().is_success() => return true,
+            Ok(response) => warn!(
+                "Webhook {} answered {} (attempt {}/{})",
+                url,
+                response.status(),
+                attempt,
+                MAX_ATTEMPTS
+            ),
+            Err(err) => warn!(
+                "Webhook {} failed: {} (attempt {}/{})",
+                url, err, attempt, MAX_ATTEMPTS
+            ),
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    error!(
+        "Giving up on webhook {} after {} attempts",
+        url, MAX_ATTEMPTS
+    );
+    false
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`
+fn sign(secret: &[u8], body: &[u8]) -> String {
+    <Hmac<Sha256> as KeyInit>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length")
+        .chain_update(body)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, extract::State, http::HeaderMap, http::StatusCode, routing::post};
+
+    type Received = Arc<Mutex<Vec<(Option<String>, Vec<u8>)>>>;
+
+    /// Endpoint recording every request, failing the first `failures` of them
+    async fn spawn_receiver(failures: usize) -> (String, Received) {
+        let received = Received::default();
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    move |State(received): State<Received>,
+                          headers: HeaderMap,
+                          body: axum::body::Bytes| async move {
+                        let mut received = received.lock().unwrap();
+                        received.push((
+                            headers
+                                .get(SIGNATURE_HEADER)
+                                .and_then(|value| value.to_str().ok())
+                                .map(str::to_string),
+                            body.to_vec(),
+                        ));
+                        if received.len() <= failures {
+                            StatusCode::INTERNAL_SERVER_ERROR
+                        } else {
+                            StatusCode::OK
+                        }
+                    },
+                ),
+            )
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (format!("http://{addr}/hook"), received)
+    }
+
+    #[test]
+    fn test_sign_matches_rfc_4231_vector() {
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_notify_only_queues_with_urls_configured() {
+        let webhooks = Webhooks::default();
+        let mut rx = webhooks.take_receiver().unwrap();
+
+        webhooks.notify(&SealboxConfig::default(), "secret.save", "db", 1);
+        assert!(rx.try_recv().is_err());
+
+        let config = SealboxConfig {
+            webhook_urls: vec!["http://127.0.0.1:9/hook".to_string()],
+            webhook_secret: Some("hook-secret".to_string()),
+            ..SealboxConfig::default()
+        };
+        webhooks.notify(&config, "secret.delete", "db", 2);
+        let event = rx.try_recv().expect("Event should be queued");
+        assert_eq!(
+            (event.event, event.key.as_str(), event.version),
+            ("secret.delete", "db", 2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deliver_retries_and_signs() {
+        let (url, received) = spawn_receiver(2).await;
+        let client = reqwest::Client::new();
+        let body = br#"{"event":"secret.save","key":"db","version":1,"timestamp":0}"#;
+
+        assert!(
+            deliver(
+                &client,
+                &url,
+                "hook-secret",
+                body,
+                Duration::from_millis(10)
+            )
+            .await
+        );
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3, "Two failures should be retried");
+        let expected = format!("sha256={}", sign(b"hook-secret", body));
+        for (signature, request_body) in received.iter() {
+            assert_eq!(signature.as_deref(), Some(expected.as_str()));
+            assert_eq!(request_body, body);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_endpoint_does_not_hold_up_others() {
+        // Never answers within the test
+        let app = Router::new().route(
+            "/hook",
+            post(|| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                StatusCode::OK
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let slow_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        let (fast_url, received) = spawn_receiver(0).await;
+
+        let config = SharedConfig::new(SealboxConfig {
+            webhook_urls: vec![slow_url, fast_url],
+            webhook_secret: Some("hook-secret".to_string()),
+            ..SealboxConfig::default()
+        });
+        let webhooks = Webhooks::default();
+        tokio::spawn(dispatch(
+            webhooks.take_receiver().unwrap(),
+            config.clone(),
+            reqwest::Client::new(),
+            Duration::from_millis(10),
+        ));
+
+        for version in 1..=3 {
+            webhooks.notify(&config.snapshot(), "secret.save", "db", version);
+        }
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while received.lock().unwrap().len() < 3 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Every event should reach the fast endpoint");
+    }
+
+    #[tokio::test]
+    async fn test_deliver_gives_up_after_max_attempts() {
+        let (url, received) = spawn_receiver(usize::MAX).await;
+        let client = reqwest::Client::new();
+
+        assert!(
+            !deliver(
+                &client,
+                &url,
+                "hook-secret",
+                b"{}",
+                Duration::from_millis(1)
+            )
+            .await
+        );
+        assert_eq!(received.lock().unwrap().len(), MAX_ATTEMPTS as usize);
+    }
+}
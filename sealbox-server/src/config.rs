@@ -1,5 +1,11 @@
-use std::env;
-use tracing::{error, info};
+use std::{
+    env,
+    net::{SocketAddr, ToSocketAddrs},
+    sync::{Arc, PoisonError, RwLock},
+};
+use tracing::{error, info, warn};
+
+use crate::crypto::totp;
 
 /// Sealbox configuration struct
 #[derive(Debug, Clone)]
@@ -7,11 +13,94 @@ pub struct SealboxConfig {
     pub auth_token: String,
     pub store_path: String,
     pub listen_addr: String,
+    pub db_pool_size: u32,               // Most SQLite connections open at once
+    pub max_ttl_seconds: Option<i64>,    // Upper bound for secret TTLs, unbounded if None
+    pub metrics_token: Option<String>,   // Bearer token for /metrics, open if None
+    pub cleanup_interval_secs: u64,      // Seconds between expired secret sweeps, disabled if 0
+    pub rate_limit_rps: Option<u32>, // Sustained requests per second per caller, unlimited if None
+    pub rate_limit_burst: Option<u32>, // Requests allowed in a burst, defaults to `rate_limit_rps`
+    pub scoped_tokens: Vec<ScopedToken>, // Extra tokens with limited access; `auth_token` has every scope
+    pub compress_secrets: bool,          // Compress new secrets unless the request says otherwise
+    pub slow_query_ms: Option<u32>, // Log database operations slower than this, disabled if None
+    pub auth_exempt_paths: Vec<String>, // Request paths served without a bearer token
+    pub require_key_proof: bool,    // Reject master key registrations without proof of possession
+    pub totp_secret: Option<Vec<u8>>, // Decoded TOTP secret gating destructive operations, off if None
+    pub webhook_urls: Vec<String>,    // Endpoints notified of secret changes, none if empty
+    pub webhook_secret: Option<String>, // Shared secret signing webhook payloads, required with `webhook_urls`
+    pub require_https: bool,            // Reject writes that did not arrive over HTTPS
+    pub max_secret_bytes: usize,        // Largest secret value accepted, in bytes
+}
+
+/// What a token is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    Read,  // Read secrets and metadata
+    Write, // Save and restore secrets, register master keys
+    Admin, // Delete secrets, rotate master keys, administration endpoints
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Admin => "admin",
+        })
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "read" => Ok(Scope::Read),
+            "write" => Ok(Scope::Write),
+            "admin" => Ok(Scope::Admin),
+            other => Err(format!("unknown scope '{other}'")),
+        }
+    }
+}
+
+/// A bearer token limited to a set of scopes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopedToken {
+    pub token: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// All scopes, granted to `AUTH_TOKEN`
+pub const ALL_SCOPES: &[Scope] = &[Scope::Read, Scope::Write, Scope::Admin];
+
+/// Sweep expired secrets hourly unless `SEALBOX_CLEANUP_INTERVAL_SECS` says otherwise
+pub const DEFAULT_CLEANUP_INTERVAL_SECS: u64 = 3600;
+
+/// SQLite connections pooled unless `--pool-size` or `SEALBOX_DB_POOL_SIZE` say otherwise
+pub const DEFAULT_DB_POOL_SIZE: u32 = 5;
+
+/// Largest secret value accepted unless `SEALBOX_MAX_SECRET_BYTES` says otherwise (1 MiB)
+pub const DEFAULT_MAX_SECRET_BYTES: usize = 1024 * 1024;
+
+/// Paths served without authentication unless `SEALBOX_AUTH_EXEMPT_PATHS` says otherwise
+pub const DEFAULT_AUTH_EXEMPT_PATHS: &[&str] =
+    &["/", "/healthz/live", "/healthz/ready", "/metrics"];
+
+/// Settings given on the command line, which take precedence over the environment
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub store_path: Option<String>,  // Replaces STORE_PATH
+    pub listen_addr: Option<String>, // Replaces LISTEN_ADDR
+    pub db_pool_size: Option<u32>,   // Replaces SEALBOX_DB_POOL_SIZE
 }
 
 impl SealboxConfig {
     /// Load configuration from environment variables. Logs and returns Err if any required variable is missing or invalid.
     pub fn from_env() -> Result<Self, String> {
+        Self::from_env_with(&ConfigOverrides::default())
+    }
+
+    /// Load configuration from environment variables, preferring any value set in `overrides`
+    pub fn from_env_with(overrides: &ConfigOverrides) -> Result<Self, String> {
         info!("Loading Sealbox configuration from environment variables...");
 
         let auth_token = match env::var("AUTH_TOKEN") {
@@ -22,28 +111,163 @@ impl SealboxConfig {
             }
         };
 
-        let store_path = match env::var("STORE_PATH") {
-            Ok(val) if !val.trim().is_empty() => val,
+        let store_path = match overrides
+            .store_path
+            .clone()
+            .or_else(|| env::var("STORE_PATH").ok())
+        {
+            Some(val) if !val.trim().is_empty() => val,
             _ => {
                 error!("Environment variable STORE_PATH is missing or empty");
                 return Err("STORE_PATH is missing or empty".into());
             }
         };
 
-        let listen_addr = match env::var("LISTEN_ADDR") {
-            Ok(val) if !val.trim().is_empty() => val,
+        let listen_addr = match overrides
+            .listen_addr
+            .clone()
+            .or_else(|| env::var("LISTEN_ADDR").ok())
+        {
+            Some(val) if !val.trim().is_empty() => val,
             _ => {
                 error!("Environment variable LISTEN_ADDR is missing or empty");
                 return Err("LISTEN_ADDR is missing or empty".into());
             }
         };
 
+        let db_pool_size = match overrides.db_pool_size {
+            Some(0) => {
+                error!("--pool-size must be a positive integer");
+                return Err("--pool-size must be a positive integer".into());
+            }
+            Some(size) => size,
+            None => positive_u32_var("SEALBOX_DB_POOL_SIZE")?.unwrap_or(DEFAULT_DB_POOL_SIZE),
+        };
+
+        let max_ttl_seconds = match env::var("MAX_TTL_SECONDS") {
+            Ok(val) if !val.trim().is_empty() => match val.trim().parse::<i64>() {
+                Ok(max) if max > 0 => Some(max),
+                _ => {
+                    error!("Environment variable MAX_TTL_SECONDS must be a positive integer");
+                    return Err("MAX_TTL_SECONDS must be a positive integer".into());
+                }
+            },
+            _ => None,
+        };
+
+        let metrics_token = env::var("METRICS_TOKEN")
+            .ok()
+            .filter(|val| !val.trim().is_empty());
+
+        let cleanup_interval_secs = match env::var("SEALBOX_CLEANUP_INTERVAL_SECS") {
+            Ok(val) if !val.trim().is_empty() => match val.trim().parse::<u64>() {
+                Ok(interval) => interval,
+                Err(_) => {
+                    error!(
+                        "Environment variable SEALBOX_CLEANUP_INTERVAL_SECS must be a non-negative integer"
+                    );
+                    return Err(
+                        "SEALBOX_CLEANUP_INTERVAL_SECS must be a non-negative integer".into(),
+                    );
+                }
+            },
+            _ => DEFAULT_CLEANUP_INTERVAL_SECS,
+        };
+
+        let rate_limit_rps = positive_u32_var("SEALBOX_RATE_LIMIT_RPS")?;
+        let rate_limit_burst = positive_u32_var("SEALBOX_RATE_LIMIT_BURST")?;
+        let slow_query_ms = positive_u32_var("SEALBOX_SLOW_QUERY_MS")?;
+
+        let scoped_tokens = match env::var("SEALBOX_SCOPED_TOKENS") {
+            Ok(val) if !val.trim().is_empty() => parse_scoped_tokens(&val).map_err(|err| {
+                error!(
+                    "Environment variable SEALBOX_SCOPED_TOKENS is invalid: {}",
+                    err
+                );
+                format!("SEALBOX_SCOPED_TOKENS is invalid: {err}")
+            })?,
+            _ => Vec::new(),
+        };
+
+        let compress_secrets = bool_var("SEALBOX_COMPRESS_SECRETS")?;
+        let require_key_proof = bool_var("SEALBOX_REQUIRE_KEY_PROOF")?;
+        let require_https = bool_var("SEALBOX_REQUIRE_HTTPS")?;
+        let max_secret_bytes = positive_u32_var("SEALBOX_MAX_SECRET_BYTES")?
+            .map_or(DEFAULT_MAX_SECRET_BYTES, |max| max as usize);
+
+        let totp_secret = match env::var("SEALBOX_TOTP_SECRET") {
+            Ok(val) if !val.trim().is_empty() => {
+                Some(totp::decode_secret(&val).map_err(|err| {
+                    error!(
+                        "Environment variable SEALBOX_TOTP_SECRET is invalid: {}",
+                        err
+                    );
+                    format!("SEALBOX_TOTP_SECRET is invalid: {err}")
+                })?)
+            }
+            _ => None,
+        };
+
+        let webhook_urls = match env::var("SEALBOX_WEBHOOK_URL") {
+            Ok(val) => parse_webhook_urls(&val).map_err(|err| {
+                error!(
+                    "Environment variable SEALBOX_WEBHOOK_URL is invalid: {}",
+                    err
+                );
+                format!("SEALBOX_WEBHOOK_URL is invalid: {err}")
+            })?,
+            Err(_) => Vec::new(),
+        };
+
+        let webhook_secret = env::var("SEALBOX_WEBHOOK_SECRET")
+            .ok()
+            .filter(|val| !val.trim().is_empty());
+        if !webhook_urls.is_empty() && webhook_secret.is_none() {
+            error!("SEALBOX_WEBHOOK_SECRET must be set when SEALBOX_WEBHOOK_URL is");
+            return Err("SEALBOX_WEBHOOK_SECRET must be set when SEALBOX_WEBHOOK_URL is".into());
+        }
+
+        // Set but empty means every route requires a token
+        let auth_exempt_paths = match env::var("SEALBOX_AUTH_EXEMPT_PATHS") {
+            Ok(val) => parse_exempt_paths(&val).map_err(|err| {
+                error!(
+                    "Environment variable SEALBOX_AUTH_EXEMPT_PATHS is invalid: {}",
+                    err
+                );
+                format!("SEALBOX_AUTH_EXEMPT_PATHS is invalid: {err}")
+            })?,
+            Err(_) => default_auth_exempt_paths(),
+        };
+
         info!(
             "Sealbox configuration loaded: {:?}",
             SealboxConfig {
                 auth_token: "[HIDDEN]".to_string(),
                 store_path: store_path.clone(),
                 listen_addr: listen_addr.clone(),
+                db_pool_size,
+                max_ttl_seconds,
+                metrics_token: metrics_token.as_ref().map(|_| "[HIDDEN]".to_string()),
+                cleanup_interval_secs,
+                rate_limit_rps,
+                rate_limit_burst,
+                scoped_tokens: scoped_tokens
+                    .iter()
+                    .map(|scoped| ScopedToken {
+                        token: "[HIDDEN]".to_string(),
+                        scopes: scoped.scopes.clone(),
+                    })
+                    .collect(),
+                compress_secrets,
+                slow_query_ms,
+                auth_exempt_paths: auth_exempt_paths.clone(),
+                require_key_proof,
+                // Only whether it is set
+                totp_secret: totp_secret.as_ref().map(|_| Vec::new()),
+                webhook_urls: webhook_urls.clone(),
+                webhook_secret: webhook_secret.as_ref().map(|_| "[HIDDEN]".to_string()),
+                require_https,
+                max_secret_bytes,
             }
         );
 
@@ -51,8 +275,173 @@ impl SealboxConfig {
             auth_token,
             store_path,
             listen_addr,
+            db_pool_size,
+            max_ttl_seconds,
+            metrics_token,
+            cleanup_interval_secs,
+            rate_limit_rps,
+            rate_limit_burst,
+            scoped_tokens,
+            compress_secrets,
+            slow_query_ms,
+            auth_exempt_paths,
+            require_key_proof,
+            totp_secret,
+            webhook_urls,
+            webhook_secret,
+            require_https,
+            max_secret_bytes,
         })
     }
+
+    /// Scopes granted to `token`, or `None` if it is not a known token
+    pub fn scopes_for(&self, token: &str) -> Option<&[Scope]> {
+        if token == self.auth_token {
+            return Some(ALL_SCOPES);
+        }
+        self.scoped_tokens
+            .iter()
+            .find(|scoped| scoped.token == token)
+            .map(|scoped| scoped.scopes.as_slice())
+    }
+}
+
+/// Parse `token:scope,scope;token:scope` into scoped tokens
+fn parse_scoped_tokens(value: &str) -> Result<Vec<ScopedToken>, String> {
+    value
+        .split(';')
+        .filter(|entry| !entry.trim().is_empty())
+        .map(|entry| {
+            let (token, scopes) = entry
+                .split_once(':')
+                .ok_or_else(|| "expected entries of the form token:scope,scope".to_string())?;
+            let token = token.trim();
+            if token.is_empty() {
+                return Err("token must not be empty".to_string());
+            }
+            let scopes = scopes
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Scope>, _>>()?;
+            Ok(ScopedToken {
+                token: token.to_string(),
+                scopes,
+            })
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of request paths such as `/healthz/ready,/v1/info`
+fn parse_exempt_paths(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            if path.starts_with('/') {
+                Ok(path.to_string())
+            } else {
+                Err(format!("path '{path}' must start with '/'"))
+            }
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of webhook endpoints such as `https://a.example/hook,http://b:9000/`
+fn parse_webhook_urls(value: &str) -> Result<Vec<String>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| {
+            if url.starts_with("http://") || url.starts_with("https://") {
+                Ok(url.to_string())
+            } else {
+                Err(format!("URL '{url}' must start with http:// or https://"))
+            }
+        })
+        .collect()
+}
+
+fn default_auth_exempt_paths() -> Vec<String> {
+    DEFAULT_AUTH_EXEMPT_PATHS
+        .iter()
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Resolve a listen address to the socket addresses to bind
+///
+/// Accepts IPv4 (`127.0.0.1:8080`), bracketed IPv6 (`[::1]:8080`) and `host:port`, where
+/// the host name is resolved and may yield several addresses.
+///
+/// # Errors
+///
+/// Returns a message naming the problem when the port is missing or invalid, an IPv6
+/// address is not bracketed, or the host name cannot be resolved.
+pub fn resolve_listen_addr(addr: &str) -> Result<Vec<SocketAddr>, String> {
+    let addr = addr.trim();
+    if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
+        return Ok(vec![socket_addr]);
+    }
+
+    if !addr.starts_with('[') && addr.matches(':').count() > 1 {
+        return Err(format!(
+            "'{addr}' looks like an IPv6 address; put it in brackets, e.g. [::1]:8080"
+        ));
+    }
+    let (host, port) = addr
+        .rsplit_once(':')
+        .ok_or_else(|| format!("'{addr}' has no port; expected host:port, e.g. 0.0.0.0:8080"))?;
+    let port: u16 = port
+        .parse()
+        .map_err(|_| format!("'{port}' in '{addr}' is not a valid port (0-65535)"))?;
+    if host.is_empty() {
+        return Err(format!(
+            "'{addr}' has no host; use 0.0.0.0:{port} to listen everywhere"
+        ));
+    }
+    if host.starts_with('[') {
+        return Err(format!("'{host}' is not a valid IPv6 address"));
+    }
+
+    let resolved: Vec<SocketAddr> = (host, port)
+        .to_socket_addrs()
+        .map_err(|err| format!("Failed to resolve host '{host}': {err}"))?
+        .collect();
+    if resolved.is_empty() {
+        return Err(format!("Host '{host}' did not resolve to any address"));
+    }
+    Ok(resolved)
+}
+
+/// Read an optional environment variable that must be a positive integer when set
+fn positive_u32_var(name: &str) -> Result<Option<u32>, String> {
+    match env::var(name) {
+        Ok(val) if !val.trim().is_empty() => match val.trim().parse::<u32>() {
+            Ok(value) if value > 0 => Ok(Some(value)),
+            _ => {
+                error!("Environment variable {} must be a positive integer", name);
+                Err(format!("{name} must be a positive integer"))
+            }
+        },
+        _ => Ok(None),
+    }
+}
+
+/// `true` for `true` or `1` and `false` for `false`, `0` or an unset variable
+fn bool_var(name: &str) -> Result<bool, String> {
+    match env::var(name) {
+        Ok(val) if !val.trim().is_empty() => match val.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(true),
+            "false" | "0" => Ok(false),
+            _ => {
+                error!("Environment variable {} must be true or false", name);
+                Err(format!("{name} must be true or false"))
+            }
+        },
+        _ => Ok(false),
+    }
 }
 
 impl Default for SealboxConfig {
@@ -61,6 +450,221 @@ impl Default for SealboxConfig {
             auth_token: "test-token".to_string(),
             store_path: ":memory:".to_string(),
             listen_addr: "127.0.0.1:8080".to_string(),
+            db_pool_size: DEFAULT_DB_POOL_SIZE,
+            max_ttl_seconds: None,
+            metrics_token: None,
+            cleanup_interval_secs: DEFAULT_CLEANUP_INTERVAL_SECS,
+            rate_limit_rps: None,
+            rate_limit_burst: None,
+            scoped_tokens: Vec::new(),
+            compress_secrets: false,
+            slow_query_ms: None,
+            auth_exempt_paths: default_auth_exempt_paths(),
+            require_key_proof: false,
+            totp_secret: None,
+            webhook_urls: Vec::new(),
+            webhook_secret: None,
+            require_https: false,
+            max_secret_bytes: DEFAULT_MAX_SECRET_BYTES,
+        }
+    }
+}
+
+/// Configuration shared with running request handlers.
+///
+/// `reload` only applies settings that are safe to change at runtime
+/// (tokens, `max_ttl_seconds`, `metrics_token`, rate limits, compression, slow query
+/// threshold, authentication exemptions, key proof requirement, TOTP secret, webhooks,
+/// `require_https`); `store_path`, `listen_addr`, `cleanup_interval_secs` and
+/// `max_secret_bytes` keep their startup values until the server is restarted.
+#[derive(Debug, Clone)]
+pub struct SharedConfig(Arc<RwLock<SealboxConfig>>);
+
+impl SharedConfig {
+    pub fn new(config: SealboxConfig) -> Self {
+        Self(Arc::new(RwLock::new(config)))
+    }
+
+    /// Current configuration
+    pub fn snapshot(&self) -> SealboxConfig {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Apply the reloadable settings of `new`, warning about any that need a restart
+    pub fn reload(&self, new: SealboxConfig) {
+        let mut current = self.0.write().unwrap_or_else(PoisonError::into_inner);
+
+        if new.store_path != current.store_path {
+            warn!("STORE_PATH changed; restart the server to apply it");
+        }
+        if new.listen_addr != current.listen_addr {
+            warn!("LISTEN_ADDR changed; restart the server to apply it");
+        }
+        if new.db_pool_size != current.db_pool_size {
+            warn!("SEALBOX_DB_POOL_SIZE changed; restart the server to apply it");
         }
+        if new.cleanup_interval_secs != current.cleanup_interval_secs {
+            warn!("SEALBOX_CLEANUP_INTERVAL_SECS changed; restart the server to apply it");
+        }
+        // Also bounds request bodies, which are limited when the routes are built
+        if new.max_secret_bytes != current.max_secret_bytes {
+            warn!("SEALBOX_MAX_SECRET_BYTES changed; restart the server to apply it");
+        }
+
+        current.auth_token = new.auth_token;
+        current.max_ttl_seconds = new.max_ttl_seconds;
+        current.metrics_token = new.metrics_token;
+        current.rate_limit_rps = new.rate_limit_rps;
+        current.rate_limit_burst = new.rate_limit_burst;
+        current.scoped_tokens = new.scoped_tokens;
+        current.compress_secrets = new.compress_secrets;
+        current.slow_query_ms = new.slow_query_ms;
+        current.auth_exempt_paths = new.auth_exempt_paths;
+        current.require_key_proof = new.require_key_proof;
+        current.totp_secret = new.totp_secret;
+        current.webhook_urls = new.webhook_urls;
+        current.webhook_secret = new.webhook_secret;
+        current.require_https = new.require_https;
+        info!(
+            "Sealbox configuration reloaded (max_ttl_seconds: {:?})",
+            current.max_ttl_seconds
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_applies_only_runtime_settings() {
+        let shared = SharedConfig::new(SealboxConfig::default());
+
+        shared.reload(SealboxConfig {
+            auth_token: "rotated-token".to_string(),
+            store_path: "/tmp/other.db".to_string(),
+            listen_addr: "0.0.0.0:9090".to_string(),
+            db_pool_size: 10,
+            max_ttl_seconds: Some(60),
+            metrics_token: Some("scrape-token".to_string()),
+            cleanup_interval_secs: 60,
+            rate_limit_rps: Some(5),
+            rate_limit_burst: Some(10),
+            scoped_tokens: Vec::new(),
+            compress_secrets: true,
+            slow_query_ms: Some(250),
+            auth_exempt_paths: vec!["/healthz/live".to_string()],
+            require_key_proof: true,
+            totp_secret: Some(b"12345678901234567890".to_vec()),
+            webhook_urls: vec!["https://hooks.example/sealbox".to_string()],
+            webhook_secret: Some("hook-secret".to_string()),
+            require_https: true,
+            max_secret_bytes: 64,
+        });
+
+        let config = shared.snapshot();
+        assert_eq!(config.auth_token, "rotated-token");
+        assert_eq!(config.max_ttl_seconds, Some(60));
+        assert!(config.compress_secrets);
+        assert_eq!(config.metrics_token.as_deref(), Some("scrape-token"));
+        assert_eq!(config.rate_limit_rps, Some(5));
+        assert_eq!(config.rate_limit_burst, Some(10));
+        assert_eq!(config.auth_exempt_paths, vec!["/healthz/live"]);
+        assert!(config.require_key_proof);
+        assert!(config.totp_secret.is_some());
+        assert_eq!(config.webhook_urls, vec!["https://hooks.example/sealbox"]);
+        assert_eq!(config.webhook_secret.as_deref(), Some("hook-secret"));
+        assert!(config.require_https);
+        assert_eq!(config.store_path, SealboxConfig::default().store_path);
+        assert_eq!(config.listen_addr, SealboxConfig::default().listen_addr);
+        assert_eq!(config.db_pool_size, DEFAULT_DB_POOL_SIZE);
+        assert_eq!(
+            config.cleanup_interval_secs,
+            SealboxConfig::default().cleanup_interval_secs
+        );
+        assert_eq!(config.max_secret_bytes, DEFAULT_MAX_SECRET_BYTES);
+    }
+
+    #[test]
+    fn test_parse_scoped_tokens() {
+        let tokens = parse_scoped_tokens("ci-token:read; deploy-token:read,write").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                ScopedToken {
+                    token: "ci-token".to_string(),
+                    scopes: vec![Scope::Read],
+                },
+                ScopedToken {
+                    token: "deploy-token".to_string(),
+                    scopes: vec![Scope::Read, Scope::Write],
+                },
+            ]
+        );
+
+        assert!(parse_scoped_tokens("no-scopes").is_err());
+        assert!(parse_scoped_tokens(":read").is_err());
+        assert!(parse_scoped_tokens("token:root").is_err());
+    }
+
+    #[test]
+    fn test_parse_exempt_paths() {
+        assert_eq!(
+            parse_exempt_paths(" /healthz/live, /v1/info ,").unwrap(),
+            vec!["/healthz/live", "/v1/info"]
+        );
+        assert!(parse_exempt_paths("").unwrap().is_empty());
+        assert!(parse_exempt_paths("metrics").is_err());
+    }
+
+    #[test]
+    fn test_parse_webhook_urls() {
+        assert_eq!(
+            parse_webhook_urls("https://a.example/hook, http://b:9000/ ,").unwrap(),
+            vec!["https://a.example/hook", "http://b:9000/"]
+        );
+        assert!(parse_webhook_urls("").unwrap().is_empty());
+        assert!(parse_webhook_urls("ftp://a.example/hook").is_err());
+    }
+
+    #[test]
+    fn test_resolve_listen_addr() {
+        assert_eq!(
+            resolve_listen_addr("127.0.0.1:8080").unwrap(),
+            vec!["127.0.0.1:8080".parse::<SocketAddr>().unwrap()]
+        );
+        assert_eq!(
+            resolve_listen_addr("[::1]:8080").unwrap(),
+            vec!["[::1]:8080".parse::<SocketAddr>().unwrap()]
+        );
+        let resolved = resolve_listen_addr("localhost:8080").unwrap();
+        assert!(
+            resolved
+                .iter()
+                .all(|addr| addr.ip().is_loopback() && addr.port() == 8080)
+        );
+
+        let err = resolve_listen_addr("::1:8080").unwrap_err();
+        assert!(err.contains("put it in brackets"), "{err}");
+        let err = resolve_listen_addr("localhost").unwrap_err();
+        assert!(err.contains("has no port"), "{err}");
+        let err = resolve_listen_addr("localhost:http").unwrap_err();
+        assert!(err.contains("not a valid port"), "{err}");
+        let err = resolve_listen_addr("[::1:8080").unwrap_err();
+        assert!(err.contains("not a valid IPv6 address"), "{err}");
+    }
+
+    #[test]
+    fn test_scopes_for() {
+        let config = SealboxConfig {
+            scoped_tokens: parse_scoped_tokens("ci-token:read").unwrap(),
+            ..SealboxConfig::default()
+        };
+        assert_eq!(config.scopes_for("test-token"), Some(ALL_SCOPES));
+        assert_eq!(config.scopes_for("ci-token"), Some(&[Scope::Read][..]));
+        assert_eq!(config.scopes_for("unknown"), None);
     }
 }
@@ -1,6 +1,6 @@
 use aes_gcm::{
     Aes256Gcm, Key, Nonce,
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
 };
 use rand::Rng;
 use thiserror::Error;
@@ -17,6 +17,15 @@ pub enum DataKeyCryptoError {
 
 pub type Result<T, E = DataKeyCryptoError> = std::result::Result<T, E>;
 
+/// Size of a data key in bytes
+pub const DATA_KEY_LEN: usize = 32;
+
+/// Size of the random nonce prefixed to every AES-256-GCM ciphertext
+pub(crate) const AES_GCM_NONCE_LEN: usize = 12;
+
+/// Size of the authentication tag appended to every AES-256-GCM ciphertext
+pub(crate) const AES_GCM_TAG_LEN: usize = 16;
+
 #[derive(Debug)]
 pub struct DataKey(Vec<u8>);
 
@@ -43,7 +52,7 @@ impl DataKey {
     /// ```
     pub fn new() -> DataKey {
         let mut rng = rand::thread_rng();
-        let mut data_key = vec![0u8; 32];
+        let mut data_key = vec![0u8; DATA_KEY_LEN];
         rng.fill(&mut data_key[..]);
         DataKey(data_key)
     }
@@ -71,7 +80,7 @@ impl DataKey {
     /// let data_key = DataKey::from_bytes(&key_bytes).unwrap();
     /// ```
     pub fn from_bytes(bytes: &[u8]) -> Result<DataKey> {
-        if bytes.len() != 32 {
+        if bytes.len() != DATA_KEY_LEN {
             return Err(DataKeyCryptoError::InvalidKeyLength(bytes.len()));
         }
         Ok(DataKey(bytes.to_vec()))
@@ -116,13 +125,25 @@ impl DataKey {
     /// - Uses randomly generated nonce for each encryption, ensuring different ciphertext for same data
     /// - Output format contains 12-byte nonce + ciphertext + 16-byte authentication tag
     pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt_with_aad(data, &[])
+    }
+
+    /// Encrypt data using AES-256-GCM, authenticating `aad` alongside it
+    ///
+    /// `aad` is not stored in the output; the same bytes must be passed to
+    /// [`DataKey::decrypt_with_aad`]. Encrypting with empty `aad` is the same as [`DataKey::encrypt`].
+    ///
+    /// # Errors
+    ///
+    /// * `DataKeyCryptoError::FailedToEncrypt` - When encryption operation fails
+    pub fn encrypt_with_aad(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
         // Generate a random nonce (12 bytes)
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
 
         // Encrypt the data
         let ciphertext = self
             .cipher()
-            .encrypt(&nonce, data)
+            .encrypt(&nonce, Payload { msg: data, aad })
             .map_err(DataKeyCryptoError::FailedToEncrypt)?;
 
         // Output: [nonce | ciphertext]
@@ -150,14 +171,34 @@ impl DataKey {
     /// - Verifies data integrity and authentication tag
     /// - Input data must contain valid nonce and authentication tag
     pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.decrypt_with_aad(data, &[])
+    }
+
+    /// Decrypt data produced by [`DataKey::encrypt_with_aad`] with the same `aad`
+    ///
+    /// # Errors
+    ///
+    /// * `DataKeyCryptoError::FailedToDecrypt` - Decryption failed, including when `aad` differs
+    ///   from the bytes the data was encrypted with
+    pub fn decrypt_with_aad(&self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        if data.len() < AES_GCM_NONCE_LEN + AES_GCM_TAG_LEN {
+            return Err(DataKeyCryptoError::FailedToDecrypt(aes_gcm::Error));
+        }
+
         // Split nonce and ciphertext
-        let (nonce_bytes, ciphertext) = data.split_at(12);
+        let (nonce_bytes, ciphertext) = data.split_at(AES_GCM_NONCE_LEN);
         let nonce = Nonce::from_slice(nonce_bytes);
 
         // Decrypt
         let plaintext = self
             .cipher()
-            .decrypt(nonce, ciphertext)
+            .decrypt(
+                nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
             .map_err(DataKeyCryptoError::FailedToDecrypt)?;
         Ok(plaintext)
     }
@@ -212,6 +253,25 @@ mod tests {
         assert_eq!(decrypted, plaintext);
     }
 
+    #[test]
+    fn test_aad_must_match() {
+        let key = DataKey::new();
+        let ciphertext = key
+            .encrypt_with_aad(b"value", b"db-password@1")
+            .expect("Encryption should succeed");
+
+        assert_eq!(
+            key.decrypt_with_aad(&ciphertext, b"db-password@1").unwrap(),
+            b"value"
+        );
+        assert!(key.decrypt_with_aad(&ciphertext, b"db-password@2").is_err());
+        assert!(key.decrypt(&ciphertext).is_err());
+
+        // No AAD is the same as empty AAD, so data encrypted without it still decrypts
+        let legacy = key.encrypt(b"value").expect("Encryption should succeed");
+        assert_eq!(key.decrypt_with_aad(&legacy, &[]).unwrap(), b"value");
+    }
+
     #[test]
     fn test_encrypt_different_nonces() {
         let key = DataKey::new();
@@ -266,6 +326,14 @@ mod tests {
             DataKeyCryptoError::FailedToDecrypt(_) => {} // Expected
             _ => panic!("Expected FailedToDecrypt error"),
         }
+
+        // Shorter than a nonce and tag
+        for len in [0, 11, AES_GCM_NONCE_LEN + AES_GCM_TAG_LEN - 1] {
+            assert!(matches!(
+                key.decrypt(&vec![0u8; len]),
+                Err(DataKeyCryptoError::FailedToDecrypt(_))
+            ));
+        }
     }
 
     #[test]
@@ -0,0 +1,607 @@
+use thiserror::Error;
+
+use crate::crypto::{
+    data_key::{AES_GCM_NONCE_LEN, AES_GCM_TAG_LEN, DATA_KEY_LEN, DataKey, DataKeyCryptoError},
+    master_key::{KeyAlgorithm, MasterKeyCryptoError, PrivateMasterKey, PublicMasterKey},
+};
+
+#[derive(Error, Debug)]
+pub enum EnvelopeError {
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(i32),
+    #[error("Failed to unwrap data key: {0}")]
+    MasterKey(#[from] MasterKeyCryptoError),
+    #[error("Failed to decrypt data: {0}")]
+    DataKey(#[from] DataKeyCryptoError),
+    #[error("Failed to compress or decompress data: {0}")]
+    Compression(std::io::Error),
+    #[error("Malformed envelope: {0}")]
+    Malformed(String),
+}
+
+pub type Result<T, E = EnvelopeError> = std::result::Result<T, E>;
+
+/// Symmetric cipher used to encrypt the secret payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cipher {
+    Aes256Gcm,
+}
+
+/// Scheme used to wrap the data key with the master key.
+///
+/// It follows from the master key's algorithm, not from the envelope version, so every
+/// envelope version works with every master key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWrapPadding {
+    RsaOaepSha256,
+    /// Ephemeral X25519 key agreement, HKDF-SHA256 and AES-256-GCM
+    X25519HkdfAesGcm,
+}
+
+impl From<KeyAlgorithm> for KeyWrapPadding {
+    fn from(algorithm: KeyAlgorithm) -> Self {
+        match algorithm {
+            KeyAlgorithm::Rsa => KeyWrapPadding::RsaOaepSha256,
+            KeyAlgorithm::X25519 => KeyWrapPadding::X25519HkdfAesGcm,
+        }
+    }
+}
+
+/// Compression applied to the plaintext before encryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Zstd,
+}
+
+/// zstd level used for compressed envelopes; the library default balances speed and ratio
+const ZSTD_LEVEL: i32 = 0;
+
+/// Prefix of the associated data binding a payload to its secret, so the bytes cannot
+/// collide with associated data used for anything else
+const AAD_DOMAIN: &[u8] = b"sealbox/secret\0";
+
+/// The secret key and version a payload belongs to, authenticated as AES-GCM associated
+/// data by envelope versions with `aad` set.
+///
+/// Opening a payload under a different binding than it was sealed with fails, so a
+/// ciphertext moved to another key in the database no longer decrypts. Rollback copies are
+/// opened with the version recorded in their unauthenticated `bound_version` column, so
+/// the version binding does not stop an older payload of the same key from being replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecretBinding<'a> {
+    pub key: &'a str,
+    pub version: i32,
+}
+
+impl SecretBinding<'_> {
+    /// Associated data for this binding: the domain prefix, the length-prefixed key and
+    /// the version, both big-endian
+    fn aad(&self) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(AAD_DOMAIN.len() + 8 + self.key.len());
+        aad.extend_from_slice(AAD_DOMAIN);
+        aad.extend_from_slice(&(self.key.len() as u32).to_be_bytes());
+        aad.extend_from_slice(self.key.as_bytes());
+        aad.extend_from_slice(&self.version.to_be_bytes());
+        aad
+    }
+}
+
+/// Parameters describing how a stored secret was encrypted; the data key wrapping is
+/// described by the master key (see [`KeyWrapPadding`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeParams {
+    pub cipher: Cipher,
+    pub aad: bool,
+    pub compression: Compression,
+}
+
+/// Version of the envelope format stored alongside each secret version.
+///
+/// Every row in the `secrets` table records the envelope version it was written with,
+/// so readers can pick the matching decoding parameters when the format evolves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeVersion {
+    /// Data key wrapped with the master key, AES-256-GCM payload, no AAD, no compression
+    V1,
+    /// As `V1`, but the plaintext is zstd-compressed before encryption
+    V2,
+    /// As `V1`, with the secret key and version authenticated as AAD
+    V3,
+    /// As `V2`, with the secret key and version authenticated as AAD
+    V4,
+}
+
+/// Envelope version used for newly written secrets
+pub const CURRENT_ENVELOPE_VERSION: EnvelopeVersion = EnvelopeVersion::V3;
+
+/// Envelope version used for newly written secrets that opt in to compression
+pub const COMPRESSED_ENVELOPE_VERSION: EnvelopeVersion = EnvelopeVersion::V4;
+
+impl EnvelopeVersion {
+    /// Numeric representation stored in the database
+    pub fn as_i32(self) -> i32 {
+        match self {
+            EnvelopeVersion::V1 => 1,
+            EnvelopeVersion::V2 => 2,
+            EnvelopeVersion::V3 => 3,
+            EnvelopeVersion::V4 => 4,
+        }
+    }
+
+    /// Envelope version for a new secret, depending on whether it should be compressed
+    pub fn for_new_secret(compress: bool) -> Self {
+        if compress {
+            COMPRESSED_ENVELOPE_VERSION
+        } else {
+            CURRENT_ENVELOPE_VERSION
+        }
+    }
+
+    /// Envelope version for a secret the client encrypts itself.
+    ///
+    /// The client cannot know which version number the server will assign, so it cannot
+    /// bind the payload to it and uses the envelope versions without AAD.
+    pub fn for_client_encrypted(compress: bool) -> Self {
+        if compress {
+            EnvelopeVersion::V2
+        } else {
+            EnvelopeVersion::V1
+        }
+    }
+
+    /// Decoding parameters for this envelope version
+    pub fn params(self) -> EnvelopeParams {
+        match self {
+            EnvelopeVersion::V1 => EnvelopeParams {
+                cipher: Cipher::Aes256Gcm,
+                aad: false,
+                compression: Compression::None,
+            },
+            EnvelopeVersion::V2 => EnvelopeParams {
+                compression: Compression::Zstd,
+                ..EnvelopeVersion::V1.params()
+            },
+            EnvelopeVersion::V3 => EnvelopeParams {
+                aad: true,
+                ..EnvelopeVersion::V1.params()
+            },
+            EnvelopeVersion::V4 => EnvelopeParams {
+                aad: true,
+                ..EnvelopeVersion::V2.params()
+            },
+        }
+    }
+
+    /// Prepare plaintext for encryption, compressing it if this envelope version calls for it
+    pub fn encode_plaintext(self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        match self.params().compression {
+            Compression::None => Ok(plaintext.to_vec()),
+            Compression::Zstd => {
+                zstd::encode_all(plaintext, ZSTD_LEVEL).map_err(EnvelopeError::Compression)
+            }
+        }
+    }
+
+    /// Encrypt a secret with this envelope version, the inverse of [`EnvelopeVersion::open`]
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key` - The public master key that wraps the freshly generated data key
+    /// * `plaintext` - The secret value
+    /// * `binding` - The key and version the secret is stored under, ignored unless this
+    ///   envelope version authenticates AAD
+    ///
+    /// # Returns
+    ///
+    /// Returns the encrypted payload and the wrapped data key, in that order
+    pub fn seal(
+        self,
+        public_key: &PublicMasterKey,
+        plaintext: &[u8],
+        binding: SecretBinding,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let params = self.params();
+        let encoded = self.encode_plaintext(plaintext)?;
+        let aad = if params.aad {
+            binding.aad()
+        } else {
+            Vec::new()
+        };
+
+        let data_key = DataKey::new();
+        let encrypted_data = match params.cipher {
+            Cipher::Aes256Gcm => data_key.encrypt_with_aad(&encoded, &aad)?,
+        };
+        let encrypted_data_key = public_key.encrypt(data_key.as_bytes())?;
+
+        Ok((encrypted_data, encrypted_data_key))
+    }
+
+    /// Decrypt a stored secret written with this envelope version
+    ///
+    /// # Arguments
+    ///
+    /// * `private_key` - The private master key matching the public key used to wrap the data key
+    /// * `encrypted_data_key` - The wrapped data key as stored on the server
+    /// * `encrypted_data` - The encrypted payload as stored on the server
+    /// * `binding` - The key and version the payload was sealed for, ignored unless this
+    ///   envelope version authenticates AAD
+    ///
+    /// # Returns
+    ///
+    /// Returns the plaintext bytes of the secret
+    ///
+    /// # Errors
+    ///
+    /// * `EnvelopeError::DataKey` - When decryption fails, including when the payload was
+    ///   sealed for a different key or version than `binding`
+    pub fn open(
+        self,
+        private_key: &PrivateMasterKey,
+        encrypted_data_key: &[u8],
+        encrypted_data: &[u8],
+        binding: SecretBinding,
+    ) -> Result<Vec<u8>> {
+        let params = self.params();
+        let aad = if params.aad {
+            binding.aad()
+        } else {
+            Vec::new()
+        };
+
+        let data_key = private_key.decrypt(encrypted_data_key)?;
+
+        let decrypted = match params.cipher {
+            Cipher::Aes256Gcm => {
+                DataKey::from_bytes(&data_key)?.decrypt_with_aad(encrypted_data, &aad)?
+            }
+        };
+
+        match params.compression {
+            Compression::None => Ok(decrypted),
+            Compression::Zstd => {
+                zstd::decode_all(decrypted.as_slice()).map_err(EnvelopeError::Compression)
+            }
+        }
+    }
+
+    /// Re-encrypt a stored payload for another binding, keeping its wrapped data key
+    ///
+    /// # Arguments
+    ///
+    /// * `private_key` - The private master key that unwraps the data key
+    /// * `encrypted_data_key` - The wrapped data key as stored on the server
+    /// * `encrypted_data` - The encrypted payload as stored on the server
+    /// * `from` - The key and version the payload was sealed for
+    /// * `to` - The key and version the payload should open under from now on
+    ///
+    /// # Returns
+    ///
+    /// Returns the new encrypted payload, or the stored one unchanged when this envelope
+    /// version does not authenticate AAD
+    ///
+    /// # Errors
+    ///
+    /// * `EnvelopeError::MasterKey` - When `private_key` cannot unwrap the data key
+    /// * `EnvelopeError::DataKey` - When the payload does not open under `from`
+    pub fn rebind(
+        self,
+        private_key: &PrivateMasterKey,
+        encrypted_data_key: &[u8],
+        encrypted_data: &[u8],
+        from: SecretBinding,
+        to: SecretBinding,
+    ) -> Result<Vec<u8>> {
+        let params = self.params();
+        if !params.aad {
+            return Ok(encrypted_data.to_vec());
+        }
+
+        let data_key = DataKey::from_bytes(&private_key.decrypt(encrypted_data_key)?)?;
+        // Still compressed if the envelope compresses; only the associated data changes
+        let encoded = match params.cipher {
+            Cipher::Aes256Gcm => data_key.decrypt_with_aad(encrypted_data, &from.aad())?,
+        };
+        let rebound = match params.cipher {
+            Cipher::Aes256Gcm => data_key.encrypt_with_aad(&encoded, &to.aad())?,
+        };
+        Ok(rebound)
+    }
+
+    /// Check that a stored secret written with this envelope version is well formed, without
+    /// decrypting it
+    ///
+    /// Only sizes can be checked: whether the authentication tags verify is known only to
+    /// the holder of the private key.
+    ///
+    /// # Errors
+    ///
+    /// * `EnvelopeError::Malformed` - When the wrapped data key does not have the size
+    ///   `public_key` produces, or the payload is too short to hold a nonce and tag
+    pub fn check_structure(
+        self,
+        public_key: &PublicMasterKey,
+        encrypted_data_key: &[u8],
+        encrypted_data: &[u8],
+    ) -> Result<()> {
+        let params = self.params();
+
+        let wrapped_len = public_key.ciphertext_len(DATA_KEY_LEN);
+        if encrypted_data_key.len() != wrapped_len {
+            return Err(EnvelopeError::Malformed(format!(
+                "wrapped data key is {} bytes, expected {wrapped_len}",
+                encrypted_data_key.len()
+            )));
+        }
+
+        let overhead = match params.cipher {
+            Cipher::Aes256Gcm => AES_GCM_NONCE_LEN + AES_GCM_TAG_LEN,
+        };
+        if encrypted_data.len() < overhead {
+            return Err(EnvelopeError::Malformed(format!(
+                "encrypted data is {} bytes, too short for its {overhead}-byte nonce and tag",
+                encrypted_data.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<i32> for EnvelopeVersion {
+    type Error = EnvelopeError;
+
+    fn try_from(value: i32) -> Result<Self> {
+        match value {
+            1 => Ok(EnvelopeVersion::V1),
+            2 => Ok(EnvelopeVersion::V2),
+            3 => Ok(EnvelopeVersion::V3),
+            4 => Ok(EnvelopeVersion::V4),
+            other => Err(EnvelopeError::UnsupportedVersion(other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        crypto::master_key::{KeyAlgorithm, generate_key_pair, generate_key_pair_with_algorithm},
+        repo::{MasterKey, Secret},
+    };
+
+    #[test]
+    fn test_v1_row_decodes_with_legacy_params() {
+        let (private_pem, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let master_key = MasterKey::new(public_pem).expect("Should create master key");
+        let secret = Secret::new(
+            "legacy-key",
+            "legacy-data",
+            master_key,
+            1,
+            None,
+            EnvelopeVersion::V1,
+        )
+        .expect("Should create secret");
+
+        // Rows written before the column existed default to version 1
+        let version = EnvelopeVersion::try_from(1).expect("Version 1 should be supported");
+        assert_eq!(version, EnvelopeVersion::V1);
+        assert_eq!(
+            version.params(),
+            EnvelopeParams {
+                cipher: Cipher::Aes256Gcm,
+                aad: false,
+                compression: Compression::None,
+            }
+        );
+
+        let private_key: PrivateMasterKey = private_pem.parse().expect("Should parse private key");
+        let plaintext = version
+            .open(
+                &private_key,
+                &secret.encrypted_data_key,
+                &secret.encrypted_data,
+                SecretBinding {
+                    key: "legacy-key",
+                    version: 1,
+                },
+            )
+            .expect("Should decrypt v1 envelope");
+        assert_eq!(plaintext, b"legacy-data");
+    }
+
+    #[test]
+    fn test_v2_round_trip_compresses() {
+        let (private_pem, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let master_key = MasterKey::new(public_pem).expect("Should create master key");
+        let data = r#"{"host": "db.internal", "user": "app", "password": "hunter2"}"#.repeat(50);
+        let plain = Secret::new(
+            "verbose",
+            &data,
+            master_key.clone(),
+            1,
+            None,
+            EnvelopeVersion::V1,
+        )
+        .expect("Should create secret");
+        let compressed = Secret::new("verbose", &data, master_key, 1, None, EnvelopeVersion::V2)
+            .expect("Should create compressed secret");
+        assert_eq!(compressed.envelope_version, 2);
+        assert!(compressed.encrypted_data.len() * 5 < plain.encrypted_data.len());
+
+        let version = EnvelopeVersion::try_from(compressed.envelope_version)
+            .expect("Version 2 should be supported");
+        let private_key: PrivateMasterKey = private_pem.parse().expect("Should parse private key");
+        let plaintext = version
+            .open(
+                &private_key,
+                &compressed.encrypted_data_key,
+                &compressed.encrypted_data,
+                SecretBinding {
+                    key: "verbose",
+                    version: 1,
+                },
+            )
+            .expect("Should decrypt v2 envelope");
+        assert_eq!(plaintext, data.as_bytes());
+    }
+
+    #[test]
+    fn test_rebind_moves_payload_to_new_key() {
+        let (private_pem, public_pem) = generate_key_pair().unwrap();
+        let public_key: PublicMasterKey = public_pem.parse().unwrap();
+        let private_key: PrivateMasterKey = private_pem.parse().unwrap();
+        let old = SecretBinding {
+            key: "old",
+            version: 2,
+        };
+        let new = SecretBinding { key: "new", ..old };
+
+        for envelope in [EnvelopeVersion::V3, EnvelopeVersion::V4] {
+            let (encrypted_data, encrypted_data_key) =
+                envelope.seal(&public_key, b"value", old).unwrap();
+            let rebound = envelope
+                .rebind(&private_key, &encrypted_data_key, &encrypted_data, old, new)
+                .unwrap();
+
+            let open =
+                |binding| envelope.open(&private_key, &encrypted_data_key, &rebound, binding);
+            assert_eq!(open(new).unwrap(), b"value");
+            assert!(open(old).is_err());
+            // The payload must have been sealed for `from`
+            assert!(
+                envelope
+                    .rebind(&private_key, &encrypted_data_key, &rebound, old, new)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_structure_flags_truncated_blobs() {
+        for algorithm in KeyAlgorithm::ALL {
+            let (_, public_pem) = generate_key_pair_with_algorithm(algorithm).unwrap();
+            let public_key: PublicMasterKey = public_pem.parse().unwrap();
+            let binding = SecretBinding {
+                key: "key",
+                version: 1,
+            };
+            let (encrypted_data, encrypted_data_key) = EnvelopeVersion::V1
+                .seal(&public_key, b"value", binding)
+                .unwrap();
+
+            let check = |data_key: &[u8], data: &[u8]| {
+                EnvelopeVersion::V1.check_structure(&public_key, data_key, data)
+            };
+            assert!(check(&encrypted_data_key, &encrypted_data).is_ok());
+            assert!(matches!(
+                check(&encrypted_data_key[1..], &encrypted_data),
+                Err(EnvelopeError::Malformed(_))
+            ));
+            assert!(matches!(
+                check(&encrypted_data_key, &encrypted_data[..20]),
+                Err(EnvelopeError::Malformed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_aad_binds_payload_to_key_and_version() {
+        let (private_pem, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let public_key: PublicMasterKey = public_pem.parse().unwrap();
+        let private_key: PrivateMasterKey = private_pem.parse().unwrap();
+        let binding = SecretBinding {
+            key: "db-password",
+            version: 2,
+        };
+        let moved = [
+            SecretBinding {
+                key: "api-token",
+                version: 2,
+            },
+            SecretBinding {
+                key: "db-password",
+                version: 3,
+            },
+        ];
+
+        for version in [EnvelopeVersion::V3, EnvelopeVersion::V4] {
+            assert!(version.params().aad);
+            let (data, data_key) = version.seal(&public_key, b"hunter2", binding).unwrap();
+            assert_eq!(
+                version
+                    .open(&private_key, &data_key, &data, binding)
+                    .unwrap(),
+                b"hunter2"
+            );
+            for other in moved {
+                assert!(matches!(
+                    version.open(&private_key, &data_key, &data, other),
+                    Err(EnvelopeError::DataKey(_))
+                ));
+            }
+            // Relabelling the row as an envelope without AAD does not help either
+            let downgraded = EnvelopeVersion::try_from(version.as_i32() - 2).unwrap();
+            assert!(
+                downgraded
+                    .open(&private_key, &data_key, &data, binding)
+                    .is_err()
+            );
+        }
+
+        // Envelopes without AAD ignore the binding, so existing rows keep decrypting
+        let (data, data_key) = EnvelopeVersion::V1
+            .seal(&public_key, b"hunter2", binding)
+            .unwrap();
+        assert_eq!(
+            EnvelopeVersion::V1
+                .open(&private_key, &data_key, &data, moved[0])
+                .unwrap(),
+            b"hunter2"
+        );
+    }
+
+    #[test]
+    fn test_key_wrap_padding_follows_master_key_algorithm() {
+        let (_, rsa_public_pem) = generate_key_pair().unwrap();
+        let rsa_row = MasterKey::new(rsa_public_pem).unwrap();
+        assert_eq!(rsa_row.key_wrap_padding(), KeyWrapPadding::RsaOaepSha256);
+
+        let (private_pem, public_pem) =
+            generate_key_pair_with_algorithm(KeyAlgorithm::X25519).unwrap();
+        let x25519_row = MasterKey::new(public_pem).unwrap();
+        assert_eq!(x25519_row.algorithm, KeyAlgorithm::X25519);
+        assert_eq!(
+            x25519_row.key_wrap_padding(),
+            KeyWrapPadding::X25519HkdfAesGcm
+        );
+
+        // Every envelope version works with the X25519 wrap
+        let private_key: PrivateMasterKey = private_pem.parse().unwrap();
+        for version in [1, 2, 3, 4].map(|v| EnvelopeVersion::try_from(v).unwrap()) {
+            let secret =
+                Secret::new("db", "hunter2", x25519_row.clone(), 1, None, version).unwrap();
+            let binding = SecretBinding {
+                key: "db",
+                version: 1,
+            };
+            let plaintext = version
+                .open(
+                    &private_key,
+                    &secret.encrypted_data_key,
+                    &secret.encrypted_data,
+                    binding,
+                )
+                .unwrap();
+            assert_eq!(plaintext, b"hunter2");
+        }
+    }
+
+    #[test]
+    fn test_unknown_version_rejected() {
+        let result = EnvelopeVersion::try_from(99);
+        assert!(matches!(result, Err(EnvelopeError::UnsupportedVersion(99))));
+    }
+}
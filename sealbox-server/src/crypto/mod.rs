@@ -1,2 +1,5 @@
 pub mod data_key;
+pub mod envelope;
+pub mod key_file;
 pub mod master_key;
+pub mod totp;
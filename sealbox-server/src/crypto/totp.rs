@@ -0,0 +1,142 @@
+use hmac::{Hmac, Mac, digest::KeyInit};
+use sha1::Sha1;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TotpError {
+    #[error("TOTP secret must be base32 (A-Z, 2-7)")]
+    InvalidSecret,
+    #[error("TOTP secret must be at least {MIN_SECRET_LEN} bytes")]
+    SecretTooShort,
+}
+
+/// Seconds each code is valid for
+pub const TIME_STEP: u64 = 30;
+
+/// Digits in a code
+const DIGITS: u32 = 6;
+
+/// RFC 4226 recommends shared secrets of at least 128 bits
+const MIN_SECRET_LEN: usize = 16;
+
+/// Decode a base32 (RFC 4648) TOTP secret, as shown by authenticator apps
+///
+/// Case, spaces and `=` padding are ignored.
+///
+/// # Errors
+///
+/// * `TotpError::InvalidSecret` - When `secret` contains characters outside the base32 alphabet
+/// * `TotpError::SecretTooShort` - When the decoded secret is shorter than 128 bits
+pub fn decode_secret(secret: &str) -> Result<Vec<u8>, TotpError> {
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in secret.chars().filter(|c| !c.is_whitespace() && *c != '=') {
+        let value = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return Err(TotpError::InvalidSecret),
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    if bytes.len() < MIN_SECRET_LEN {
+        return Err(TotpError::SecretTooShort);
+    }
+    Ok(bytes)
+}
+
+/// RFC 6238 code (HMAC-SHA1, six digits) for the time step containing `unix_time`
+pub fn code_at(secret: &[u8], unix_time: u64) -> String {
+    let counter = unix_time / TIME_STEP;
+    let digest = <Hmac<Sha1> as KeyInit>::new_from_slice(secret)
+        .expect("HMAC accepts keys of any length")
+        .chain_update(counter.to_be_bytes())
+        .finalize()
+        .into_bytes();
+
+    // Dynamic truncation (RFC 4226 section 5.3)
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let value =
+        u32::from_be_bytes(digest[offset..offset + 4].try_into().expect("4 bytes")) & 0x7fff_ffff;
+    format!(
+        "{:0width$}",
+        value % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Whether `code` is valid at `unix_time`, allowing one time step of clock drift either way
+pub fn verify(secret: &[u8], code: &str, unix_time: u64) -> bool {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    [
+        unix_time.saturating_sub(TIME_STEP),
+        unix_time,
+        unix_time + TIME_STEP,
+    ]
+    .iter()
+    .any(|&time| constant_time_eq(code_at(secret, time).as_bytes(), code.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The RFC 6238 appendix B SHA-1 secret, "12345678901234567890"
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn test_code_matches_rfc_6238_vectors() {
+        // Appendix B lists eight-digit codes; six-digit codes are their last six digits
+        assert_eq!(code_at(RFC_SECRET, 59), "287082");
+        assert_eq!(code_at(RFC_SECRET, 1_111_111_109), "081804");
+        assert_eq!(code_at(RFC_SECRET, 1_234_567_890), "005924");
+        assert_eq!(code_at(RFC_SECRET, 2_000_000_000), "279037");
+    }
+
+    #[test]
+    fn test_verify_allows_one_step_of_drift() {
+        let now = 1_234_567_890;
+        let code = code_at(RFC_SECRET, now);
+        assert!(verify(RFC_SECRET, &code, now));
+        assert!(verify(RFC_SECRET, &code, now + TIME_STEP));
+        assert!(verify(RFC_SECRET, &code, now - TIME_STEP));
+        assert!(!verify(RFC_SECRET, &code, now + 2 * TIME_STEP));
+        assert!(!verify(RFC_SECRET, &code, now - 2 * TIME_STEP));
+        assert!(!verify(RFC_SECRET, "12345", now));
+        assert!(!verify(RFC_SECRET, "abcdef", now));
+    }
+
+    #[test]
+    fn test_decode_secret() {
+        // Base32 of the RFC secret
+        assert_eq!(
+            decode_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap(),
+            RFC_SECRET
+        );
+        assert_eq!(
+            decode_secret("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap(),
+            RFC_SECRET
+        );
+        assert!(matches!(
+            decode_secret("GEZDGNBVGY3TQOJ1"),
+            Err(TotpError::InvalidSecret)
+        ));
+        assert!(matches!(
+            decode_secret("GEZDGNBV"),
+            Err(TotpError::SecretTooShort)
+        ));
+    }
+}
@@ -1,12 +1,15 @@
 use axum::{
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::crypto::{data_key::DataKeyCryptoError, master_key::MasterKeyCryptoError};
+use crate::crypto::{
+    data_key::DataKeyCryptoError, envelope::EnvelopeError, master_key::MasterKeyCryptoError,
+};
 
 pub type Result<T, E = SealboxError> = std::result::Result<T, E>;
 
@@ -33,9 +36,33 @@ pub enum SealboxError {
     #[error("Response build failed: {0}")]
     ResponseBuildFailed(String),
 
+    #[error("Job not found: {0}")]
+    JobNotFound(Uuid),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Validation failed: {}", .0.join("; "))]
+    ValidationFailed(Vec<String>),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("A valid TOTP code is required in the X-TOTP header")]
+    TotpRequired,
+
+    #[error("Too many requests, retry after {0}s")]
+    RateLimited(u64),
+
+    #[error("Secrets can only be written over HTTPS")]
+    HttpsRequired,
+
     #[error("Invalid API version")]
     InvalidApiVersion,
 
@@ -43,36 +70,96 @@ pub enum SealboxError {
     Unknown,
 }
 
-fn errorfmt(error: &SealboxError) -> String {
-    format!("[SealboxError] {error}")
+impl SealboxError {
+    /// Stable machine-readable code, safe for clients to match on
+    pub fn code(&self) -> &'static str {
+        match self {
+            SealboxError::SecretNotFound(_) => "secret_not_found",
+            SealboxError::MissingValidMasterKey => "missing_valid_master_key",
+            SealboxError::MasterKeyNotFound(_) => "master_key_not_found",
+            SealboxError::MasterKeyMismatch(_, _, _) => "master_key_mismatch",
+            SealboxError::CryptoError(_) => "crypto_error",
+            SealboxError::DatabaseError(_) => "database_error",
+            SealboxError::ResponseBuildFailed(_) => "response_build_failed",
+            SealboxError::JobNotFound(_) => "job_not_found",
+            SealboxError::InvalidInput(_) => "invalid_input",
+            SealboxError::ValidationFailed(_) => "validation_failed",
+            SealboxError::Conflict(_) => "conflict",
+            SealboxError::Unauthorized => "unauthorized",
+            SealboxError::Forbidden(_) => "forbidden",
+            SealboxError::TotpRequired => "totp_required",
+            SealboxError::RateLimited(_) => "rate_limited",
+            SealboxError::HttpsRequired => "https_required",
+            SealboxError::InvalidApiVersion => "invalid_api_version",
+            SealboxError::Unknown => "unknown",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            SealboxError::SecretNotFound(_) => StatusCode::NOT_FOUND,
+            SealboxError::MissingValidMasterKey => StatusCode::PRECONDITION_REQUIRED,
+            SealboxError::MasterKeyNotFound(_) => StatusCode::NOT_FOUND,
+            SealboxError::MasterKeyMismatch(_, _, _) => StatusCode::INTERNAL_SERVER_ERROR,
+            SealboxError::CryptoError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            SealboxError::DatabaseError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            SealboxError::ResponseBuildFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            SealboxError::JobNotFound(_) => StatusCode::NOT_FOUND,
+            SealboxError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+            SealboxError::ValidationFailed(_) => StatusCode::BAD_REQUEST,
+            SealboxError::Conflict(_) => StatusCode::CONFLICT,
+            SealboxError::Unauthorized => StatusCode::UNAUTHORIZED,
+            SealboxError::Forbidden(_) => StatusCode::FORBIDDEN,
+            SealboxError::TotpRequired => StatusCode::FORBIDDEN,
+            SealboxError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            SealboxError::HttpsRequired => StatusCode::UPGRADE_REQUIRED,
+            SealboxError::InvalidApiVersion => StatusCode::NOT_FOUND,
+            SealboxError::Unknown => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// The `error` object of every error response.
+///
+/// It is also stored in the response extensions, so the request id middleware can
+/// fill in `request_id`, which the error itself does not know.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ErrorBody {
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+    /// Every validation problem, so clients can fix them all in one round trip
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) errors: Option<Vec<String>>,
+    pub(crate) request_id: Option<String>,
+}
+
+impl IntoResponse for ErrorBody {
+    fn into_response(self) -> Response {
+        let mut response = axum::Json(json!({ "error": &self })).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
 }
 
 impl IntoResponse for SealboxError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            SealboxError::SecretNotFound(_) => (StatusCode::NOT_FOUND, errorfmt(&self)),
-            SealboxError::MissingValidMasterKey => {
-                (StatusCode::PRECONDITION_REQUIRED, errorfmt(&self))
-            }
-            SealboxError::MasterKeyNotFound(_) => (StatusCode::NOT_FOUND, errorfmt(&self)),
-            SealboxError::MasterKeyMismatch(_, _, _) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, errorfmt(&self))
-            }
-            SealboxError::CryptoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, errorfmt(&self)),
-            SealboxError::DatabaseError(_) => (StatusCode::INTERNAL_SERVER_ERROR, errorfmt(&self)),
-            SealboxError::ResponseBuildFailed(_) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, errorfmt(&self))
-            }
-            SealboxError::Unauthorized => (StatusCode::UNAUTHORIZED, errorfmt(&self)),
-            SealboxError::InvalidApiVersion => (StatusCode::NOT_FOUND, errorfmt(&self)),
-            SealboxError::Unknown => (StatusCode::INTERNAL_SERVER_ERROR, errorfmt(&self)),
+        let body = ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            errors: match &self {
+                SealboxError::ValidationFailed(errors) => Some(errors.clone()),
+                _ => None,
+            },
+            request_id: None,
         };
 
-        let body = axum::Json(json!({
-            "error": message,
-        }));
-
-        (status, body).into_response()
+        let mut response = (self.status(), body).into_response();
+        if let SealboxError::RateLimited(retry_after) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
     }
 }
 
@@ -88,10 +175,14 @@ impl From<DataKeyCryptoError> for SealboxError {
     }
 }
 
-impl From<std::sync::PoisonError<std::sync::MutexGuard<'_, rusqlite::Connection>>>
-    for SealboxError
-{
-    fn from(err: std::sync::PoisonError<std::sync::MutexGuard<'_, rusqlite::Connection>>) -> Self {
+impl From<EnvelopeError> for SealboxError {
+    fn from(err: EnvelopeError) -> Self {
+        SealboxError::CryptoError(err.to_string())
+    }
+}
+
+impl From<r2d2::Error> for SealboxError {
+    fn from(err: r2d2::Error) -> Self {
         SealboxError::DatabaseError(err.to_string())
     }
 }
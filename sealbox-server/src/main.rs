@@ -1,9 +1,45 @@
-use sealbox_server::{config::SealboxConfig, create_app, error::Result};
+use clap::Parser;
+use sealbox_server::{
+    config::{ConfigOverrides, SealboxConfig, SharedConfig, resolve_listen_addr},
+    create_app,
+    error::Result,
+};
 use tracing::{error, info};
 use tracing_subscriber::{self, EnvFilter};
 
+#[derive(Parser)]
+#[command(name = "sealbox-server")]
+#[command(about = "Sealbox: A Simple Secret Storage Service")]
+struct Args {
+    /// Reload runtime settings from the environment and .env on SIGHUP; see
+    /// `SharedConfig::reload` for which settings apply without a restart
+    #[arg(long)]
+    watch: bool,
+    /// SQLite database file, overriding STORE_PATH
+    #[arg(long, value_name = "PATH")]
+    db_path: Option<String>,
+    /// Address and port to listen on, overriding LISTEN_ADDR
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<String>,
+    /// Most SQLite connections open at once, overriding SEALBOX_DB_POOL_SIZE
+    #[arg(long, value_name = "N")]
+    pool_size: Option<u32>,
+}
+
+impl Args {
+    fn overrides(&self) -> ConfigOverrides {
+        ConfigOverrides {
+            store_path: self.db_path.clone(),
+            listen_addr: self.listen.clone(),
+            db_pool_size: self.pool_size,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse();
+
     // Load environment variables from .env file if present
     dotenvy::dotenv().ok();
 
@@ -24,8 +60,9 @@ async fn main() -> Result<()> {
 
     info!("Sealbox Server starting up...");
 
-    // Load configuration from environment variables
-    let config = match SealboxConfig::from_env() {
+    // Load configuration from environment variables, with command-line flags taking precedence
+    let overrides = args.overrides();
+    let config = match SealboxConfig::from_env_with(&overrides) {
         Ok(cfg) => cfg,
         Err(e) => {
             error!("Failed to load configuration: {}", e);
@@ -34,20 +71,65 @@ async fn main() -> Result<()> {
     };
 
     // Build application routes (all routes are managed in api.rs)
-    let app = create_app(&config)?;
+    let shared_config = SharedConfig::new(config.clone());
+    let app = create_app(&shared_config)?;
 
-    // Listening address from configuration
+    if args.watch {
+        watch_for_reload(shared_config, overrides);
+    }
+
+    // Listening address from configuration, checked before binding so typos get a clear error
     let addr = &config.listen_addr;
-    info!("Listening on {}", addr);
-    let listener = tokio::net::TcpListener::bind(addr)
+    let socket_addrs = resolve_listen_addr(addr).unwrap_or_else(|e| {
+        error!("Invalid listen address: {}", e);
+        std::process::exit(1);
+    });
+    let listener = tokio::net::TcpListener::bind(socket_addrs.as_slice())
         .await
         .unwrap_or_else(|e| {
             error!("Failed to bind address {}: {}", addr, e);
             std::process::exit(1);
         });
+    match listener.local_addr() {
+        Ok(local_addr) => info!("Listening on {} ({})", addr, local_addr),
+        Err(_) => info!("Listening on {}", addr),
+    }
+    // Peer addresses let the rate limiter tell unauthenticated callers apart
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
     if let Err(e) = axum::serve(listener, app).await {
         error!("Server crashed: {}", e);
     }
 
     Ok(())
 }
+
+/// Reload configuration whenever the process receives SIGHUP
+#[cfg(unix)]
+fn watch_for_reload(shared_config: SharedConfig, overrides: ConfigOverrides) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            error!("Failed to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    info!("Watching for SIGHUP to reload configuration");
+
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            info!("SIGHUP received, reloading configuration...");
+            dotenvy::dotenv_override().ok();
+            match SealboxConfig::from_env_with(&overrides) {
+                Ok(config) => shared_config.reload(config),
+                Err(e) => error!("Keeping current configuration: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+fn watch_for_reload(_shared_config: SharedConfig, _overrides: ConfigOverrides) {
+    error!("--watch is only supported on Unix platforms");
+}
@@ -1,30 +1,141 @@
 use std::str::FromStr;
 
 use rusqlite::{ToSql, types::FromSql};
-use serde::{Deserialize, Serialize};
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{self, SeqAccess, Visitor},
+};
 use uuid::Uuid;
 
 use crate::{
     crypto::{
-        data_key::DataKey,
-        master_key::{PrivateMasterKey, PublicMasterKey},
+        envelope::{EnvelopeVersion, KeyWrapPadding, SecretBinding},
+        master_key::{KeyAlgorithm, PrivateMasterKey, PublicMasterKey},
     },
     error::{Result, SealboxError},
 };
 
 pub(crate) use self::sqlite::{
-    SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo, create_db_connection,
+    DbPool, SqliteAuditRepo, SqliteHealthRepo, SqliteMasterKeyRepo, SqliteSecretRepo,
+    create_db_pool,
 };
+pub(crate) use self::timed::{QueryTimer, Timed};
 
 mod sqlite;
+mod timed;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretInfo {
     pub key: String,             // Secret key identifier
-    pub version: i32,            // Latest version number
+    pub version: i32,            // Version number (latest version when listing secrets)
     pub created_at: i64,         // Creation timestamp (Unix time)
     pub updated_at: i64,         // Last update timestamp (Unix time)
     pub expires_at: Option<i64>, // Expiry timestamp (Unix time), optional for TTL
+    #[serde(default)]
+    pub tags: Vec<String>, // Labels of this version, e.g. "env=prod"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<i64>, // Soft-deletion timestamp (Unix time), set only in the recycle bin
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool, // Locked snapshot that can never be deleted or cleaned up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_after: Option<i64>, // When this version is due for rotation (Unix time), optional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, // Plaintext note about the secret, never encrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>, // `User-Agent` of the client that saved this version, if known
+    #[serde(default)]
+    pub read_count: u64, // Reads of this version, or of every live version when listing secrets
+    #[serde(default)]
+    pub last_read_at: Option<i64>, // Latest of those reads (Unix time), None if never read
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretPage {
+    pub total: usize,             // Matching secrets across all pages
+    pub secrets: Vec<SecretInfo>, // Secrets on the requested page
+}
+
+/// Row counts reported by `GET /{version}/admin/stats`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretCounts {
+    pub secrets: usize, // Distinct keys with a version that is not soft-deleted
+    pub expired_versions: usize, // Expired versions the next cleanup would remove
+}
+
+/// A top-level key prefix, the part of a key before its first `/`, and how many live
+/// secrets sit under it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamespaceCount {
+    pub namespace: String,
+    pub secrets: usize, // Distinct keys with a live, unexpired version
+}
+
+/// Criteria for listing secrets; all filters are optional
+#[derive(Debug, Clone, Default)]
+pub struct SecretFilter<'a> {
+    pub prefix: Option<&'a str>, // Only keys starting with this prefix
+    pub tag: Option<&'a str>,    // Only secrets whose latest version has this tag
+    pub limit: Option<u32>,      // Maximum number of secrets to return
+    pub offset: u32,             // Number of matching secrets to skip
+    pub deleted: bool,           // List soft-deleted versions instead of live secrets
+}
+
+/// One new version to write, with the metadata stored in the same insert as its value
+#[derive(Debug, Clone)]
+pub(crate) struct NewSecret {
+    pub(crate) key: String,
+    pub(crate) value: SecretValue,
+    pub(crate) ttl: Option<i64>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) rotate_after: Option<i64>, // When the version is due for rotation (Unix time)
+    pub(crate) description: Option<String>, // None keeps the previous version's, "" clears it
+    pub(crate) user_agent: Option<String>, // Client that saved the version
+}
+
+impl NewSecret {
+    /// A plaintext version of `key` without TTL, tags or other metadata
+    pub(crate) fn new(key: &str, data: &str, envelope: EnvelopeVersion) -> Self {
+        Self {
+            key: key.to_string(),
+            value: SecretValue::Plaintext {
+                data: data.to_string(),
+                envelope,
+            },
+            ttl: None,
+            tags: Vec::new(),
+            rotate_after: None,
+            description: None,
+            user_agent: None,
+        }
+    }
+}
+
+/// Value of a new version: plaintext the server encrypts on insert, or a payload the client
+/// already encrypted
+#[derive(Debug, Clone)]
+pub(crate) enum SecretValue {
+    Plaintext {
+        data: String,
+        envelope: EnvelopeVersion,
+    },
+    Encrypted(EncryptedSecret),
+}
+
+/// Outcome of a batch write: either every entry was committed or none was
+#[derive(Debug)]
+pub(crate) struct BatchOutcome {
+    pub(crate) committed: bool,
+    pub(crate) results: Vec<(String, Result<Secret>)>, // Per-entry result, in request order
+}
+
+/// A secret value encrypted by the client, which the server stores without ever seeing
+/// its plaintext
+#[derive(Debug, Clone)]
+pub(crate) struct EncryptedSecret {
+    pub(crate) encrypted_data: Vec<u8>,
+    pub(crate) encrypted_data_key: Vec<u8>,
+    pub(crate) master_key_id: Uuid,
+    pub(crate) envelope: EnvelopeVersion,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +150,58 @@ pub struct Secret {
     pub updated_at: i64,             // Last update timestamp (Unix time)
     pub expires_at: Option<i64>,     // Expiry timestamp (Unix time), optional for TTL
     pub metadata: Option<String>,    // Optional metadata in serialized format
+    pub envelope_version: i32,       // Crypto envelope format used to encrypt this version
+    #[serde(default, deserialize_with = "deserialize_tags")]
+    pub tags: Vec<String>, // Labels for grouping and filtering, e.g. "env=prod"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotate_after: Option<i64>, // When this version is due for rotation (Unix time), optional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, // Plaintext note about the secret, never encrypted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>, // `User-Agent` of the client that saved this version, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bound_version: Option<i32>, // Version the payload was sealed as, set on rollback copies; not authenticated
+}
+
+/// Deserialize tags from either a list or the JSON-encoded text stored in SQLite
+fn deserialize_tags<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct TagsVisitor;
+
+    impl<'de> Visitor<'de> for TagsVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a list of tags or a JSON-encoded list of tags")
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> std::result::Result<Self::Value, E> {
+            serde_json::from_str(value).map_err(E::custom)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(Vec::new())
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            let mut tags = Vec::new();
+            while let Some(tag) = seq.next_element()? {
+                tags.push(tag);
+            }
+            Ok(tags)
+        }
+    }
+
+    deserializer.deserialize_any(TagsVisitor)
 }
 
 impl Secret {
@@ -50,6 +213,8 @@ impl Secret {
     /// * `key` - The identifier for the secret.
     /// * `data` - The plaintext data to be encrypted and stored.
     /// * `master_key` - The `MasterKey` used to encrypt the data key.
+    /// * `envelope` - The envelope format to write, which decides whether the data is compressed
+    ///   and bound to `key` and `version`.
     ///
     /// # Returns
     ///
@@ -57,9 +222,10 @@ impl Secret {
     ///
     /// # Logic
     ///
-    /// 1. Converts the input data to bytes.
+    /// 1. Converts the input data to bytes, compressing it if the envelope calls for it.
     /// 2. Generates a random data key for encrypting the secret data.
-    /// 3. Encrypts the secret data using the generated data key.
+    /// 3. Encrypts the secret data using the generated data key, with `key` and `version` as
+    ///    associated data if the envelope calls for it.
     /// 4. Encrypts the data key using the provided master key's public key.
     /// 5. Sets the creation and update timestamps to the current time.
     /// 6. Constructs and returns the new `Secret` instance.
@@ -69,31 +235,54 @@ impl Secret {
         master_key: MasterKey,
         version: i32,
         ttl: Option<i64>,
+        envelope: EnvelopeVersion,
     ) -> Result<Self> {
-        let data_bytes = data.as_bytes();
-
-        let data_key = DataKey::new();
-        let encrypted_data = data_key.encrypt(data_bytes)?;
-
         let pub_key = PublicMasterKey::from_str(&master_key.public_key)?;
-        let encrypted_data_key = pub_key.encrypt(data_key.as_bytes())?;
+        let (encrypted_data, encrypted_data_key) =
+            envelope.seal(&pub_key, data.as_bytes(), SecretBinding { key, version })?;
+
+        Ok(Self::from_encrypted(
+            key,
+            EncryptedSecret {
+                encrypted_data,
+                encrypted_data_key,
+                master_key_id: master_key.id,
+                envelope,
+            },
+            version,
+            ttl,
+        ))
+    }
 
+    /// Creates a new `Secret` from data the client already encrypted, storing it verbatim
+    pub(crate) fn from_encrypted(
+        key: &str,
+        encrypted: EncryptedSecret,
+        version: i32,
+        ttl: Option<i64>,
+    ) -> Self {
         let now_timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
 
         let expires_at = ttl.map(|ttl| now_timestamp + ttl);
 
-        Ok(Self {
+        Self {
             namespace: String::new(),
             key: key.to_string(),
             version,
-            encrypted_data,
-            encrypted_data_key,
-            master_key_id: master_key.id,
+            encrypted_data: encrypted.encrypted_data,
+            encrypted_data_key: encrypted.encrypted_data_key,
+            master_key_id: encrypted.master_key_id,
             created_at: now_timestamp,
             updated_at: now_timestamp,
             expires_at,
             metadata: None,
-        })
+            envelope_version: encrypted.envelope.as_i32(),
+            tags: Vec::new(),
+            rotate_after: None,
+            description: None,
+            user_agent: None,
+            bound_version: None,
+        }
     }
 
     pub(crate) fn rotate_master_key(
@@ -141,20 +330,80 @@ pub(crate) trait SecretRepo: Send + Sync {
         key: &str,
         version: i32,
     ) -> Result<Secret>;
+    /// Store `secret` as the next version of its key; plaintext values are encrypted with
+    /// `master_key`, client-encrypted ones are stored without being decrypted
     fn create_new_version(
         &self,
         conn: &mut rusqlite::Connection,
-        key: &str,
-        data: &str,
         master_key: MasterKey,
-        ttl: Option<i64>,
+        secret: NewSecret,
     ) -> Result<Secret>;
+    /// Create a new version of every entry inside a single transaction.
+    ///
+    /// Every entry is attempted so each gets a result; if any of them fails the
+    /// transaction is rolled back and nothing is written.
+    fn create_new_versions_batch(
+        &self,
+        conn: &mut rusqlite::Connection,
+        master_key: &MasterKey,
+        entries: Vec<NewSecret>,
+    ) -> Result<BatchOutcome>;
+    /// Permanently remove one version; locked versions are refused with a conflict
     fn delete_secret_by_version(
         &self,
         conn: &rusqlite::Connection,
         key: &str,
         version: i32,
     ) -> Result<()>;
+    /// Permanently remove one version after overwriting its ciphertext and wrapped data key
+    /// with zeros, so copies of the row left in the WAL or free pages cannot be decrypted
+    fn shred_secret_by_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<()>;
+    /// Move one version to the recycle bin by setting `deleted_at`; reads and listings skip it
+    fn soft_delete(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()>;
+    /// Take one version, or every version of `key` when `version` is None, out of the
+    /// recycle bin and return how many were restored
+    fn restore_secret(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: Option<i32>,
+    ) -> Result<usize>;
+    /// Save a copy of `version` as the new latest version of `key`, reusing its ciphertext,
+    /// wrapped data key, expiry and tags, and return the new version
+    fn promote_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<Secret>;
+    /// Lock one version as an immutable snapshot. Locked versions are never deleted, shredded,
+    /// moved to the recycle bin or cleaned up after expiring, and cannot be unlocked
+    fn lock_version(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()>;
+    /// Set or clear (`None`, never expires) when one version expires, without re-encrypting it.
+    /// Versions that already expired but were not cleaned up yet are reported as not found
+    fn set_expiry(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: i32,
+        expires_at: Option<i64>,
+    ) -> Result<()>;
+    /// Move every version of `old_key`, including those in the recycle bin, to `new_key` and
+    /// return how many were moved; fails if `new_key` already has any version.
+    ///
+    /// Payloads bound to their key are re-sealed for `new_key`, which needs `private_key`.
+    fn rename_secret(
+        &self,
+        conn: &mut rusqlite::Connection,
+        old_key: &str,
+        new_key: &str,
+        private_key: Option<&PrivateMasterKey>,
+    ) -> Result<usize>;
 
     /// Fetch all secrets using the given master_key_id.
     fn fetch_secrets_by_master_key(
@@ -162,15 +411,46 @@ pub(crate) trait SecretRepo: Send + Sync {
         conn: &rusqlite::Connection,
         master_key_id: &Uuid,
     ) -> Result<Vec<Secret>>;
+    /// Fetch every stored version, including expired and soft-deleted ones
+    fn fetch_all_secrets(&self, conn: &rusqlite::Connection) -> Result<Vec<Secret>>;
     /// Update the master_key_id, encrypted_data_key, and updated_at fields for a list of secrets in a single transaction.
     fn update_secret_master_key(&self, conn: &rusqlite::Connection, secret: &Secret) -> Result<()>;
-    /// Batch delete all expired secrets and return the count of deleted records.
+    /// Batch delete all expired secrets except locked versions and return the count of deleted records.
     fn cleanup_expired_secrets(&self, conn: &rusqlite::Connection) -> Result<usize>;
-    /// List all secrets with basic information (key, latest version, timestamps)
-    fn list_secrets(&self, conn: &rusqlite::Connection) -> Result<Vec<SecretInfo>>;
+    /// Count secrets and expired versions still waiting for cleanup
+    fn count_secrets(&self, conn: &rusqlite::Connection) -> Result<SecretCounts>;
+    /// List one page of secrets with basic information (key, latest version, timestamps, tags)
+    /// matching `filter`, along with the total number of matches. With `filter.deleted` set,
+    /// lists every soft-deleted version instead
+    fn list_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &SecretFilter,
+    ) -> Result<SecretPage>;
+    /// List the namespaces derived from live, unexpired keys with the number of secrets in
+    /// each, sorted by name. Keys without a `/` belong to no namespace
+    fn list_namespaces(&self, conn: &rusqlite::Connection) -> Result<Vec<NamespaceCount>>;
+    /// Count a read of one version and stamp its `last_read_at`
+    fn record_read(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()>;
+    /// List the latest version of every secret none of whose live versions was read within
+    /// `days`, never-read secrets first
+    fn list_unused_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<SecretInfo>>;
+    /// List every unexpired version of a secret, newest version first
+    fn list_secret_versions(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+    ) -> Result<Vec<SecretInfo>>;
+    /// List secrets whose latest live version is past its `rotate_after` time, most overdue
+    /// first
+    fn list_rotation_due(&self, conn: &rusqlite::Connection) -> Result<Vec<SecretInfo>>;
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MasterKeyStatus {
     Active,
     Retired,
@@ -196,6 +476,23 @@ impl FromSql for MasterKeyStatus {
     }
 }
 
+impl ToSql for KeyAlgorithm {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput> {
+        Ok(rusqlite::types::ToSqlOutput::from(self.as_str()))
+    }
+}
+impl FromSql for KeyAlgorithm {
+    fn column_result(value: rusqlite::types::ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|err| rusqlite::types::FromSqlError::Other(Box::new(err)))
+    }
+}
+
+/// Placeholder shown instead of a master key's PEM wherever the key itself is not needed
+pub const HIDDEN_PUBLIC_KEY: &str = "[HIDDEN]";
+
 /// MasterKey struct, represents a row in the master_keys table
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterKey {
@@ -205,15 +502,42 @@ pub struct MasterKey {
     pub status: MasterKeyStatus,     // Status: Active/Retired/Disabled
     pub description: Option<String>, // Optional description
     pub metadata: Option<String>,    // Optional metadata
+    #[serde(default)]
+    pub algorithm: KeyAlgorithm, // Algorithm of the public key, so clients know how to unwrap
+    #[serde(default)]
+    pub default_ttl: Option<i64>, // TTL in seconds for secrets saved without one, never expire if None
+    #[serde(default)]
+    pub last_used_at: Option<i64>, // Last read of a secret wrapped with this key, None if never
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>, // SHA-256 fingerprint of the public key, derived when read
+}
+
+/// Fingerprint of a stored public key PEM, `None` if it does not parse
+pub(crate) fn public_key_fingerprint(public_key: &str) -> Option<String> {
+    PublicMasterKey::from_str(public_key)
+        .ok()?
+        .fingerprint()
+        .ok()
 }
 
 impl MasterKey {
+    /// A new active master key for `public_key`
+    ///
+    /// # Errors
+    ///
+    /// * `SealboxError::InvalidInput` - When `public_key` is not a well-formed RSA or X25519 public key PEM
     pub(crate) fn new(public_key: String) -> Result<Self> {
+        // Storing an unparsable key would only surface later, when a secret is saved with it
+        let parsed = PublicMasterKey::from_str(&public_key).map_err(|err| {
+            SealboxError::InvalidInput(format!("Invalid master public key: {err}"))
+        })?;
+
         let id = Uuid::new_v4();
         let created_at = time::OffsetDateTime::now_utc().unix_timestamp();
         let status = MasterKeyStatus::Active;
         let description = None;
         let metadata = None;
+        let algorithm = KeyAlgorithm::detect(&public_key);
 
         Ok(MasterKey {
             id,
@@ -222,14 +546,46 @@ impl MasterKey {
             status,
             description,
             metadata,
+            algorithm,
+            default_ttl: None,
+            last_used_at: None,
+            fingerprint: parsed.fingerprint().ok(),
         })
     }
+
+    /// How data keys of secrets saved under this master key are wrapped
+    pub fn key_wrap_padding(&self) -> KeyWrapPadding {
+        self.algorithm.into()
+    }
+}
+
+/// Criteria for listing master keys; all filters are optional
+#[derive(Debug, Clone, Default)]
+pub struct MasterKeyFilter<'a> {
+    pub status: Option<MasterKeyStatus>, // Only keys with this status
+    pub description_contains: Option<&'a str>, // Only keys whose description contains this text
+    pub limit: Option<u32>,              // Maximum number of keys to return
+    pub offset: u32,                     // Number of matching keys to skip
+}
+
+/// One page of master keys along with the number of keys matching the filter
+#[derive(Debug, Clone)]
+pub struct MasterKeyPage {
+    pub total: usize,                // Matching keys across all pages
+    pub master_keys: Vec<MasterKey>, // Keys on the requested page, newest first
 }
 
 /// MasterKeyRepo trait for managing master_keys table
 pub(crate) trait MasterKeyRepo: Send + Sync {
     fn create_master_key(&self, conn: &rusqlite::Connection, key: &MasterKey) -> Result<()>;
     fn fetch_all_master_keys(&self, conn: &rusqlite::Connection) -> Result<Vec<MasterKey>>;
+    /// List one page of master keys matching `filter`, newest first, along with the total
+    /// number of matches
+    fn list_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &MasterKeyFilter,
+    ) -> Result<MasterKeyPage>;
 
     /// Fetch the PEM-encoded public key for a given master_key_id.
     fn fetch_public_key(
@@ -240,16 +596,79 @@ pub(crate) trait MasterKeyRepo: Send + Sync {
 
     /// Fetch a valid master key.
     fn get_valid_master_key(&self, conn: &rusqlite::Connection) -> Result<MasterKey>;
+
+    /// Count registered master keys, whatever their status.
+    fn count_master_keys(&self, conn: &rusqlite::Connection) -> Result<usize>;
+
+    /// Record that a secret wrapped with this master key was just read.
+    fn mark_used(&self, conn: &rusqlite::Connection, master_key_id: &Uuid) -> Result<()>;
+
+    /// Fetch master keys that are not disabled and were never used, or not in the last `days` days.
+    fn fetch_stale_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<MasterKey>>;
 }
 
 pub(crate) trait HealthRepo: Send + Sync {
     fn check_health(&self, conn: &rusqlite::Connection) -> Result<bool>;
+    /// Size of the database in bytes, as SQLite's page count times page size
+    fn database_size(&self, conn: &rusqlite::Connection) -> Result<u64>;
+    /// Write a transactionally consistent copy of the database to a new file at `path`
+    fn backup_into(&self, conn: &rusqlite::Connection, path: &std::path::Path) -> Result<()>;
+}
+
+/// AuditEntry struct, represents a row in the audit_log table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,                     // Insertion order, increasing
+    pub timestamp: i64,              // When the operation completed (Unix time)
+    pub request_id: Option<String>,  // `x-request-id` of the request that performed it
+    pub action: String,              // Operation, e.g. "secret.get" or "master_key.create"
+    pub target_key: Option<String>,  // Secret key the operation addressed
+    pub target_version: Option<i32>, // Secret version the request named, if any
+    pub status: u16,                 // HTTP status code of the response
+    #[serde(default)]
+    pub user_agent: Option<String>, // `User-Agent` header of the request
+}
+
+/// An operation about to be appended to the audit log
+#[derive(Debug, Clone)]
+pub(crate) struct NewAuditEntry {
+    pub(crate) request_id: Option<String>,
+    pub(crate) action: &'static str,
+    pub(crate) target_key: Option<String>,
+    pub(crate) target_version: Option<i32>,
+    pub(crate) status: u16,
+    pub(crate) user_agent: Option<String>,
+}
+
+/// Criteria for reading the audit log
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub since: Option<i64>,     // Only entries recorded at or after this Unix time
+    pub until: Option<i64>,     // Only entries recorded before this Unix time
+    pub action: Option<String>, // Only entries for this operation, e.g. "secret.get"
+    pub target_key: Option<String>, // Only entries addressing this secret key
+    pub before_id: Option<i64>, // Pagination cursor: only entries older than this id
+    pub limit: u32,             // Maximum number of entries to return, newest first
+}
+
+/// AuditRepo trait for the append-only audit_log table
+pub(crate) trait AuditRepo: Send + Sync {
+    fn record(&self, conn: &rusqlite::Connection, entry: &NewAuditEntry) -> Result<()>;
+    fn list_entries(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEntry>>;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crypto::master_key::generate_key_pair;
+    use crate::crypto::{envelope::CURRENT_ENVELOPE_VERSION, master_key::generate_key_pair};
 
     #[test]
     fn test_master_key_new() {
@@ -263,6 +682,22 @@ mod tests {
         assert!(master_key.created_at > 0);
     }
 
+    #[test]
+    fn test_master_key_new_rejects_invalid_public_key() {
+        let (private_pem, _) = generate_key_pair().expect("Should generate key pair");
+        for public_key in [
+            "garbage".to_string(),
+            "-----BEGIN RSA PUBLIC KEY-----\nAAAA\n-----END RSA PUBLIC KEY-----\n".to_string(),
+            // A private key is not a public key
+            private_pem,
+        ] {
+            assert!(matches!(
+                MasterKey::new(public_key),
+                Err(SealboxError::InvalidInput(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_master_key_status_serialization() {
         // Test ToSql conversion
@@ -290,8 +725,15 @@ mod tests {
         let version = 1;
         let ttl = Some(3600); // 1 hour
 
-        let secret = Secret::new(secret_key, secret_data, master_key.clone(), version, ttl)
-            .expect("Should create secret");
+        let secret = Secret::new(
+            secret_key,
+            secret_data,
+            master_key.clone(),
+            version,
+            ttl,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         assert_eq!(secret.key, secret_key);
         assert_eq!(secret.version, version);
@@ -303,6 +745,7 @@ mod tests {
         assert!(!secret.encrypted_data_key.is_empty());
         assert_eq!(secret.namespace, "");
         assert!(secret.metadata.is_none());
+        assert_eq!(secret.envelope_version, CURRENT_ENVELOPE_VERSION.as_i32());
     }
 
     #[test]
@@ -310,8 +753,15 @@ mod tests {
         let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
         let master_key = MasterKey::new(public_pem).expect("Should create master key");
 
-        let secret = Secret::new("test-key", "test-data", master_key, 1, None)
-            .expect("Should create secret");
+        let secret = Secret::new(
+            "test-key",
+            "test-data",
+            master_key,
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         assert!(secret.expires_at.is_none());
     }
@@ -323,10 +773,24 @@ mod tests {
 
         let secret_data = "Same secret data";
 
-        let secret1 = Secret::new("key1", secret_data, master_key.clone(), 1, None)
-            .expect("Should create first secret");
-        let secret2 = Secret::new("key2", secret_data, master_key, 2, None)
-            .expect("Should create second secret");
+        let secret1 = Secret::new(
+            "key1",
+            secret_data,
+            master_key.clone(),
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create first secret");
+        let secret2 = Secret::new(
+            "key2",
+            secret_data,
+            master_key,
+            2,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create second secret");
 
         // Even with same data, encrypted results should be different due to random data keys
         assert_ne!(secret1.encrypted_data, secret2.encrypted_data);
@@ -342,9 +806,15 @@ mod tests {
         let old_master_key = MasterKey::new(old_public_pem).expect("Should create old master key");
         let new_master_key = MasterKey::new(new_public_pem).expect("Should create new master key");
 
-        let original_secret =
-            Secret::new("test-key", "secret-data", old_master_key.clone(), 1, None)
-                .expect("Should create secret");
+        let original_secret = Secret::new(
+            "test-key",
+            "secret-data",
+            old_master_key.clone(),
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         let original_created_at = original_secret.created_at;
         let original_encrypted_data = original_secret.encrypted_data.clone();
@@ -374,8 +844,15 @@ mod tests {
         let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
         let master_key = MasterKey::new(public_pem).expect("Should create master key");
 
-        let original_secret = Secret::new("test-key", "secret-data", master_key.clone(), 1, None)
-            .expect("Should create secret");
+        let original_secret = Secret::new(
+            "test-key",
+            "secret-data",
+            master_key.clone(),
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         // Rotating to the same key should return the secret unchanged
         let rotated_secret = original_secret
@@ -407,8 +884,15 @@ mod tests {
         let wrong_master_key =
             MasterKey::new(wrong_public_pem).expect("Should create wrong master key");
 
-        let original_secret = Secret::new("test-key", "secret-data", old_master_key, 1, None)
-            .expect("Should create secret");
+        let original_secret = Secret::new(
+            "test-key",
+            "secret-data",
+            old_master_key,
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         // Trying to rotate with wrong old key ID should fail
         let result = original_secret.rotate_master_key(
@@ -433,9 +917,15 @@ mod tests {
         let old_master_key = MasterKey::new(old_public_pem).expect("Should create old master key");
         let new_master_key = MasterKey::new(new_public_pem).expect("Should create new master key");
 
-        let original_secret =
-            Secret::new("test-key", "secret-data", old_master_key.clone(), 1, None)
-                .expect("Should create secret");
+        let original_secret = Secret::new(
+            "test-key",
+            "secret-data",
+            old_master_key.clone(),
+            1,
+            None,
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         // Invalid private key should cause rotation to fail
         let result = original_secret.rotate_master_key(
@@ -454,8 +944,15 @@ mod tests {
         let master_key = MasterKey::new(public_pem).expect("Should create master key");
 
         let ttl_seconds = 7200i64; // 2 hours
-        let secret = Secret::new("test-key", "test-data", master_key, 1, Some(ttl_seconds))
-            .expect("Should create secret");
+        let secret = Secret::new(
+            "test-key",
+            "test-data",
+            master_key,
+            1,
+            Some(ttl_seconds),
+            CURRENT_ENVELOPE_VERSION,
+        )
+        .expect("Should create secret");
 
         let expected_expiry = secret.created_at + ttl_seconds;
         assert_eq!(secret.expires_at, Some(expected_expiry));
@@ -0,0 +1,241 @@
+use crate::{
+    error::Result,
+    repo::{AuditEntry, AuditFilter, AuditRepo, NewAuditEntry, sqlite::ensure_column},
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct SqliteAuditRepo;
+
+impl SqliteAuditRepo {
+    pub fn init_table(conn: &rusqlite::Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp INTEGER NOT NULL,
+                request_id TEXT,
+                action TEXT NOT NULL,
+                target_key TEXT,
+                target_version INTEGER,
+                status INTEGER NOT NULL,
+                user_agent TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_audit_log_timestamp ON audit_log(timestamp);
+            -- Entries are evidence: refuse to rewrite or remove them through SQL
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;
+            CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit_log is append-only');
+            END;",
+        )?;
+        ensure_column(conn, "audit_log", "user_agent", "TEXT")?;
+        Ok(())
+    }
+}
+
+impl AuditRepo for SqliteAuditRepo {
+    fn record(&self, conn: &rusqlite::Connection, entry: &NewAuditEntry) -> Result<()> {
+        let timestamp = time::OffsetDateTime::now_utc().unix_timestamp();
+        conn.execute(
+            "INSERT INTO audit_log (
+                timestamp,
+                request_id,
+                action,
+                target_key,
+                target_version,
+                status,
+                user_agent
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                timestamp,
+                &entry.request_id,
+                entry.action,
+                &entry.target_key,
+                entry.target_version,
+                entry.status,
+                &entry.user_agent,
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn list_entries(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEntry>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, timestamp, request_id, action, target_key, target_version, status,
+                user_agent
+             FROM audit_log
+             WHERE (?1 IS NULL OR timestamp >= ?1)
+               AND (?2 IS NULL OR timestamp < ?2)
+               AND (?3 IS NULL OR action = ?3)
+               AND (?4 IS NULL OR target_key = ?4)
+               AND (?5 IS NULL OR id < ?5)
+             ORDER BY id DESC
+             LIMIT ?6",
+        )?;
+        let params = (
+            filter.since,
+            filter.until,
+            &filter.action,
+            &filter.target_key,
+            filter.before_id,
+            filter.limit,
+        );
+        let entries = stmt
+            .query_map(params, |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    request_id: row.get(2)?,
+                    action: row.get(3)?,
+                    target_key: row.get(4)?,
+                    target_version: row.get(5)?,
+                    status: row.get(6)?,
+                    user_agent: row.get(7)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_test_db() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+        SqliteAuditRepo::init_table(&conn).expect("Should initialize tables");
+        conn
+    }
+
+    fn entry(action: &'static str, key: &str) -> NewAuditEntry {
+        NewAuditEntry {
+            request_id: Some("req-1".to_string()),
+            action,
+            target_key: Some(key.to_string()),
+            target_version: None,
+            status: 200,
+            user_agent: Some("sealbox-cli/0.1.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_record_and_list_newest_first() {
+        let conn = setup_test_db();
+        let repo = SqliteAuditRepo;
+        repo.record(&conn, &entry("secret.save", "a")).unwrap();
+        repo.record(&conn, &entry("secret.get", "a")).unwrap();
+        repo.record(&conn, &entry("secret.delete", "b")).unwrap();
+
+        let entries = repo
+            .list_entries(
+                &conn,
+                &AuditFilter {
+                    limit: 2,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let actions: Vec<_> = entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, vec!["secret.delete", "secret.get"]);
+        assert_eq!(entries[0].request_id.as_deref(), Some("req-1"));
+        assert_eq!(entries[0].status, 200);
+        assert_eq!(entries[0].user_agent.as_deref(), Some("sealbox-cli/0.1.0"));
+
+        let future = time::OffsetDateTime::now_utc().unix_timestamp() + 60;
+        let entries = repo
+            .list_entries(
+                &conn,
+                &AuditFilter {
+                    since: Some(future),
+                    limit: 10,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(entries.is_empty());
+    }
+
+    #[test]
+    fn test_list_filters_and_cursor() {
+        let conn = setup_test_db();
+        let repo = SqliteAuditRepo;
+        repo.record(&conn, &entry("secret.save", "a")).unwrap();
+        repo.record(&conn, &entry("secret.get", "a")).unwrap();
+        repo.record(&conn, &entry("secret.get", "b")).unwrap();
+        repo.record(&conn, &entry("secret.get", "a")).unwrap();
+
+        let list = |filter: AuditFilter| repo.list_entries(&conn, &filter).unwrap();
+
+        let gets_of_a = list(AuditFilter {
+            action: Some("secret.get".to_string()),
+            target_key: Some("a".to_string()),
+            limit: 10,
+            ..Default::default()
+        });
+        assert_eq!(gets_of_a.len(), 2);
+        assert!(
+            gets_of_a
+                .iter()
+                .all(|e| e.action == "secret.get" && e.target_key.as_deref() == Some("a"))
+        );
+
+        // Walking the cursor visits every entry exactly once, newest first
+        let mut ids = Vec::new();
+        let mut before_id = None;
+        loop {
+            let page = list(AuditFilter {
+                before_id,
+                limit: 3,
+                ..Default::default()
+            });
+            ids.extend(page.iter().map(|e| e.id));
+            match page.last() {
+                Some(last) if page.len() == 3 => before_id = Some(last.id),
+                _ => break,
+            }
+        }
+        assert_eq!(ids.len(), 4);
+        assert!(ids.windows(2).all(|pair| pair[0] > pair[1]));
+
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        assert!(
+            list(AuditFilter {
+                until: Some(now - 60),
+                limit: 10,
+                ..Default::default()
+            })
+            .is_empty()
+        );
+        assert_eq!(
+            list(AuditFilter {
+                since: Some(now - 60),
+                until: Some(now + 60),
+                limit: 10,
+                ..Default::default()
+            })
+            .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_audit_log_is_append_only() {
+        let conn = setup_test_db();
+        SqliteAuditRepo
+            .record(&conn, &entry("secret.save", "a"))
+            .unwrap();
+
+        assert!(
+            conn.execute("UPDATE audit_log SET status = 500", ())
+                .is_err()
+        );
+        assert!(conn.execute("DELETE FROM audit_log", ()).is_err());
+    }
+}
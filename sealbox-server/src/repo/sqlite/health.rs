@@ -3,7 +3,7 @@ use crate::repo::HealthRepo;
 #[derive(Debug, Clone)]
 pub(crate) struct SqliteHealthRepo;
 
-use crate::error::Result;
+use crate::error::{Result, SealboxError};
 
 impl HealthRepo for SqliteHealthRepo {
     fn check_health(&self, conn: &rusqlite::Connection) -> Result<bool> {
@@ -11,4 +11,48 @@ impl HealthRepo for SqliteHealthRepo {
         let row: i32 = stmt.query_row([], |row| row.get(0))?;
         Ok(row == 1)
     }
+
+    fn database_size(&self, conn: &rusqlite::Connection) -> Result<u64> {
+        let size = conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(size)
+    }
+
+    fn backup_into(&self, conn: &rusqlite::Connection, path: &std::path::Path) -> Result<()> {
+        // VACUUM INTO reads the database in one transaction, so concurrent writes are either
+        // wholly in the copy or not at all; the target must not exist yet
+        let path = path.to_str().ok_or_else(|| {
+            SealboxError::InvalidInput(format!("Backup path is not UTF-8: {}", path.display()))
+        })?;
+        conn.execute("VACUUM INTO ?1", [path])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_into_copies_database() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('kept');")
+            .unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("backup.db");
+
+        SqliteHealthRepo.backup_into(&conn, &path).unwrap();
+
+        let copy = rusqlite::Connection::open(&path).unwrap();
+        let value: String = copy
+            .query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, "kept");
+
+        // An existing file is never overwritten
+        assert!(SqliteHealthRepo.backup_into(&conn, &path).is_err());
+    }
 }
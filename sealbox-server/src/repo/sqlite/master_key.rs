@@ -1,9 +1,13 @@
 use rusqlite::OptionalExtension;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
     error::{Result, SealboxError},
-    repo::{MasterKey, MasterKeyRepo, MasterKeyStatus},
+    repo::{
+        HIDDEN_PUBLIC_KEY, MasterKey, MasterKeyFilter, MasterKeyPage, MasterKeyRepo,
+        MasterKeyStatus, public_key_fingerprint, sqlite::ensure_column,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -20,14 +24,60 @@ impl SqliteMasterKeyRepo {
                 status TEXT NOT NULL,
                 description TEXT,
                 version INTEGER,
-                metadata TEXT
+                metadata TEXT,
+                algorithm TEXT NOT NULL DEFAULT 'rsa',
+                default_ttl INTEGER,
+                last_used_at INTEGER
             )",
             (),
         )?;
+        // Keys registered before X25519 support are all RSA
+        ensure_column(
+            conn,
+            "master_keys",
+            "algorithm",
+            "TEXT NOT NULL DEFAULT 'rsa'",
+        )?;
+        ensure_column(conn, "master_keys", "default_ttl", "INTEGER")?;
+        ensure_column(conn, "master_keys", "last_used_at", "INTEGER")?;
+
+        // Databases that already hold duplicate keys keep working, just without the index
+        if let Err(err) = conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_master_keys_public_key ON master_keys(public_key)",
+            (),
+        ) {
+            if is_constraint_violation(&err) {
+                warn!("Duplicate master public keys found; not enforcing unique registration");
+            } else {
+                return Err(err.into());
+            }
+        }
         Ok(())
     }
 }
 
+/// Columns read by `listed_master_key`; the public key is only read to fingerprint it
+const LISTED_COLUMNS: &str = "id, created_at, status, description, metadata, algorithm, default_ttl, last_used_at, public_key";
+
+fn listed_master_key(row: &rusqlite::Row) -> rusqlite::Result<MasterKey> {
+    Ok(MasterKey {
+        id: row.get(0)?,
+        public_key: HIDDEN_PUBLIC_KEY.to_string(),
+        created_at: row.get(1)?,
+        status: row.get(2)?,
+        description: row.get(3)?,
+        metadata: row.get(4)?,
+        algorithm: row.get(5)?,
+        default_ttl: row.get(6)?,
+        last_used_at: row.get(7)?,
+        fingerprint: public_key_fingerprint(&row.get::<_, String>(8)?),
+    })
+}
+
+fn is_constraint_violation(err: &rusqlite::Error) -> bool {
+    err.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation)
+}
+
 impl MasterKeyRepo for SqliteMasterKeyRepo {
     fn create_master_key(&self, conn: &rusqlite::Connection, key: &MasterKey) -> Result<()> {
         conn.execute(
@@ -37,8 +87,10 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
                 created_at,
                 status,
                 description,
-                metadata
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                metadata,
+                algorithm,
+                default_ttl
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             (
                 &key.id,
                 &key.public_key,
@@ -46,8 +98,17 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
                 &key.status,
                 &key.description,
                 &key.metadata,
+                &key.algorithm,
+                &key.default_ttl,
             ),
-        )?;
+        )
+        .map_err(|err| {
+            if is_constraint_violation(&err) {
+                SealboxError::Conflict("This public key is already registered".to_string())
+            } else {
+                err.into()
+            }
+        })?;
         Ok(())
     }
 
@@ -64,7 +125,11 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
     }
 
     fn get_valid_master_key(&self, conn: &rusqlite::Connection) -> Result<MasterKey> {
-        let mut stmt = conn.prepare("SELECT * FROM master_keys WHERE status = ?1 LIMIT 1")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, public_key, created_at, status, description, metadata, algorithm, default_ttl,
+                last_used_at
+             FROM master_keys WHERE status = ?1 LIMIT 1",
+        )?;
         let master_key = stmt
             .query_one([MasterKeyStatus::Active], |row| {
                 Ok(MasterKey {
@@ -74,6 +139,10 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
                     status: row.get(3)?,
                     description: row.get(4)?,
                     metadata: row.get(5)?,
+                    algorithm: row.get(6)?,
+                    default_ttl: row.get(7)?,
+                    last_used_at: row.get(8)?,
+                    fingerprint: public_key_fingerprint(&row.get::<_, String>(1)?),
                 })
             })
             .optional()?;
@@ -86,18 +155,8 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
     }
 
     fn fetch_all_master_keys(&self, conn: &rusqlite::Connection) -> Result<Vec<MasterKey>> {
-        let mut stmt =
-            conn.prepare("SELECT id, created_at, status, description, metadata FROM master_keys")?;
-        let master_key_iter = stmt.query_map([], |row| {
-            Ok(MasterKey {
-                id: row.get(0)?,
-                public_key: "[HIDDEN]".to_string(),
-                created_at: row.get(1)?,
-                status: row.get(2)?,
-                description: row.get(3)?,
-                metadata: row.get(4)?,
-            })
-        })?;
+        let mut stmt = conn.prepare(&format!("SELECT {LISTED_COLUMNS} FROM master_keys"))?;
+        let master_key_iter = stmt.query_map([], listed_master_key)?;
 
         let master_keys: Vec<_> = master_key_iter
             .filter_map(|res| {
@@ -108,4 +167,200 @@ impl MasterKeyRepo for SqliteMasterKeyRepo {
 
         Ok(master_keys)
     }
+
+    fn list_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &MasterKeyFilter,
+    ) -> Result<MasterKeyPage> {
+        // instr() matches the description text literally, unlike LIKE wildcards
+        let matching = "FROM master_keys
+            WHERE (?1 IS NULL OR status = ?1)
+                AND (?2 IS NULL OR instr(description, ?2) > 0)";
+        // SQLite treats a negative LIMIT as no limit
+        let limit = filter.limit.map_or(-1, i64::from);
+
+        let total: usize = conn.query_row(
+            &format!("SELECT COUNT(*) {matching}"),
+            (&filter.status, filter.description_contains),
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {LISTED_COLUMNS} {matching}
+            ORDER BY created_at DESC, id
+            LIMIT ?3 OFFSET ?4"
+        ))?;
+        let master_keys = stmt
+            .query_map(
+                (
+                    &filter.status,
+                    filter.description_contains,
+                    limit,
+                    filter.offset,
+                ),
+                listed_master_key,
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(MasterKeyPage { total, master_keys })
+    }
+
+    fn count_master_keys(&self, conn: &rusqlite::Connection) -> Result<usize> {
+        let count: usize =
+            conn.query_row("SELECT COUNT(*) FROM master_keys", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    fn mark_used(&self, conn: &rusqlite::Connection, master_key_id: &Uuid) -> Result<()> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        conn.execute(
+            "UPDATE master_keys SET last_used_at = ?1 WHERE id = ?2",
+            (now, master_key_id),
+        )?;
+        Ok(())
+    }
+
+    fn fetch_stale_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<MasterKey>> {
+        let cutoff = time::OffsetDateTime::now_utc().unix_timestamp() - i64::from(days) * 86_400;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {LISTED_COLUMNS} FROM master_keys
+             WHERE status != ?1 AND (last_used_at IS NULL OR last_used_at < ?2)
+             ORDER BY last_used_at IS NOT NULL, last_used_at, created_at"
+        ))?;
+        let master_keys = stmt
+            .query_map((MasterKeyStatus::Disabled, cutoff), listed_master_key)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(master_keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::master_key::{
+        KeyAlgorithm, generate_key_pair, generate_key_pair_with_algorithm,
+    };
+
+    fn setup_test_db() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should initialize tables");
+        conn
+    }
+
+    #[test]
+    fn test_create_duplicate_public_key_conflicts() {
+        let conn = setup_test_db();
+        let repo = SqliteMasterKeyRepo;
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+
+        let first = MasterKey::new(public_pem.clone()).unwrap();
+        repo.create_master_key(&conn, &first)
+            .expect("First registration should succeed");
+
+        let duplicate = MasterKey::new(public_pem).unwrap();
+        let err = repo
+            .create_master_key(&conn, &duplicate)
+            .expect_err("Duplicate registration should fail");
+        assert!(matches!(err, SealboxError::Conflict(_)));
+
+        let (_, other_pem) = generate_key_pair().expect("Should generate key pair");
+        repo.create_master_key(&conn, &MasterKey::new(other_pem).unwrap())
+            .expect("A different key should register");
+    }
+
+    #[test]
+    fn test_init_table_tolerates_existing_duplicates() {
+        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+        conn.execute_batch(
+            "CREATE TABLE master_keys (
+                id BLOB PRIMARY KEY,
+                public_key TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                status TEXT NOT NULL,
+                description TEXT,
+                version INTEGER,
+                metadata TEXT
+            );
+            INSERT INTO master_keys (id, public_key, created_at, status) VALUES (x'01', 'pem', 0, 'Active');
+            INSERT INTO master_keys (id, public_key, created_at, status) VALUES (x'02', 'pem', 0, 'Retired');",
+        )
+        .expect("Should create legacy table");
+
+        SqliteMasterKeyRepo::init_table(&conn).expect("Should migrate despite duplicates");
+    }
+
+    #[test]
+    fn test_list_master_keys_filters_and_pages() {
+        let conn = setup_test_db();
+        let repo = SqliteMasterKeyRepo;
+
+        for (created_at, status, description) in [
+            (100, MasterKeyStatus::Active, Some("laptop 1")),
+            (200, MasterKeyStatus::Retired, Some("laptop 2")),
+            (300, MasterKeyStatus::Active, Some("ci runner")),
+            (400, MasterKeyStatus::Active, None),
+        ] {
+            let (_, public_pem) = generate_key_pair_with_algorithm(KeyAlgorithm::X25519).unwrap();
+            let mut key = MasterKey::new(public_pem).unwrap();
+            key.created_at = created_at;
+            key.status = status;
+            key.description = description.map(str::to_string);
+            repo.create_master_key(&conn, &key).unwrap();
+        }
+
+        let created = |page: &MasterKeyPage| {
+            page.master_keys
+                .iter()
+                .map(|key| key.created_at)
+                .collect::<Vec<_>>()
+        };
+
+        let page = repo
+            .list_master_keys(&conn, &MasterKeyFilter::default())
+            .unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(created(&page), vec![400, 300, 200, 100]);
+        assert_eq!(page.master_keys[0].public_key, HIDDEN_PUBLIC_KEY);
+
+        let page = repo
+            .list_master_keys(
+                &conn,
+                &MasterKeyFilter {
+                    status: Some(MasterKeyStatus::Active),
+                    limit: Some(2),
+                    offset: 1,
+                    ..MasterKeyFilter::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(created(&page), vec![300, 100]);
+
+        let page = repo
+            .list_master_keys(
+                &conn,
+                &MasterKeyFilter {
+                    description_contains: Some("laptop"),
+                    ..MasterKeyFilter::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(created(&page), vec![200, 100]);
+
+        // The text is matched literally, not as a LIKE pattern
+        let page = repo
+            .list_master_keys(
+                &conn,
+                &MasterKeyFilter {
+                    description_contains: Some("%"),
+                    ..MasterKeyFilter::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(page.total, 0);
+    }
 }
@@ -1,23 +1,124 @@
+pub(crate) mod audit;
 pub(crate) mod health;
 pub(crate) mod master_key;
 pub(crate) mod secret;
 
-use rusqlite::Connection;
+use std::time::Duration;
+
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{Connection, TransactionBehavior};
 
 use crate::error::Result;
 
 pub(crate) use self::{
-    health::SqliteHealthRepo, master_key::SqliteMasterKeyRepo, secret::SqliteSecretRepo,
+    audit::SqliteAuditRepo, health::SqliteHealthRepo, master_key::SqliteMasterKeyRepo,
+    secret::SqliteSecretRepo,
 };
 
-pub(crate) fn create_db_connection(db_path: &str) -> Result<Connection> {
-    let conn = Connection::open(db_path)?;
+/// Pool of SQLite connections shared by request handlers and background jobs
+pub(crate) type DbPool = r2d2::Pool<SqliteConnectionManager>;
+
+/// Path that opens a private in-memory database instead of a file
+const IN_MEMORY_PATH: &str = ":memory:";
+
+/// Open a pool of up to `pool_size` connections to the database at `db_path`.
+///
+/// Every connection to `:memory:` opens its own empty database, so that path always gets
+/// a pool of one.
+pub(crate) fn create_db_pool(db_path: &str, pool_size: u32) -> Result<DbPool> {
+    let (manager, pool_size) = if db_path == IN_MEMORY_PATH {
+        (SqliteConnectionManager::memory(), 1)
+    } else {
+        (SqliteConnectionManager::file(db_path), pool_size)
+    };
 
-    // Enable WAL mode to improve concurrency
+    let pool = r2d2::Pool::builder()
+        .max_size(pool_size)
+        // Connections are never recycled, closing one to `:memory:` would lose its data
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .build(manager.with_init(configure_connection))?;
+
+    Ok(pool)
+}
+
+/// Settings applied to every pooled connection when it is opened
+fn configure_connection(conn: &mut Connection) -> rusqlite::Result<()> {
+    // Enable WAL mode so readers never wait for a writer
     conn.pragma_update(None, "journal_mode", "WAL")?;
 
-    // Set busy timeout to prevent immediate failure on lock conflicts
-    conn.busy_timeout(std::time::Duration::from_millis(500))?;
+    // Writers on other connections hold the lock for at most one transaction
+    conn.busy_timeout(Duration::from_secs(5))?;
+
+    // Take the write lock when a transaction starts, so two transactions that read before
+    // writing wait for each other instead of failing to upgrade their read locks
+    conn.set_transaction_behavior(TransactionBehavior::Immediate);
+
+    Ok(())
+}
+
+/// Add a column to an existing table if it is missing.
+///
+/// `CREATE TABLE IF NOT EXISTS` leaves tables created by older releases untouched,
+/// so new columns must be added explicitly to keep those databases usable.
+pub(crate) fn ensure_column(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let exists = conn
+        .prepare(&format!(
+            "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+        ))?
+        .exists([column])?;
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+            (),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_db_pool_uses_pool_size() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("sealbox.db");
+        let pool = create_db_pool(path.to_str().unwrap(), 3).unwrap();
+        assert_eq!(pool.max_size(), 3);
+
+        // Every checked-out connection shares the same database file
+        let first = pool.get().unwrap();
+        let second = pool.get().unwrap();
+        first.execute_batch("CREATE TABLE t (v TEXT)").unwrap();
+        second
+            .execute("INSERT INTO t VALUES ('shared')", ())
+            .unwrap();
+        let count: i64 = first
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_create_db_pool_keeps_in_memory_database_on_one_connection() {
+        let pool = create_db_pool(IN_MEMORY_PATH, 8).unwrap();
+        assert_eq!(pool.max_size(), 1);
 
-    Ok(conn)
+        pool.get()
+            .unwrap()
+            .execute_batch("CREATE TABLE t (v TEXT)")
+            .unwrap();
+        pool.get()
+            .unwrap()
+            .execute("INSERT INTO t VALUES ('kept')", ())
+            .unwrap();
+    }
 }
@@ -1,15 +1,89 @@
+use rusqlite::OptionalExtension;
 use serde_rusqlite::*;
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
+    crypto::{
+        envelope::{EnvelopeVersion, SecretBinding},
+        master_key::PrivateMasterKey,
+    },
     error::{Result, SealboxError},
-    repo::{Secret, SecretRepo},
+    repo::{
+        BatchOutcome, NamespaceCount, NewSecret, Secret, SecretCounts, SecretFilter, SecretInfo,
+        SecretPage, SecretRepo, SecretValue, sqlite::ensure_column,
+    },
 };
 
+/// Columns selected for a full `Secret` row, in the order expected by `from_row`
+const SECRET_COLUMNS: &str = "namespace,
+                key,
+                version,
+                encrypted_data,
+                encrypted_data_key,
+                master_key_id,
+                created_at,
+                updated_at,
+                expires_at,
+                metadata,
+                envelope_version,
+                tags,
+                rotate_after,
+                description,
+                user_agent,
+                bound_version";
+
+/// Reads of every live unexpired version of `s.key`, as the `read_count, last_read_at` of a
+/// `SecretInfo` row; `?1` must be the current time
+const KEY_READ_COLUMNS: &str = "(
+                    SELECT COALESCE(SUM(r.read_count), 0) FROM secrets r
+                    WHERE r.key = s.key
+                        AND r.deleted_at IS NULL
+                        AND (r.expires_at IS NULL OR r.expires_at > ?1)
+                ),
+                (
+                    SELECT MAX(r.last_read_at) FROM secrets r
+                    WHERE r.key = s.key
+                        AND r.deleted_at IS NULL
+                        AND (r.expires_at IS NULL OR r.expires_at > ?1)
+                )";
+
+/// Latest live unexpired version of each key; `?1` must be the current time
+const LATEST_LIVE_VERSION: &str = "s.deleted_at IS NULL
+                AND (s.expires_at IS NULL OR s.expires_at > ?1)
+                AND s.version = (
+                    SELECT MAX(latest.version) FROM secrets latest
+                    WHERE latest.key = s.key
+                        AND latest.deleted_at IS NULL
+                        AND (latest.expires_at IS NULL OR latest.expires_at > ?1)
+                )";
+
 #[derive(Debug, Clone)]
 pub(crate) struct SqliteSecretRepo;
 
+/// Map a `key, version, created_at, updated_at, expires_at, tags, deleted_at, locked,
+/// rotate_after, description, user_agent, read_count, last_read_at` row to `SecretInfo`
+fn secret_info_from_row(row: &rusqlite::Row) -> rusqlite::Result<SecretInfo> {
+    let tags: String = row.get(5)?;
+    Ok(SecretInfo {
+        key: row.get(0)?,
+        version: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+        expires_at: row.get(4)?,
+        tags: serde_json::from_str(&tags).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        deleted_at: row.get(6)?,
+        locked: row.get(7)?,
+        rotate_after: row.get(8)?,
+        description: row.get(9)?,
+        user_agent: row.get(10)?,
+        read_count: row.get(11)?,
+        last_read_at: row.get(12)?,
+    })
+}
+
 impl SqliteSecretRepo {
     pub fn init_table(conn: &rusqlite::Connection) -> Result<()> {
         // Initialize database table structure
@@ -25,16 +99,56 @@ impl SqliteSecretRepo {
                 updated_at INTEGER NOT NULL,
                 expires_at INTEGER,
                 metadata TEXT,
+                envelope_version INTEGER NOT NULL DEFAULT 1,
+                tags TEXT NOT NULL DEFAULT '[]',
+                deleted_at INTEGER,
+                locked INTEGER NOT NULL DEFAULT 0,
+                rotate_after INTEGER,
+                description TEXT,
+                user_agent TEXT,
+                bound_version INTEGER,
+                read_count INTEGER NOT NULL DEFAULT 0,
+                last_read_at INTEGER,
                 PRIMARY KEY (namespace, key, version)
             )",
             (),
         )?;
 
+        // Rows written before envelope versioning existed use the v1 format
+        ensure_column(
+            conn,
+            "secrets",
+            "envelope_version",
+            "INTEGER NOT NULL DEFAULT 1",
+        )?;
+        ensure_column(conn, "secrets", "tags", "TEXT NOT NULL DEFAULT '[]'")?;
+        ensure_column(conn, "secrets", "deleted_at", "INTEGER")?;
+        ensure_column(conn, "secrets", "locked", "INTEGER NOT NULL DEFAULT 0")?;
+        ensure_column(conn, "secrets", "rotate_after", "INTEGER")?;
+        ensure_column(conn, "secrets", "description", "TEXT")?;
+        ensure_column(conn, "secrets", "user_agent", "TEXT")?;
+        ensure_column(conn, "secrets", "bound_version", "INTEGER")?;
+        ensure_column(conn, "secrets", "read_count", "INTEGER NOT NULL DEFAULT 0")?;
+        ensure_column(conn, "secrets", "last_read_at", "INTEGER")?;
+
         Ok(())
     }
 }
 
 impl SqliteSecretRepo {
+    /// Refuse to remove a locked version; missing versions are left to the caller to report
+    fn ensure_unlocked(conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        let locked = conn
+            .prepare_cached("SELECT 1 FROM secrets WHERE key = ?1 AND version = ?2 AND locked = 1")?
+            .exists((key, version))?;
+        if locked {
+            return Err(SealboxError::Conflict(format!(
+                "Secret '{key}' version {version} is locked"
+            )));
+        }
+        Ok(())
+    }
+
     /// Helper function to check expiry and clean up expired secrets atomically
     fn check_and_cleanup_expired(
         tx: &rusqlite::Transaction,
@@ -43,15 +157,18 @@ impl SqliteSecretRepo {
         if let Some(expires_at) = secret.expires_at {
             let now = time::OffsetDateTime::now_utc().unix_timestamp();
             if expires_at < now {
-                // Secret has expired, delete it atomically within transaction
-                tx.execute(
-                    "DELETE FROM secrets WHERE key = ?1 AND version = ?2",
+                // Secret has expired, delete it atomically within transaction.
+                // Locked snapshots are kept, though they can no longer be read.
+                let deleted = tx.execute(
+                    "DELETE FROM secrets WHERE key = ?1 AND version = ?2 AND locked = 0",
                     [&secret.key, &secret.version.to_string()],
                 )?;
-                info!(
-                    "Secret '{}' version {} has expired and been deleted",
-                    secret.key, secret.version
-                );
+                if deleted > 0 {
+                    info!(
+                        "Secret '{}' version {} has expired and been deleted",
+                        secret.key, secret.version
+                    );
+                }
                 return Ok(None);
             }
         }
@@ -96,27 +213,107 @@ impl SqliteSecretRepo {
     }
 }
 
+/// Insert `new_secret` as the next version of its key using the caller's transaction
+fn insert_new_version(
+    tx: &rusqlite::Connection,
+    master_key: crate::repo::MasterKey,
+    new_secret: NewSecret,
+) -> Result<Secret> {
+    let key = &new_secret.key;
+    let version = next_version(tx, key)?;
+    let mut secret = match new_secret.value {
+        SecretValue::Plaintext { data, envelope } => {
+            Secret::new(key, &data, master_key, version, new_secret.ttl, envelope)?
+        }
+        SecretValue::Encrypted(encrypted) => {
+            Secret::from_encrypted(key, encrypted, version, new_secret.ttl)
+        }
+    };
+    secret.tags = new_secret.tags;
+    secret.rotate_after = new_secret.rotate_after;
+    secret.description = match new_secret.description {
+        Some(description) => Some(description).filter(|d| !d.is_empty()),
+        None => latest_description(tx, key)?,
+    };
+    secret.user_agent = new_secret.user_agent;
+    insert_secret(tx, &secret)?;
+    Ok(secret)
+}
+
+/// Version number the next save of `key` gets
+fn next_version(tx: &rusqlite::Connection, key: &str) -> Result<i32> {
+    let mut stmt = tx.prepare("SELECT COALESCE(MAX(version), 0) FROM secrets WHERE key = ?1")?;
+    let latest_version: i32 = stmt.query_one([key], |row| row.get(0))?;
+    Ok(latest_version + 1)
+}
+
+/// Description of the newest version of `key`, which a new version inherits
+fn latest_description(tx: &rusqlite::Connection, key: &str) -> Result<Option<String>> {
+    let description = tx
+        .query_row(
+            "SELECT description FROM secrets WHERE key = ?1 ORDER BY version DESC LIMIT 1",
+            [key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(description.flatten())
+}
+
+fn insert_secret(tx: &rusqlite::Connection, secret: &Secret) -> Result<()> {
+    tx.execute(
+        "INSERT INTO secrets (
+          namespace,
+          key,
+          version,
+          encrypted_data,
+          encrypted_data_key,
+          master_key_id,
+          created_at,
+          updated_at,
+          expires_at,
+          metadata,
+          envelope_version,
+          tags,
+          rotate_after,
+          description,
+          user_agent,
+          bound_version
+      ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        (
+            &secret.namespace,
+            &secret.key,
+            &secret.version,
+            &secret.encrypted_data,
+            &secret.encrypted_data_key,
+            &secret.master_key_id,
+            &secret.created_at,
+            &secret.updated_at,
+            &secret.expires_at,
+            &secret.metadata,
+            &secret.envelope_version,
+            serde_json::json!(secret.tags).to_string(),
+            &secret.rotate_after,
+            &secret.description,
+            &secret.user_agent,
+            &secret.bound_version,
+        ),
+    )?;
+    Ok(())
+}
+
 impl SecretRepo for SqliteSecretRepo {
     fn get_secret(&self, conn: &mut rusqlite::Connection, key: &str) -> Result<Secret> {
         info!("get_secret: key={}", key);
 
         self.get_secret_with_query(
             conn,
-            "SELECT
-                namespace,
-                key,
-                version,
-                encrypted_data,
-                encrypted_data_key,
-                master_key_id,
-                created_at,
-                updated_at,
-                expires_at,
-                metadata
-            FROM secrets
-            WHERE key = ?1
-            ORDER BY version DESC
-            LIMIT 1",
+            &format!(
+                "SELECT {SECRET_COLUMNS}
+                FROM secrets
+                WHERE key = ?1 AND deleted_at IS NULL
+                ORDER BY version DESC
+                LIMIT 1"
+            ),
             [key],
             key,
         )
@@ -132,20 +329,12 @@ impl SecretRepo for SqliteSecretRepo {
 
         self.get_secret_with_query(
             conn,
-            "SELECT
-                namespace,
-                key,
-                version,
-                encrypted_data,
-                encrypted_data_key,
-                master_key_id,
-                created_at,
-                updated_at,
-                expires_at,
-                metadata
-            FROM secrets
-            WHERE key = ?1 AND version = ?2
-            LIMIT 1",
+            &format!(
+                "SELECT {SECRET_COLUMNS}
+                FROM secrets
+                WHERE key = ?1 AND version = ?2 AND deleted_at IS NULL
+                LIMIT 1"
+            ),
             (key, version),
             key,
         )
@@ -154,54 +343,42 @@ impl SecretRepo for SqliteSecretRepo {
     fn create_new_version(
         &self,
         conn: &mut rusqlite::Connection,
-        key: &str,
-        data: &str,
         master_key: crate::repo::MasterKey,
-        ttl: Option<i64>,
+        secret: NewSecret,
     ) -> Result<Secret> {
         info!("create_new_version");
 
         let tx = conn.transaction()?;
+        let secret = insert_new_version(&tx, master_key, secret)?;
+        tx.commit()?;
 
-        let next_version = {
-            let mut stmt =
-                tx.prepare("SELECT COALESCE(MAX(version), 0) FROM secrets WHERE key = ?1")?;
-            let latest_version: i32 = stmt.query_one([key], |row| row.get(0))?;
-            latest_version + 1
-        };
+        Ok(secret)
+    }
 
-        let secret = Secret::new(key, data, master_key, next_version, ttl)?;
+    fn create_new_versions_batch(
+        &self,
+        conn: &mut rusqlite::Connection,
+        master_key: &crate::repo::MasterKey,
+        entries: Vec<NewSecret>,
+    ) -> Result<BatchOutcome> {
+        info!("create_new_versions_batch: {} entries", entries.len());
 
-        tx.execute(
-            "INSERT INTO secrets (
-              namespace,
-              key,
-              version,
-              encrypted_data,
-              encrypted_data_key,
-              master_key_id,
-              created_at,
-              updated_at,
-              expires_at,
-              metadata
-          ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            (
-                &secret.namespace,
-                &secret.key,
-                &secret.version,
-                &secret.encrypted_data,
-                &secret.encrypted_data_key,
-                &secret.master_key_id,
-                &secret.created_at,
-                &secret.updated_at,
-                &secret.expires_at,
-                &secret.metadata,
-            ),
-        )?;
+        let tx = conn.transaction()?;
+        let results: Vec<_> = entries
+            .into_iter()
+            .map(|entry| {
+                let key = entry.key.clone();
+                (key, insert_new_version(&tx, master_key.clone(), entry))
+            })
+            .collect();
 
-        tx.commit()?;
+        // Dropping the transaction without committing rolls it back
+        let committed = results.iter().all(|(_, result)| result.is_ok());
+        if committed {
+            tx.commit()?;
+        }
 
-        Ok(secret)
+        Ok(BatchOutcome { committed, results })
     }
 
     fn delete_secret_by_version(
@@ -211,6 +388,7 @@ impl SecretRepo for SqliteSecretRepo {
         version: i32,
     ) -> Result<()> {
         info!("delete_secret_by_version");
+        Self::ensure_unlocked(conn, key, version)?;
         let changed = conn.execute(
             "DELETE FROM secrets WHERE key = ?1 AND version = ?2",
             (key, version),
@@ -221,13 +399,94 @@ impl SecretRepo for SqliteSecretRepo {
         Ok(())
     }
 
-    fn fetch_secrets_by_master_key(
+    fn shred_secret_by_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<()> {
+        info!("shred_secret_by_version: key={}, version={}", key, version);
+        let secure_delete: bool =
+            conn.pragma_query_value(None, "secure_delete", |row| row.get(0))?;
+
+        let tx = conn.transaction()?;
+        Self::ensure_unlocked(&tx, key, version)?;
+        let changed = tx.execute(
+            "UPDATE secrets
+             SET encrypted_data = zeroblob(length(encrypted_data)),
+                 encrypted_data_key = zeroblob(length(encrypted_data_key))
+             WHERE key = ?1 AND version = ?2",
+            (key, version),
+        )?;
+        if changed == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        // Make SQLite zero the freed pages instead of leaving the zeroed row's old copy behind
+        tx.pragma_update(None, "secure_delete", true)?;
+        tx.execute(
+            "DELETE FROM secrets WHERE key = ?1 AND version = ?2",
+            (key, version),
+        )?;
+        tx.commit()?;
+
+        // Fold the WAL, which still holds pre-shred page images, back into the database
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        conn.pragma_update(None, "secure_delete", secure_delete)?;
+        Ok(())
+    }
+
+    fn soft_delete(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        info!("soft_delete: key={}, version={}", key, version);
+        Self::ensure_unlocked(conn, key, version)?;
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let changed = conn.execute(
+            "UPDATE secrets SET deleted_at = ?1
+             WHERE key = ?2 AND version = ?3 AND deleted_at IS NULL",
+            (now, key, version),
+        )?;
+        if changed == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        Ok(())
+    }
+
+    fn restore_secret(
         &self,
         conn: &rusqlite::Connection,
-        master_key_id: &Uuid,
-    ) -> Result<Vec<Secret>> {
-        let mut stmt = conn.prepare(
-            "SELECT
+        key: &str,
+        version: Option<i32>,
+    ) -> Result<usize> {
+        info!("restore_secret: key={}, version={:?}", key, version);
+        let restored = conn.execute(
+            "UPDATE secrets SET deleted_at = NULL
+             WHERE key = ?1 AND (?2 IS NULL OR version = ?2) AND deleted_at IS NOT NULL",
+            (key, version),
+        )?;
+        if restored == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        Ok(restored)
+    }
+
+    fn promote_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<Secret> {
+        info!("promote_version: key={}, version={}", key, version);
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        let tx = conn.transaction()?;
+        let next_version: i32 = tx.query_row(
+            "SELECT COALESCE(MAX(version), 0) + 1 FROM secrets WHERE key = ?1",
+            [key],
+            |row| row.get(0),
+        )?;
+        // Expired or soft-deleted versions cannot be promoted, as they cannot be read either.
+        // The copy keeps the version its payload is bound to, so it still decrypts.
+        let copied = tx.execute(
+            "INSERT INTO secrets (
                 namespace,
                 key,
                 version,
@@ -237,10 +496,166 @@ impl SecretRepo for SqliteSecretRepo {
                 created_at,
                 updated_at,
                 expires_at,
-                metadata
+                metadata,
+                envelope_version,
+                tags,
+                description,
+                bound_version
+            )
+            SELECT namespace, key, ?3, encrypted_data, encrypted_data_key, master_key_id,
+                ?4, ?4, expires_at, metadata, envelope_version, tags, description,
+                COALESCE(bound_version, version)
             FROM secrets
-            WHERE master_key_id = ?1",
+            WHERE key = ?1 AND version = ?2 AND deleted_at IS NULL
+                AND (expires_at IS NULL OR expires_at >= ?4)",
+            (key, version, next_version, now),
+        )?;
+        if copied == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        let secret = tx.query_row_and_then(
+            &format!("SELECT {SECRET_COLUMNS} FROM secrets WHERE key = ?1 AND version = ?2"),
+            (key, next_version),
+            |row| from_row::<Secret>(row).map_err(|e| SealboxError::DatabaseError(e.to_string())),
+        )?;
+        tx.commit()?;
+        Ok(secret)
+    }
+
+    fn lock_version(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        info!("lock_version: key={}, version={}", key, version);
+        let changed = conn.execute(
+            "UPDATE secrets SET locked = 1 WHERE key = ?1 AND version = ?2 AND deleted_at IS NULL",
+            (key, version),
+        )?;
+        if changed == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        Ok(())
+    }
+
+    fn record_read(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        conn.execute(
+            "UPDATE secrets SET read_count = read_count + 1, last_read_at = ?3
+            WHERE key = ?1 AND version = ?2",
+            (key, version, now),
+        )?;
+        Ok(())
+    }
+
+    fn set_expiry(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: i32,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        info!(
+            "set_expiry: key={}, version={}, expires_at={:?}",
+            key, version, expires_at
+        );
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let changed = conn.execute(
+            "UPDATE secrets SET expires_at = ?3, updated_at = ?4
+            WHERE key = ?1 AND version = ?2 AND deleted_at IS NULL
+            AND (expires_at IS NULL OR expires_at > ?4)",
+            (key, version, expires_at, now),
+        )?;
+        if changed == 0 {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+        Ok(())
+    }
+
+    fn rename_secret(
+        &self,
+        conn: &mut rusqlite::Connection,
+        old_key: &str,
+        new_key: &str,
+        private_key: Option<&PrivateMasterKey>,
+    ) -> Result<usize> {
+        info!("rename_secret: old_key={}, new_key={}", old_key, new_key);
+        let tx = conn.transaction()?;
+        let taken: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM secrets WHERE key = ?1)",
+            [new_key],
+            |row| row.get(0),
+        )?;
+        if taken {
+            return Err(SealboxError::Conflict(format!(
+                "Secret '{new_key}' already exists"
+            )));
+        }
+        // Payloads bound to the old key would no longer decrypt under the new one, so they
+        // are re-sealed for the new key in the same transaction
+        let secrets: Vec<Secret> = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {SECRET_COLUMNS} FROM secrets WHERE key = ?1"
+            ))?;
+            let rows = stmt.query([old_key])?;
+            from_rows::<Secret>(rows)
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| SealboxError::DatabaseError(e.to_string()))?
+        };
+        for secret in &secrets {
+            let envelope = EnvelopeVersion::try_from(secret.envelope_version)?;
+            if !envelope.params().aad {
+                continue;
+            }
+            let Some(private_key) = private_key else {
+                return Err(SealboxError::Conflict(format!(
+                    "Secret '{old_key}' is encrypted bound to its key; renaming it needs the \
+                     private master key to re-seal it under '{new_key}'"
+                )));
+            };
+            let version = secret.bound_version.unwrap_or(secret.version);
+            let rebound = envelope
+                .rebind(
+                    private_key,
+                    &secret.encrypted_data_key,
+                    &secret.encrypted_data,
+                    SecretBinding {
+                        key: old_key,
+                        version,
+                    },
+                    SecretBinding {
+                        key: new_key,
+                        version,
+                    },
+                )
+                .map_err(|err| {
+                    SealboxError::InvalidInput(format!(
+                        "Cannot re-seal version {} of '{old_key}': {err}",
+                        secret.version
+                    ))
+                })?;
+            tx.execute(
+                "UPDATE secrets SET encrypted_data = ?3 WHERE key = ?1 AND version = ?2",
+                (old_key, secret.version, rebound),
+            )?;
+        }
+        let renamed = tx.execute(
+            "UPDATE secrets SET key = ?2 WHERE key = ?1",
+            (old_key, new_key),
         )?;
+        if renamed == 0 {
+            return Err(SealboxError::SecretNotFound(old_key.to_string()));
+        }
+        tx.commit()?;
+        Ok(renamed)
+    }
+
+    fn fetch_secrets_by_master_key(
+        &self,
+        conn: &rusqlite::Connection,
+        master_key_id: &Uuid,
+    ) -> Result<Vec<Secret>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SECRET_COLUMNS}
+            FROM secrets
+            WHERE master_key_id = ?1"
+        ))?;
         // Using query() and from_rows(), the most efficient way as shown in the official example
         let rows = stmt.query([master_key_id])?;
         let secrets: Vec<Secret> = from_rows::<Secret>(rows)
@@ -249,6 +664,20 @@ impl SecretRepo for SqliteSecretRepo {
         Ok(secrets)
     }
 
+    fn fetch_all_secrets(&self, conn: &rusqlite::Connection) -> Result<Vec<Secret>> {
+        info!("fetch_all_secrets");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {SECRET_COLUMNS}
+            FROM secrets
+            ORDER BY key, version"
+        ))?;
+        let rows = stmt.query([])?;
+        let secrets: Vec<Secret> = from_rows::<Secret>(rows)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
+        Ok(secrets)
+    }
+
     fn update_secret_master_key(&self, conn: &rusqlite::Connection, secret: &Secret) -> Result<()> {
         conn.execute(
             "UPDATE secrets SET
@@ -272,66 +701,234 @@ impl SecretRepo for SqliteSecretRepo {
         info!("cleanup_expired_secrets");
         let now = time::OffsetDateTime::now_utc().unix_timestamp();
         let deleted_count = conn.execute(
-            "DELETE FROM secrets WHERE expires_at IS NOT NULL AND expires_at < ?1",
+            "DELETE FROM secrets WHERE expires_at IS NOT NULL AND expires_at < ?1 AND locked = 0",
             [now],
         )?;
         info!("Cleaned up {} expired secrets", deleted_count);
         Ok(deleted_count)
     }
 
-    fn list_secrets(&self, conn: &rusqlite::Connection) -> Result<Vec<crate::repo::SecretInfo>> {
-        info!("list_secrets");
+    fn count_secrets(&self, conn: &rusqlite::Connection) -> Result<SecretCounts> {
         let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let counts = conn.query_row(
+            "SELECT
+                (SELECT COUNT(DISTINCT key) FROM secrets WHERE deleted_at IS NULL),
+                (SELECT COUNT(*) FROM secrets
+                    WHERE expires_at IS NOT NULL AND expires_at < ?1 AND locked = 0)",
+            [now],
+            |row| {
+                Ok(SecretCounts {
+                    secrets: row.get(0)?,
+                    expired_versions: row.get(1)?,
+                })
+            },
+        )?;
+        Ok(counts)
+    }
 
-        let mut stmt = conn.prepare(
-            "SELECT 
-                key,
-                MAX(version) as version,
-                created_at,
-                MAX(updated_at) as updated_at,
-                expires_at
-            FROM secrets 
-            WHERE expires_at IS NULL OR expires_at > ?1
-            GROUP BY key
-            ORDER BY updated_at DESC",
+    fn list_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &SecretFilter,
+    ) -> Result<SecretPage> {
+        info!("list_secrets: filter={:?}", filter);
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        // An empty prefix matches every key
+        let prefix = filter.prefix.unwrap_or("");
+        // SQLite treats a negative LIMIT as no limit
+        let limit = filter.limit.map_or(-1, i64::from);
+
+        let (reads, matching, order) = if filter.deleted {
+            // Every soft-deleted unexpired version, narrowed by prefix and tag
+            (
+                "s.read_count, s.last_read_at",
+                "FROM secrets s
+                WHERE s.deleted_at IS NOT NULL
+                    AND (s.expires_at IS NULL OR s.expires_at > ?1)
+                    AND substr(s.key, 1, length(?2)) = ?2
+                    AND (?3 IS NULL OR EXISTS (SELECT 1 FROM json_each(s.tags) WHERE value = ?3))",
+                "s.deleted_at DESC, s.key, s.version DESC",
+            )
+        } else {
+            // Latest live unexpired version of each key, narrowed by prefix and tag
+            (
+                KEY_READ_COLUMNS,
+                "FROM secrets s
+                WHERE s.deleted_at IS NULL
+                    AND (s.expires_at IS NULL OR s.expires_at > ?1)
+                    AND substr(s.key, 1, length(?2)) = ?2
+                    AND s.version = (
+                        SELECT MAX(latest.version) FROM secrets latest
+                        WHERE latest.key = s.key
+                            AND latest.deleted_at IS NULL
+                            AND (latest.expires_at IS NULL OR latest.expires_at > ?1)
+                    )
+                    AND (?3 IS NULL OR EXISTS (SELECT 1 FROM json_each(s.tags) WHERE value = ?3))",
+                "s.updated_at DESC, s.key",
+            )
+        };
+
+        let total: usize = conn.query_row(
+            &format!("SELECT COUNT(*) {matching}"),
+            (now, prefix, filter.tag),
+            |row| row.get(0),
         )?;
 
-        let secret_infos = stmt
+        let mut stmt = conn.prepare(&format!(
+            "SELECT s.key, s.version, s.created_at, s.updated_at, s.expires_at, s.tags, s.deleted_at,
+                s.locked, s.rotate_after, s.description, s.user_agent, {reads}
+            {matching}
+            ORDER BY {order}
+            LIMIT ?4 OFFSET ?5"
+        ))?;
+
+        let secrets = stmt
+            .query_map(
+                (now, prefix, filter.tag, limit, filter.offset),
+                secret_info_from_row,
+            )?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
+
+        Ok(SecretPage { total, secrets })
+    }
+
+    fn list_namespaces(&self, conn: &rusqlite::Connection) -> Result<Vec<NamespaceCount>> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        // Keys starting with `/` have an empty first segment and are left out. The alias
+        // must not be `namespace`, which would group by the legacy column of that name
+        let mut stmt = conn.prepare(
+            "SELECT substr(key, 1, instr(key, '/') - 1) AS top_level, COUNT(DISTINCT key)
+            FROM secrets
+            WHERE instr(key, '/') > 1
+                AND deleted_at IS NULL
+                AND (expires_at IS NULL OR expires_at > ?1)
+            GROUP BY top_level
+            ORDER BY top_level",
+        )?;
+        let namespaces = stmt
             .query_map([now], |row| {
-                Ok(crate::repo::SecretInfo {
-                    key: row.get(0)?,
-                    version: row.get(1)?,
-                    created_at: row.get(2)?,
-                    updated_at: row.get(3)?,
-                    expires_at: row.get(4)?,
+                Ok(NamespaceCount {
+                    namespace: row.get(0)?,
+                    secrets: row.get(1)?,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
-
-        Ok(secret_infos)
+        Ok(namespaces)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::crypto::master_key::generate_key_pair;
-    use crate::repo::MasterKey;
+    fn list_secret_versions(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+    ) -> Result<Vec<SecretInfo>> {
+        info!("list_secret_versions: key={}", key);
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
 
-    fn setup_test_db() -> rusqlite::Connection {
-        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
-        SqliteSecretRepo::init_table(&conn).expect("Should initialize tables");
-        conn
-    }
+        let mut stmt = conn.prepare(
+            "SELECT
+                key,
+                version,
+                created_at,
+                updated_at,
+                expires_at,
+                tags,
+                deleted_at,
+                locked,
+                rotate_after,
+                description,
+                user_agent,
+                read_count,
+                last_read_at
+            FROM secrets
+            WHERE key = ?1 AND deleted_at IS NULL AND (expires_at IS NULL OR expires_at > ?2)
+            ORDER BY version DESC",
+        )?;
 
-    fn create_test_master_key() -> MasterKey {
-        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
-        MasterKey::new(public_pem).expect("Should create master key")
-    }
+        let versions = stmt
+            .query_map((key, now), secret_info_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
 
-    #[test]
-    fn test_init_table() {
+        if versions.is_empty() {
+            return Err(SealboxError::SecretNotFound(key.to_string()));
+        }
+
+        Ok(versions)
+    }
+
+    fn list_rotation_due(&self, conn: &rusqlite::Connection) -> Result<Vec<SecretInfo>> {
+        info!("list_rotation_due");
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        // Only the latest live version counts: saving a new version is the rotation
+        let mut stmt = conn.prepare(&format!(
+            "SELECT s.key, s.version, s.created_at, s.updated_at, s.expires_at, s.tags, s.deleted_at,
+                s.locked, s.rotate_after, s.description, s.user_agent, {KEY_READ_COLUMNS}
+            FROM secrets s
+            WHERE {LATEST_LIVE_VERSION}
+                AND s.rotate_after IS NOT NULL AND s.rotate_after <= ?1
+            ORDER BY s.rotate_after, s.key"
+        ))?;
+
+        let secrets = stmt
+            .query_map([now], secret_info_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
+
+        Ok(secrets)
+    }
+
+    fn list_unused_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<SecretInfo>> {
+        info!("list_unused_secrets: days={}", days);
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let cutoff = now - i64::from(days) * 86400;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT * FROM (
+                SELECT s.key, s.version, s.created_at, s.updated_at, s.expires_at, s.tags,
+                    s.deleted_at, s.locked, s.rotate_after, s.description, s.user_agent,
+                    {KEY_READ_COLUMNS} AS last_read
+                FROM secrets s
+                WHERE {LATEST_LIVE_VERSION}
+            )
+            WHERE last_read IS NULL OR last_read < ?2
+            ORDER BY last_read IS NOT NULL, last_read, key"
+        ))?;
+
+        let secrets = stmt
+            .query_map((now, cutoff), secret_info_from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| SealboxError::DatabaseError(e.to_string()))?;
+
+        Ok(secrets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{envelope::CURRENT_ENVELOPE_VERSION, master_key::generate_key_pair};
+    use crate::repo::MasterKey;
+
+    fn setup_test_db() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+        SqliteSecretRepo::init_table(&conn).expect("Should initialize tables");
+        conn
+    }
+
+    fn create_test_master_key() -> MasterKey {
+        let (_, public_pem) = generate_key_pair().expect("Should generate key pair");
+        MasterKey::new(public_pem).expect("Should create master key")
+    }
+
+    #[test]
+    fn test_init_table() {
         let conn = setup_test_db();
 
         // Verify table exists and has correct structure
@@ -357,6 +954,14 @@ mod tests {
             "updated_at",
             "expires_at",
             "metadata",
+            "envelope_version",
+            "tags",
+            "rotate_after",
+            "description",
+            "user_agent",
+            "bound_version",
+            "read_count",
+            "last_read_at",
         ];
 
         for expected_col in expected_columns {
@@ -367,6 +972,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_init_table_migrates_legacy_rows() {
+        let conn = rusqlite::Connection::open_in_memory().expect("Should create in-memory DB");
+
+        // Table layout used before envelope versioning was introduced
+        conn.execute(
+            "CREATE TABLE secrets (
+                namespace TEXT NOT NULL,
+                key TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                encrypted_data BLOB NOT NULL,
+                encrypted_data_key BLOB NOT NULL,
+                master_key_id BLOB NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                expires_at INTEGER,
+                metadata TEXT,
+                PRIMARY KEY (namespace, key, version)
+            )",
+            (),
+        )
+        .expect("Should create legacy table");
+        conn.execute(
+            "INSERT INTO secrets (namespace, key, version, encrypted_data, encrypted_data_key,
+                master_key_id, created_at, updated_at)
+             VALUES ('', 'legacy', 1, x'00', x'00', x'00', 0, 0)",
+            (),
+        )
+        .expect("Should insert legacy row");
+
+        SqliteSecretRepo::init_table(&conn).expect("Should migrate legacy table");
+        // Running the migration twice must be a no-op
+        SqliteSecretRepo::init_table(&conn).expect("Should tolerate existing column");
+
+        let envelope_version: i32 = conn
+            .query_row(
+                "SELECT envelope_version FROM secrets WHERE key = 'legacy'",
+                [],
+                |row| row.get(0),
+            )
+            .expect("Should read envelope version");
+        assert_eq!(envelope_version, 1);
+
+        let tags: String = conn
+            .query_row("SELECT tags FROM secrets WHERE key = 'legacy'", [], |row| {
+                row.get(0)
+            })
+            .expect("Should read tags");
+        assert_eq!(tags, "[]");
+    }
+
     #[test]
     fn test_create_and_get_secret() {
         let conn = setup_test_db();
@@ -379,7 +1035,11 @@ mod tests {
         // Create secret
         let mut conn_mut = conn;
         let created_secret = repo
-            .create_new_version(&mut conn_mut, secret_key, secret_data, master_key, None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key,
+                NewSecret::new(secret_key, secret_data, CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret");
 
         // Get secret back
@@ -428,10 +1088,8 @@ mod tests {
         let secret_v1 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 1",
                 master_key.clone(),
-                None,
+                NewSecret::new(secret_key, "data version 1", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 1");
 
@@ -439,10 +1097,8 @@ mod tests {
         let secret_v2 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 2",
                 master_key,
-                None,
+                NewSecret::new(secret_key, "data version 2", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 2");
 
@@ -471,20 +1127,16 @@ mod tests {
         let secret_v1 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 1",
                 master_key.clone(),
-                None,
+                NewSecret::new(secret_key, "data version 1", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 1");
 
         let _secret_v2 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 2",
                 master_key,
-                None,
+                NewSecret::new(secret_key, "data version 2", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 2");
 
@@ -525,20 +1177,16 @@ mod tests {
         let _secret_v1 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 1",
                 master_key.clone(),
-                None,
+                NewSecret::new(secret_key, "data version 1", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 1");
 
         let secret_v2 = repo
             .create_new_version(
                 &mut conn_mut,
-                secret_key,
-                "data version 2",
                 master_key,
-                None,
+                NewSecret::new(secret_key, "data version 2", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create version 2");
 
@@ -572,6 +1220,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_shred_secret_overwrites_before_delete() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let secret = repo
+            .create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new("shredded", "value", CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret");
+        repo.create_new_version(
+            &mut conn,
+            master_key,
+            NewSecret::new("kept", "value", CURRENT_ENVELOPE_VERSION),
+        )
+        .expect("Should create secret");
+
+        // Capture what the row held at the moment it was deleted
+        conn.execute_batch(
+            "CREATE TABLE deleted_rows (encrypted_data BLOB, encrypted_data_key BLOB);
+             CREATE TRIGGER capture_deleted BEFORE DELETE ON secrets BEGIN
+                 INSERT INTO deleted_rows VALUES (OLD.encrypted_data, OLD.encrypted_data_key);
+             END;",
+        )
+        .unwrap();
+
+        repo.shred_secret_by_version(&mut conn, "shredded", 1)
+            .expect("Should shred secret");
+
+        let (data, data_key): (Vec<u8>, Vec<u8>) = conn
+            .query_row(
+                "SELECT encrypted_data, encrypted_data_key FROM deleted_rows",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(data.len(), secret.encrypted_data.len());
+        assert!(data.iter().all(|byte| *byte == 0));
+        assert_eq!(data_key.len(), secret.encrypted_data_key.len());
+        assert!(data_key.iter().all(|byte| *byte == 0));
+
+        assert!(repo.get_secret(&mut conn, "shredded").is_err());
+        assert!(repo.get_secret(&mut conn, "kept").is_ok());
+
+        let err = repo
+            .shred_secret_by_version(&mut conn, "shredded", 1)
+            .expect_err("Shredding twice should fail");
+        assert!(matches!(err, SealboxError::SecretNotFound(_)));
+    }
+
     #[test]
     fn test_fetch_secrets_by_master_key() {
         let conn = setup_test_db();
@@ -582,15 +1283,27 @@ mod tests {
         // Create secrets with different master keys
         let mut conn_mut = conn;
         let _secret1 = repo
-            .create_new_version(&mut conn_mut, "secret1", "data1", master_key1.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key1.clone(),
+                NewSecret::new("secret1", "data1", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret1");
 
         let _secret2 = repo
-            .create_new_version(&mut conn_mut, "secret2", "data2", master_key1.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key1.clone(),
+                NewSecret::new("secret2", "data2", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret2");
 
         let _secret3 = repo
-            .create_new_version(&mut conn_mut, "secret3", "data3", master_key2.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key2.clone(),
+                NewSecret::new("secret3", "data3", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret3");
 
         // Fetch secrets by master key 1
@@ -623,7 +1336,11 @@ mod tests {
         // Create a secret
         let mut conn_mut = conn;
         let mut secret = repo
-            .create_new_version(&mut conn_mut, "test-secret", "test-data", master_key, None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key,
+                NewSecret::new("test-secret", "test-data", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret");
 
         // Modify the secret
@@ -658,10 +1375,11 @@ mod tests {
         let secret = repo
             .create_new_version(
                 &mut conn_mut,
-                "ttl-secret",
-                "temporary-data",
                 master_key,
-                ttl,
+                NewSecret {
+                    ttl,
+                    ..NewSecret::new("ttl-secret", "temporary-data", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create secret with TTL");
 
@@ -687,10 +1405,11 @@ mod tests {
         let _secret = repo
             .create_new_version(
                 &mut conn_mut,
-                "expired-secret",
-                "temporary-data",
                 master_key,
-                Some(1i64), // 1 second
+                NewSecret {
+                    ttl: Some(1i64), // 1 second
+                    ..NewSecret::new("expired-secret", "temporary-data", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create secret with short TTL");
 
@@ -718,10 +1437,15 @@ mod tests {
         let secret = repo
             .create_new_version(
                 &mut conn_mut,
-                "expired-secret-v",
-                "temporary-data",
                 master_key,
-                Some(1i64), // 1 second
+                NewSecret {
+                    ttl: Some(1i64), // 1 second
+                    ..NewSecret::new(
+                        "expired-secret-v",
+                        "temporary-data",
+                        CURRENT_ENVELOPE_VERSION,
+                    )
+                },
             )
             .expect("Should create secret with short TTL");
 
@@ -750,40 +1474,41 @@ mod tests {
         let _expired1 = repo
             .create_new_version(
                 &mut conn_mut,
-                "expired1",
-                "data1",
                 master_key.clone(),
-                Some(1i64), // 1 second
+                NewSecret {
+                    ttl: Some(1i64), // 1 second
+                    ..NewSecret::new("expired1", "data1", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create expired secret 1");
 
         let _expired2 = repo
             .create_new_version(
                 &mut conn_mut,
-                "expired2",
-                "data2",
                 master_key.clone(),
-                Some(1i64), // 1 second
+                NewSecret {
+                    ttl: Some(1i64), // 1 second
+                    ..NewSecret::new("expired2", "data2", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create expired secret 2");
 
         let _permanent = repo
             .create_new_version(
                 &mut conn_mut,
-                "permanent",
-                "permanent-data",
                 master_key.clone(),
-                None, // No TTL
+                NewSecret::new("permanent", "permanent-data", CURRENT_ENVELOPE_VERSION),
             )
             .expect("Should create permanent secret");
 
         let _long_lived = repo
             .create_new_version(
                 &mut conn_mut,
-                "long-lived",
-                "long-data",
                 master_key,
-                Some(3600i64), // 1 hour
+                NewSecret {
+                    ttl: Some(3600i64), // 1 hour
+                    ..NewSecret::new("long-lived", "long-data", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create long-lived secret");
 
@@ -827,16 +1552,21 @@ mod tests {
 
         // Create only non-expired secrets
         let _permanent = repo
-            .create_new_version(&mut conn_mut, "permanent", "data", master_key.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new("permanent", "data", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create permanent secret");
 
         let _long_lived = repo
             .create_new_version(
                 &mut conn_mut,
-                "long-lived",
-                "data",
                 master_key,
-                Some(3600i64),
+                NewSecret {
+                    ttl: Some(3600i64),
+                    ..NewSecret::new("long-lived", "data", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create long-lived secret");
 
@@ -865,30 +1595,46 @@ mod tests {
 
         // Create several secrets
         let _secret1 = repo
-            .create_new_version(&mut conn_mut, "secret1", "data1", master_key.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new("secret1", "data1", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret1");
 
         let _secret2 = repo
             .create_new_version(
                 &mut conn_mut,
-                "secret2",
-                "data2",
                 master_key.clone(),
-                Some(3600),
+                NewSecret {
+                    ttl: Some(3600),
+                    ..NewSecret::new("secret2", "data2", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create secret2 with TTL");
 
         let _secret3 = repo
-            .create_new_version(&mut conn_mut, "secret3", "data3", master_key.clone(), None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new("secret3", "data3", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret3");
 
         // Create multiple versions of secret1
         let _secret1_v2 = repo
-            .create_new_version(&mut conn_mut, "secret1", "data1-v2", master_key, None)
+            .create_new_version(
+                &mut conn_mut,
+                master_key,
+                NewSecret::new("secret1", "data1-v2", CURRENT_ENVELOPE_VERSION),
+            )
             .expect("Should create secret1 version 2");
 
         // List all secrets
-        let secret_list = repo.list_secrets(&conn_mut).expect("Should list secrets");
+        let secret_list = repo
+            .list_secrets(&conn_mut, &SecretFilter::default())
+            .expect("Should list secrets")
+            .secrets;
 
         // Should return 3 unique secrets (secret1, secret2, secret3)
         assert_eq!(secret_list.len(), 3);
@@ -927,10 +1673,11 @@ mod tests {
         let _expired_secret = repo
             .create_new_version(
                 &mut conn_mut,
-                "expired-secret",
-                "temporary-data",
                 master_key.clone(),
-                Some(1i64), // 1 second
+                NewSecret {
+                    ttl: Some(1i64), // 1 second
+                    ..NewSecret::new("expired-secret", "temporary-data", CURRENT_ENVELOPE_VERSION)
+                },
             )
             .expect("Should create expired secret");
 
@@ -938,10 +1685,12 @@ mod tests {
         let _permanent_secret = repo
             .create_new_version(
                 &mut conn_mut,
-                "permanent-secret",
-                "permanent-data",
                 master_key,
-                None,
+                NewSecret::new(
+                    "permanent-secret",
+                    "permanent-data",
+                    CURRENT_ENVELOPE_VERSION,
+                ),
             )
             .expect("Should create permanent secret");
 
@@ -949,9 +1698,878 @@ mod tests {
         std::thread::sleep(std::time::Duration::from_secs(2));
 
         // List secrets should only return the permanent one
-        let secret_list = repo.list_secrets(&conn_mut).expect("Should list secrets");
+        let secret_list = repo
+            .list_secrets(&conn_mut, &SecretFilter::default())
+            .expect("Should list secrets")
+            .secrets;
 
         assert_eq!(secret_list.len(), 1);
         assert_eq!(secret_list[0].key, "permanent-secret");
     }
+
+    #[test]
+    fn test_list_secrets_by_prefix() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let mut conn_mut = conn;
+
+        for key in ["prod/db", "prod/api", "prod_legacy", "staging/db"] {
+            repo.create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new(key, "data", CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret");
+        }
+
+        let page = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    prefix: Some("prod/"),
+                    ..Default::default()
+                },
+            )
+            .expect("Should list secrets by prefix");
+
+        assert_eq!(page.total, 2);
+        let mut keys: Vec<&str> = page.secrets.iter().map(|s| s.key.as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["prod/api", "prod/db"]);
+
+        // A prefix with LIKE wildcards is matched literally
+        let page = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    prefix: Some("prod_"),
+                    ..Default::default()
+                },
+            )
+            .expect("Should list secrets by prefix");
+        assert_eq!(page.total, 1);
+        assert_eq!(page.secrets[0].key, "prod_legacy");
+    }
+
+    #[test]
+    fn test_list_namespaces() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        for key in [
+            "team-a/db",
+            "team-a/api",
+            "team-a/api",
+            "team-b/nested/db",
+            "toplevel",
+            "/rooted",
+            "gone/db",
+        ] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new(key, "data", CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret");
+        }
+        repo.soft_delete(&conn, "gone/db", 1)
+            .expect("Should soft-delete secret");
+
+        let namespaces = repo.list_namespaces(&conn).expect("Should list namespaces");
+        assert_eq!(
+            namespaces,
+            vec![
+                NamespaceCount {
+                    namespace: "team-a".to_string(),
+                    secrets: 2,
+                },
+                NamespaceCount {
+                    namespace: "team-b".to_string(),
+                    secrets: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_read_and_list_unused() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        for key in ["fresh", "fresh", "stale", "never"] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret");
+        }
+        // A read of an older version still counts for the secret
+        repo.record_read(&conn, "fresh", 1)
+            .expect("Should record read");
+        repo.record_read(&conn, "fresh", 2)
+            .expect("Should record read");
+        repo.record_read(&conn, "stale", 1)
+            .expect("Should record read");
+        conn.execute(
+            "UPDATE secrets SET last_read_at = ?1 WHERE key = 'stale'",
+            [now - 100 * 86400],
+        )
+        .unwrap();
+
+        let page = repo
+            .list_secrets(&conn, &SecretFilter::default())
+            .expect("Should list secrets");
+        let fresh = page.secrets.iter().find(|s| s.key == "fresh").unwrap();
+        assert_eq!((fresh.version, fresh.read_count), (2, 2));
+        assert!(fresh.last_read_at.unwrap() >= now);
+
+        let versions = repo
+            .list_secret_versions(&conn, "fresh")
+            .expect("Should list versions");
+        assert_eq!(versions[0].read_count, 1);
+
+        let unused = repo
+            .list_unused_secrets(&conn, 90)
+            .expect("Should list unused secrets");
+        let keys: Vec<_> = unused.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, vec!["never", "stale"]);
+        assert_eq!(unused[1].read_count, 1);
+        assert_eq!(unused[1].last_read_at, Some(now - 100 * 86400));
+
+        let unused = repo
+            .list_unused_secrets(&conn, 365)
+            .expect("Should list unused secrets");
+        assert_eq!(unused.len(), 1);
+    }
+
+    #[test]
+    fn test_list_secrets_limit_offset() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let mut conn_mut = conn;
+
+        for i in 0..5 {
+            repo.create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new(&format!("key-{i}"), "data", CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret");
+        }
+
+        let first = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    limit: Some(2),
+                    offset: 0,
+                    ..Default::default()
+                },
+            )
+            .expect("Should list first page");
+        let second = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    limit: Some(2),
+                    offset: 2,
+                    ..Default::default()
+                },
+            )
+            .expect("Should list second page");
+        let last = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    limit: Some(2),
+                    offset: 4,
+                    ..Default::default()
+                },
+            )
+            .expect("Should list last page");
+
+        // The total counts every match, not just the page
+        assert_eq!(first.total, 5);
+        assert_eq!(first.secrets.len(), 2);
+        assert_eq!(second.secrets.len(), 2);
+        assert_eq!(last.secrets.len(), 1);
+
+        // Pages do not overlap
+        let mut keys: Vec<String> = first
+            .secrets
+            .iter()
+            .chain(&second.secrets)
+            .chain(&last.secrets)
+            .map(|s| s.key.clone())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 5);
+    }
+
+    #[test]
+    fn test_create_new_versions_batch() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        repo.create_new_version(
+            &mut conn,
+            master_key.clone(),
+            NewSecret::new("existing", "v1", CURRENT_ENVELOPE_VERSION),
+        )
+        .expect("Should create secret");
+
+        let entries = vec![
+            NewSecret::new("existing", "v2", CURRENT_ENVELOPE_VERSION),
+            NewSecret {
+                ttl: Some(3600),
+                tags: vec!["env=prod".to_string()],
+                ..NewSecret::new("fresh", "v1", CURRENT_ENVELOPE_VERSION)
+            },
+        ];
+        let outcome = repo
+            .create_new_versions_batch(&mut conn, &master_key, entries)
+            .expect("Batch should run");
+
+        assert!(outcome.committed);
+        let versions: Vec<i32> = outcome
+            .results
+            .iter()
+            .map(|(_, result)| result.as_ref().expect("Entry should succeed").version)
+            .collect();
+        assert_eq!(versions, vec![2, 1]);
+        assert_eq!(repo.get_secret(&mut conn, "fresh").unwrap().version, 1);
+    }
+
+    #[test]
+    fn test_create_new_versions_batch_rolls_back_on_failure() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        // Make inserts of one particular key fail
+        conn.execute_batch(
+            "CREATE TRIGGER reject_bad BEFORE INSERT ON secrets WHEN NEW.key = 'bad'
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+        )
+        .expect("Should create trigger");
+
+        let entries = ["good", "bad"]
+            .into_iter()
+            .map(|key| NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION))
+            .collect();
+        let outcome = repo
+            .create_new_versions_batch(&mut conn, &master_key, entries)
+            .expect("Batch should run");
+
+        assert!(!outcome.committed);
+        assert!(outcome.results[0].1.is_ok());
+        assert!(outcome.results[1].1.is_err());
+
+        // The successful entry was rolled back with the rest
+        assert!(repo.get_secret(&mut conn, "good").is_err());
+    }
+
+    #[test]
+    fn test_tags_roundtrip_and_filter() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let mut conn_mut = conn;
+
+        let prod_tags = vec!["env=prod".to_string(), "team=payments".to_string()];
+        let created = repo
+            .create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret {
+                    tags: prod_tags.clone(),
+                    ..NewSecret::new("stripe-key", "data", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .expect("Should create tagged secret");
+        assert_eq!(created.tags, prod_tags);
+        repo.create_new_version(
+            &mut conn_mut,
+            master_key.clone(),
+            NewSecret {
+                tags: vec!["env=staging".to_string()],
+                ..NewSecret::new("staging-key", "data", CURRENT_ENVELOPE_VERSION)
+            },
+        )
+        .expect("Should create tagged secret");
+        repo.create_new_version(
+            &mut conn_mut,
+            master_key,
+            NewSecret::new("untagged", "data", CURRENT_ENVELOPE_VERSION),
+        )
+        .expect("Should create untagged secret");
+
+        // Tags survive the database roundtrip
+        let fetched = repo
+            .get_secret(&mut conn_mut, "stripe-key")
+            .expect("Should get secret");
+        assert_eq!(fetched.tags, prod_tags);
+
+        let page = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    tag: Some("env=prod"),
+                    ..Default::default()
+                },
+            )
+            .expect("Should list secrets by tag");
+        assert_eq!(page.total, 1);
+        assert_eq!(page.secrets[0].key, "stripe-key");
+        assert_eq!(page.secrets[0].tags, prod_tags);
+
+        // Tags must match exactly, not as substrings
+        let page = repo
+            .list_secrets(
+                &conn_mut,
+                &SecretFilter {
+                    tag: Some("env"),
+                    ..Default::default()
+                },
+            )
+            .expect("Should list secrets by tag");
+        assert_eq!(page.total, 0);
+    }
+
+    #[test]
+    fn test_tag_filter_uses_latest_version() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let mut conn_mut = conn;
+
+        repo.create_new_version(
+            &mut conn_mut,
+            master_key.clone(),
+            NewSecret {
+                tags: vec!["env=staging".to_string()],
+                ..NewSecret::new("moved", "v1", CURRENT_ENVELOPE_VERSION)
+            },
+        )
+        .expect("Should create version 1");
+        repo.create_new_version(
+            &mut conn_mut,
+            master_key,
+            NewSecret {
+                tags: vec!["env=prod".to_string()],
+                ..NewSecret::new("moved", "v2", CURRENT_ENVELOPE_VERSION)
+            },
+        )
+        .expect("Should create version 2");
+
+        let staging = SecretFilter {
+            tag: Some("env=staging"),
+            ..Default::default()
+        };
+        let prod = SecretFilter {
+            tag: Some("env=prod"),
+            ..Default::default()
+        };
+        assert_eq!(repo.list_secrets(&conn_mut, &staging).unwrap().total, 0);
+        let page = repo.list_secrets(&conn_mut, &prod).unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.secrets[0].version, 2);
+    }
+
+    #[test]
+    fn test_list_secret_versions() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let mut conn_mut = conn;
+
+        for data in ["data-v1", "data-v2", "data-v3"] {
+            repo.create_new_version(
+                &mut conn_mut,
+                master_key.clone(),
+                NewSecret::new("audited", data, CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret version");
+        }
+        repo.create_new_version(
+            &mut conn_mut,
+            master_key,
+            NewSecret::new("other", "other-data", CURRENT_ENVELOPE_VERSION),
+        )
+        .expect("Should create unrelated secret");
+
+        let versions = repo
+            .list_secret_versions(&conn_mut, "audited")
+            .expect("Should list secret versions");
+
+        // Every version is returned, newest first, and other keys are excluded
+        let numbers: Vec<i32> = versions.iter().map(|v| v.version).collect();
+        assert_eq!(numbers, vec![3, 2, 1]);
+        assert!(versions.iter().all(|v| v.key == "audited"));
+    }
+
+    #[test]
+    fn test_list_secret_versions_not_found() {
+        let conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+
+        let result = repo.list_secret_versions(&conn, "nonexistent");
+        assert!(matches!(result, Err(SealboxError::SecretNotFound(_))));
+    }
+
+    #[test]
+    fn test_soft_delete_and_restore() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        for data in ["data-v1", "data-v2"] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new("bin", data, CURRENT_ENVELOPE_VERSION),
+            )
+            .expect("Should create secret version");
+        }
+
+        repo.soft_delete(&conn, "bin", 2)
+            .expect("Should soft-delete version 2");
+
+        // The deleted version is skipped, so the previous one becomes the latest
+        let latest = repo.get_secret(&mut conn, "bin").unwrap();
+        assert_eq!(latest.version, 1);
+        assert!(matches!(
+            repo.get_secret_by_version(&mut conn, "bin", 2),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+        let versions = repo.list_secret_versions(&conn, "bin").unwrap();
+        assert_eq!(versions.len(), 1);
+        assert!(matches!(
+            repo.soft_delete(&conn, "bin", 2),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+
+        let live = repo.list_secrets(&conn, &SecretFilter::default()).unwrap();
+        assert_eq!(live.secrets[0].version, 1);
+        let bin = repo
+            .list_secrets(
+                &conn,
+                &SecretFilter {
+                    deleted: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(bin.total, 1);
+        assert_eq!(bin.secrets[0].version, 2);
+        assert!(bin.secrets[0].deleted_at.is_some());
+
+        // New versions never reuse the number of a deleted one
+        let v3 = repo
+            .create_new_version(
+                &mut conn,
+                master_key,
+                NewSecret::new("bin", "data-v3", CURRENT_ENVELOPE_VERSION),
+            )
+            .unwrap();
+        assert_eq!(v3.version, 3);
+
+        let restored = repo.restore_secret(&conn, "bin", Some(2)).unwrap();
+        assert_eq!(restored, 1);
+        assert_eq!(repo.list_secret_versions(&conn, "bin").unwrap().len(), 3);
+        assert!(matches!(
+            repo.restore_secret(&conn, "bin", None),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_soft_deleted_key_hidden_from_list() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        repo.create_new_version(
+            &mut conn,
+            master_key,
+            NewSecret::new("gone", "data", CURRENT_ENVELOPE_VERSION),
+        )
+        .unwrap();
+        repo.soft_delete(&conn, "gone", 1).unwrap();
+
+        let live = repo.list_secrets(&conn, &SecretFilter::default()).unwrap();
+        assert_eq!(live.total, 0);
+        assert!(matches!(
+            repo.get_secret(&mut conn, "gone"),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+
+        // Purging works on a version that is already in the recycle bin
+        repo.delete_secret_by_version(&conn, "gone", 1)
+            .expect("Should purge soft-deleted version");
+        assert!(matches!(
+            repo.restore_secret(&conn, "gone", None),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_rename_secret() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        // Rows written before payloads were bound to their key can be renamed
+        for data in ["v1", "v2"] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new("old", data, EnvelopeVersion::V1),
+            )
+            .unwrap();
+        }
+        repo.soft_delete(&conn, "old", 1).unwrap();
+        repo.create_new_version(
+            &mut conn,
+            master_key,
+            NewSecret::new("taken", "data", EnvelopeVersion::V1),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            repo.rename_secret(&mut conn, "old", "taken", None),
+            Err(SealboxError::Conflict(_))
+        ));
+        assert!(matches!(
+            repo.rename_secret(&mut conn, "missing", "new", None),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+
+        // Secrets whose payloads are bound to their key are re-sealed with the private key
+        let (private_pem, public_pem) = generate_key_pair().unwrap();
+        let private_key: PrivateMasterKey = private_pem.parse().unwrap();
+        repo.create_new_version(
+            &mut conn,
+            MasterKey::new(public_pem).unwrap(),
+            NewSecret::new("bound", "data", CURRENT_ENVELOPE_VERSION),
+        )
+        .unwrap();
+        assert!(matches!(
+            repo.rename_secret(&mut conn, "bound", "rebound", None),
+            Err(SealboxError::Conflict(_))
+        ));
+        assert_eq!(
+            repo.rename_secret(&mut conn, "bound", "rebound", Some(&private_key))
+                .unwrap(),
+            1
+        );
+        let rebound = repo.get_secret(&mut conn, "rebound").unwrap();
+        let plaintext = CURRENT_ENVELOPE_VERSION
+            .open(
+                &private_key,
+                &rebound.encrypted_data_key,
+                &rebound.encrypted_data,
+                SecretBinding {
+                    key: "rebound",
+                    version: 1,
+                },
+            )
+            .unwrap();
+        assert_eq!(plaintext, b"data");
+
+        // Versions in the recycle bin move with the rest
+        assert_eq!(
+            repo.rename_secret(&mut conn, "old", "new", None).unwrap(),
+            2
+        );
+        assert_eq!(repo.get_secret(&mut conn, "new").unwrap().version, 2);
+        assert_eq!(repo.restore_secret(&conn, "new", Some(1)).unwrap(), 1);
+        assert!(matches!(
+            repo.get_secret(&mut conn, "old"),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_promote_version() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        let tags = vec!["env=prod".to_string()];
+        let v1 = repo
+            .create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret {
+                    tags: tags.clone(),
+                    ..NewSecret::new("rollback", "data-v1", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .unwrap();
+        repo.create_new_version(
+            &mut conn,
+            master_key,
+            NewSecret::new("rollback", "data-v2", CURRENT_ENVELOPE_VERSION),
+        )
+        .unwrap();
+
+        let promoted = repo.promote_version(&mut conn, "rollback", 1).unwrap();
+        assert_eq!(promoted.version, 3);
+        assert_eq!(promoted.encrypted_data, v1.encrypted_data);
+        assert_eq!(promoted.encrypted_data_key, v1.encrypted_data_key);
+        assert_eq!(promoted.tags, tags);
+        assert_eq!(promoted.bound_version, Some(1));
+
+        let latest = repo.get_secret(&mut conn, "rollback").unwrap();
+        assert_eq!(latest.version, 3);
+        assert_eq!(latest.encrypted_data, v1.encrypted_data);
+
+        // Promoting a copy keeps the version the payload was originally sealed as
+        let recopied = repo.promote_version(&mut conn, "rollback", 3).unwrap();
+        assert_eq!(recopied.version, 4);
+        assert_eq!(recopied.bound_version, Some(1));
+
+        repo.soft_delete(&conn, "rollback", 2).unwrap();
+        assert!(matches!(
+            repo.promote_version(&mut conn, "rollback", 2),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+        assert!(matches!(
+            repo.promote_version(&mut conn, "rollback", 9),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_locked_version_survives_delete_and_cleanup() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+
+        for data in ["v1", "v2"] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret {
+                    ttl: Some(3600),
+                    ..NewSecret::new("snapshot", data, CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .unwrap();
+        }
+        repo.lock_version(&conn, "snapshot", 1).unwrap();
+        assert!(matches!(
+            repo.lock_version(&conn, "snapshot", 9),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+
+        let versions = repo.list_secret_versions(&conn, "snapshot").unwrap();
+        assert_eq!(
+            versions.iter().map(|v| v.locked).collect::<Vec<_>>(),
+            vec![false, true]
+        );
+
+        assert!(matches!(
+            repo.delete_secret_by_version(&conn, "snapshot", 1),
+            Err(SealboxError::Conflict(_))
+        ));
+        assert!(matches!(
+            repo.shred_secret_by_version(&mut conn, "snapshot", 1),
+            Err(SealboxError::Conflict(_))
+        ));
+        assert!(matches!(
+            repo.soft_delete(&conn, "snapshot", 1),
+            Err(SealboxError::Conflict(_))
+        ));
+
+        // Expire both versions: only the unlocked one is cleaned up
+        conn.execute(
+            "UPDATE secrets SET expires_at = 1 WHERE key = 'snapshot'",
+            (),
+        )
+        .unwrap();
+        assert_eq!(repo.cleanup_expired_secrets(&conn).unwrap(), 1);
+        assert!(matches!(
+            repo.get_secret_by_version(&mut conn, "snapshot", 1),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+        let remaining: i32 = conn
+            .query_row(
+                "SELECT version FROM secrets WHERE key = 'snapshot'",
+                (),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+
+    #[test]
+    fn test_set_expiry() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        for key in ["extended", "expired"] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret {
+                    ttl: Some(60),
+                    ..NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .unwrap();
+        }
+
+        repo.set_expiry(&conn, "extended", 1, Some(now + 3600))
+            .unwrap();
+        assert_eq!(
+            repo.get_secret(&mut conn, "extended").unwrap().expires_at,
+            Some(now + 3600)
+        );
+        repo.set_expiry(&conn, "extended", 1, None).unwrap();
+        assert_eq!(
+            repo.get_secret(&mut conn, "extended").unwrap().expires_at,
+            None
+        );
+
+        // An expired version waiting for cleanup cannot be brought back
+        conn.execute(
+            "UPDATE secrets SET expires_at = ?1 WHERE key = 'expired'",
+            [now - 1],
+        )
+        .unwrap();
+        assert!(matches!(
+            repo.set_expiry(&conn, "expired", 1, None),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+        assert!(matches!(
+            repo.set_expiry(&conn, "extended", 9, None),
+            Err(SealboxError::SecretNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_create_new_version_writes_metadata_in_one_insert() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        // Nothing may be patched in after the insert
+        conn.execute_batch(
+            "CREATE TRIGGER reject_updates BEFORE UPDATE ON secrets
+             BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+        )
+        .expect("Should create trigger");
+
+        let v1 = repo
+            .create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret {
+                    rotate_after: Some(now + 3600),
+                    description: Some("Payments API key".to_string()),
+                    user_agent: Some("sealbox-cli/1.0".to_string()),
+                    ..NewSecret::new("api", "v1", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .expect("Should create version 1");
+        let stored = repo.get_secret(&mut conn, "api").unwrap();
+        assert_eq!(stored.rotate_after, v1.rotate_after);
+        assert_eq!(stored.description.as_deref(), Some("Payments API key"));
+        assert_eq!(stored.user_agent.as_deref(), Some("sealbox-cli/1.0"));
+
+        // Without a description the previous one is kept, an empty one clears it
+        let v2 = repo
+            .create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new("api", "v2", CURRENT_ENVELOPE_VERSION),
+            )
+            .unwrap();
+        assert_eq!(v2.description.as_deref(), Some("Payments API key"));
+        assert_eq!(v2.rotate_after, None);
+        let v3 = repo
+            .create_new_version(
+                &mut conn,
+                master_key,
+                NewSecret {
+                    description: Some(String::new()),
+                    ..NewSecret::new("api", "v3", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .unwrap();
+        assert_eq!(repo.get_secret(&mut conn, "api").unwrap().description, None);
+        assert_eq!(v3.description, None);
+    }
+
+    #[test]
+    fn test_list_rotation_due() {
+        let mut conn = setup_test_db();
+        let repo = SqliteSecretRepo;
+        let master_key = create_test_master_key();
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+
+        for (key, rotate_after) in [
+            ("overdue", Some(now - 60)),
+            ("due_later", Some(now + 3600)),
+            ("no_policy", None),
+            ("rotated", Some(now - 60)),
+        ] {
+            repo.create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret {
+                    rotate_after,
+                    ..NewSecret::new(key, "value", CURRENT_ENVELOPE_VERSION)
+                },
+            )
+            .unwrap();
+        }
+
+        // Saving a new version rotates the secret and clears the reminder
+        let rotated = repo
+            .create_new_version(
+                &mut conn,
+                master_key.clone(),
+                NewSecret::new("rotated", "new value", CURRENT_ENVELOPE_VERSION),
+            )
+            .unwrap();
+        assert_eq!(rotated.rotate_after, None);
+
+        let due = repo.list_rotation_due(&conn).unwrap();
+        assert_eq!(
+            due.iter().map(|s| s.key.as_str()).collect::<Vec<_>>(),
+            vec!["overdue"]
+        );
+        assert_eq!(due[0].rotate_after, Some(now - 60));
+
+        let secret = repo.get_secret(&mut conn, "due_later").unwrap();
+        assert_eq!(secret.rotate_after, Some(now + 3600));
+
+        // Rotating the last overdue secret empties the list
+        repo.create_new_version(
+            &mut conn,
+            master_key,
+            NewSecret::new("overdue", "new value", CURRENT_ENVELOPE_VERSION),
+        )
+        .unwrap();
+        assert!(repo.list_rotation_due(&conn).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,455 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    config::SharedConfig,
+    crypto::master_key::PrivateMasterKey,
+    error::Result,
+    repo::{
+        AuditEntry, AuditFilter, AuditRepo, BatchOutcome, HealthRepo, MasterKey, MasterKeyFilter,
+        MasterKeyPage, MasterKeyRepo, NamespaceCount, NewAuditEntry, NewSecret, Secret,
+        SecretCounts, SecretFilter, SecretInfo, SecretPage, SecretRepo,
+    },
+};
+
+/// Source of the current time, replaceable in tests
+pub(crate) type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// Times repository calls and warns about those slower than `SEALBOX_SLOW_QUERY_MS`
+#[derive(Clone)]
+pub(crate) struct QueryTimer {
+    config: SharedConfig,
+    clock: Clock,
+}
+
+impl QueryTimer {
+    pub(crate) fn new(config: SharedConfig) -> Self {
+        Self::with_clock(config, Arc::new(Instant::now))
+    }
+
+    pub(crate) fn with_clock(config: SharedConfig, clock: Clock) -> Self {
+        Self { config, clock }
+    }
+
+    /// Run `query`, logging `operation` and the elapsed time if it was slow.
+    ///
+    /// Only the operation name is logged, never arguments, which may contain secret values.
+    fn time<T>(&self, operation: &'static str, query: impl FnOnce() -> T) -> T {
+        let Some(threshold_ms) = self.config.snapshot().slow_query_ms else {
+            return query();
+        };
+
+        let start = (self.clock)();
+        let result = query();
+        let elapsed = (self.clock)().saturating_duration_since(start);
+        if elapsed >= Duration::from_millis(threshold_ms.into()) {
+            warn!(
+                operation,
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms,
+                "Slow query"
+            );
+        }
+        result
+    }
+}
+
+/// Repository wrapper timing every call of the repository it wraps
+pub(crate) struct Timed<R> {
+    inner: R,
+    timer: QueryTimer,
+}
+
+impl<R> Timed<R> {
+    pub(crate) fn new(inner: R, timer: QueryTimer) -> Self {
+        Self { inner, timer }
+    }
+}
+
+impl<R: SecretRepo> SecretRepo for Timed<R> {
+    fn get_secret(&self, conn: &mut rusqlite::Connection, key: &str) -> Result<Secret> {
+        self.timer
+            .time("secret.get", || self.inner.get_secret(conn, key))
+    }
+
+    fn get_secret_by_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<Secret> {
+        self.timer.time("secret.get_by_version", || {
+            self.inner.get_secret_by_version(conn, key, version)
+        })
+    }
+
+    fn create_new_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        master_key: MasterKey,
+        secret: NewSecret,
+    ) -> Result<Secret> {
+        self.timer.time("secret.create_version", || {
+            self.inner.create_new_version(conn, master_key, secret)
+        })
+    }
+
+    fn create_new_versions_batch(
+        &self,
+        conn: &mut rusqlite::Connection,
+        master_key: &MasterKey,
+        entries: Vec<NewSecret>,
+    ) -> Result<BatchOutcome> {
+        self.timer.time("secret.create_versions_batch", || {
+            self.inner
+                .create_new_versions_batch(conn, master_key, entries)
+        })
+    }
+
+    fn delete_secret_by_version(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<()> {
+        self.timer.time("secret.delete", || {
+            self.inner.delete_secret_by_version(conn, key, version)
+        })
+    }
+
+    fn shred_secret_by_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<()> {
+        self.timer.time("secret.shred", || {
+            self.inner.shred_secret_by_version(conn, key, version)
+        })
+    }
+
+    fn soft_delete(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        self.timer.time("secret.soft_delete", || {
+            self.inner.soft_delete(conn, key, version)
+        })
+    }
+
+    fn restore_secret(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: Option<i32>,
+    ) -> Result<usize> {
+        self.timer.time("secret.restore", || {
+            self.inner.restore_secret(conn, key, version)
+        })
+    }
+
+    fn promote_version(
+        &self,
+        conn: &mut rusqlite::Connection,
+        key: &str,
+        version: i32,
+    ) -> Result<Secret> {
+        self.timer.time("secret.promote", || {
+            self.inner.promote_version(conn, key, version)
+        })
+    }
+
+    fn lock_version(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        self.timer.time("secret.lock", || {
+            self.inner.lock_version(conn, key, version)
+        })
+    }
+
+    fn set_expiry(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+        version: i32,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        self.timer.time("secret.set_expiry", || {
+            self.inner.set_expiry(conn, key, version, expires_at)
+        })
+    }
+
+    fn rename_secret(
+        &self,
+        conn: &mut rusqlite::Connection,
+        old_key: &str,
+        new_key: &str,
+        private_key: Option<&PrivateMasterKey>,
+    ) -> Result<usize> {
+        self.timer.time("secret.rename", || {
+            self.inner
+                .rename_secret(conn, old_key, new_key, private_key)
+        })
+    }
+
+    fn fetch_secrets_by_master_key(
+        &self,
+        conn: &rusqlite::Connection,
+        master_key_id: &Uuid,
+    ) -> Result<Vec<Secret>> {
+        self.timer.time("secret.fetch_by_master_key", || {
+            self.inner.fetch_secrets_by_master_key(conn, master_key_id)
+        })
+    }
+
+    fn fetch_all_secrets(&self, conn: &rusqlite::Connection) -> Result<Vec<Secret>> {
+        self.timer
+            .time("secret.fetch_all", || self.inner.fetch_all_secrets(conn))
+    }
+
+    fn update_secret_master_key(&self, conn: &rusqlite::Connection, secret: &Secret) -> Result<()> {
+        self.timer.time("secret.update_master_key", || {
+            self.inner.update_secret_master_key(conn, secret)
+        })
+    }
+
+    fn cleanup_expired_secrets(&self, conn: &rusqlite::Connection) -> Result<usize> {
+        self.timer.time("secret.cleanup_expired", || {
+            self.inner.cleanup_expired_secrets(conn)
+        })
+    }
+
+    fn count_secrets(&self, conn: &rusqlite::Connection) -> Result<SecretCounts> {
+        self.timer
+            .time("secret.count", || self.inner.count_secrets(conn))
+    }
+
+    fn list_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &SecretFilter,
+    ) -> Result<SecretPage> {
+        self.timer
+            .time("secret.list", || self.inner.list_secrets(conn, filter))
+    }
+
+    fn list_namespaces(&self, conn: &rusqlite::Connection) -> Result<Vec<NamespaceCount>> {
+        self.timer
+            .time("secret.namespaces", || self.inner.list_namespaces(conn))
+    }
+
+    fn record_read(&self, conn: &rusqlite::Connection, key: &str, version: i32) -> Result<()> {
+        self.timer.time("secret.record_read", || {
+            self.inner.record_read(conn, key, version)
+        })
+    }
+
+    fn list_unused_secrets(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<SecretInfo>> {
+        self.timer.time("secret.list_unused", || {
+            self.inner.list_unused_secrets(conn, days)
+        })
+    }
+
+    fn list_secret_versions(
+        &self,
+        conn: &rusqlite::Connection,
+        key: &str,
+    ) -> Result<Vec<SecretInfo>> {
+        self.timer.time("secret.list_versions", || {
+            self.inner.list_secret_versions(conn, key)
+        })
+    }
+
+    fn list_rotation_due(&self, conn: &rusqlite::Connection) -> Result<Vec<SecretInfo>> {
+        self.timer.time("secret.list_rotation_due", || {
+            self.inner.list_rotation_due(conn)
+        })
+    }
+}
+
+impl<R: MasterKeyRepo> MasterKeyRepo for Timed<R> {
+    fn create_master_key(&self, conn: &rusqlite::Connection, key: &MasterKey) -> Result<()> {
+        self.timer.time("master_key.create", || {
+            self.inner.create_master_key(conn, key)
+        })
+    }
+
+    fn fetch_all_master_keys(&self, conn: &rusqlite::Connection) -> Result<Vec<MasterKey>> {
+        self.timer.time("master_key.fetch_all", || {
+            self.inner.fetch_all_master_keys(conn)
+        })
+    }
+
+    fn list_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &MasterKeyFilter,
+    ) -> Result<MasterKeyPage> {
+        self.timer.time("master_key.list", || {
+            self.inner.list_master_keys(conn, filter)
+        })
+    }
+
+    fn fetch_public_key(
+        &self,
+        conn: &rusqlite::Connection,
+        master_key_id: &Uuid,
+    ) -> Result<Option<String>> {
+        self.timer.time("master_key.fetch_public_key", || {
+            self.inner.fetch_public_key(conn, master_key_id)
+        })
+    }
+
+    fn get_valid_master_key(&self, conn: &rusqlite::Connection) -> Result<MasterKey> {
+        self.timer.time("master_key.get_valid", || {
+            self.inner.get_valid_master_key(conn)
+        })
+    }
+
+    fn count_master_keys(&self, conn: &rusqlite::Connection) -> Result<usize> {
+        self.timer
+            .time("master_key.count", || self.inner.count_master_keys(conn))
+    }
+
+    fn mark_used(&self, conn: &rusqlite::Connection, master_key_id: &Uuid) -> Result<()> {
+        self.timer.time("master_key.mark_used", || {
+            self.inner.mark_used(conn, master_key_id)
+        })
+    }
+
+    fn fetch_stale_master_keys(
+        &self,
+        conn: &rusqlite::Connection,
+        days: u32,
+    ) -> Result<Vec<MasterKey>> {
+        self.timer.time("master_key.fetch_stale", || {
+            self.inner.fetch_stale_master_keys(conn, days)
+        })
+    }
+}
+
+impl<R: HealthRepo> HealthRepo for Timed<R> {
+    fn check_health(&self, conn: &rusqlite::Connection) -> Result<bool> {
+        self.timer
+            .time("health.check", || self.inner.check_health(conn))
+    }
+
+    fn database_size(&self, conn: &rusqlite::Connection) -> Result<u64> {
+        self.timer
+            .time("health.database_size", || self.inner.database_size(conn))
+    }
+
+    fn backup_into(&self, conn: &rusqlite::Connection, path: &std::path::Path) -> Result<()> {
+        self.timer
+            .time("health.backup", || self.inner.backup_into(conn, path))
+    }
+}
+
+impl<R: AuditRepo> AuditRepo for Timed<R> {
+    fn record(&self, conn: &rusqlite::Connection, entry: &NewAuditEntry) -> Result<()> {
+        self.timer
+            .time("audit.record", || self.inner.record(conn, entry))
+    }
+
+    fn list_entries(
+        &self,
+        conn: &rusqlite::Connection,
+        filter: &AuditFilter,
+    ) -> Result<Vec<AuditEntry>> {
+        self.timer
+            .time("audit.list", || self.inner.list_entries(conn, filter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::SealboxConfig,
+        repo::{SqliteHealthRepo, SqliteSecretRepo},
+    };
+    use std::{
+        io,
+        sync::{
+            Mutex,
+            atomic::{AtomicU64, Ordering},
+        },
+    };
+
+    /// A clock that moves forward by `step` every time it is read
+    fn stepping_clock(step: Duration) -> Clock {
+        let start = Instant::now();
+        let reads = AtomicU64::new(0);
+        Arc::new(move || start + step * reads.fetch_add(1, Ordering::SeqCst) as u32)
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Run a health check through a timed repo and return what was logged
+    fn logs_for(slow_query_ms: Option<u32>, step: Duration) -> String {
+        let timer = QueryTimer::with_clock(
+            SharedConfig::new(SealboxConfig {
+                slow_query_ms,
+                ..SealboxConfig::default()
+            }),
+            stepping_clock(step),
+        );
+        let repo = Timed::new(SqliteHealthRepo, timer);
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(repo.check_health(&conn).unwrap());
+        });
+
+        String::from_utf8(logs.0.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn test_slow_query_is_logged() {
+        let logs = logs_for(Some(100), Duration::from_millis(250));
+        assert!(logs.contains("Slow query"), "{logs}");
+        assert!(logs.contains("operation=\"health.check\""), "{logs}");
+        assert!(logs.contains("elapsed_ms=250"), "{logs}");
+    }
+
+    #[test]
+    fn test_fast_or_untimed_query_is_not_logged() {
+        assert!(!logs_for(Some(1_000), Duration::from_millis(250)).contains("Slow query"));
+        assert!(!logs_for(None, Duration::from_secs(60)).contains("Slow query"));
+    }
+
+    #[test]
+    fn test_timed_repo_passes_results_through() {
+        let timer = QueryTimer::new(SharedConfig::new(SealboxConfig::default()));
+        let repo = Timed::new(SqliteSecretRepo, timer);
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        SqliteSecretRepo::init_table(&conn).unwrap();
+
+        assert!(matches!(
+            repo.get_secret(&mut conn, "missing"),
+            Err(crate::error::SealboxError::SecretNotFound(_))
+        ));
+    }
+}
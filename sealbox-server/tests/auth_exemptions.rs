@@ -0,0 +1,47 @@
+mod common;
+
+use common::TestServer;
+use reqwest::StatusCode;
+use sealbox_server::config::SealboxConfig;
+
+#[tokio::test]
+async fn test_configured_exempt_paths_skip_auth() {
+    let server = TestServer::start_with(SealboxConfig {
+        auth_exempt_paths: vec!["/healthz/live".to_string(), "/v1/info".to_string()],
+        ..SealboxConfig::default()
+    })
+    .await;
+
+    let get_without_token = |path: &str| {
+        server
+            .client
+            .get(format!("{}{}", server.base_url, path))
+            .send()
+    };
+
+    assert_eq!(
+        get_without_token("/v1/info").await.unwrap().status(),
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_without_token("/healthz/live").await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    // Removed from the default list, so readiness now needs a token
+    assert_eq!(
+        get_without_token("/healthz/ready").await.unwrap().status(),
+        StatusCode::UNAUTHORIZED
+    );
+    let response = server
+        .request(reqwest::Method::GET, "/healthz/ready")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+        get_without_token("/v1/secrets").await.unwrap().status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
@@ -0,0 +1,121 @@
+//! Fixture serving the full application over a real socket.
+//!
+//! Tests talk to it with `reqwest` exactly like the CLI does, so routing, middleware
+//! and (de)serialization are all exercised.
+
+// Each test crate uses its own subset of the fixture
+#![allow(dead_code)]
+
+use std::{net::SocketAddr, str::FromStr};
+
+use reqwest::{Client, Method, RequestBuilder};
+use sealbox_server::{
+    config::{SealboxConfig, SharedConfig},
+    create_app,
+    crypto::{
+        envelope::{EnvelopeVersion, SecretBinding},
+        master_key::{PrivateMasterKey, generate_key_pair},
+    },
+};
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+pub const AUTH_TOKEN: &str = "integration-token";
+
+pub struct TestServer {
+    pub base_url: String,
+    pub client: Client,
+    // Holds the SQLite file; removed when the server is dropped
+    _store_dir: TempDir,
+}
+
+impl TestServer {
+    /// Serve `create_app` on an ephemeral localhost port backed by a fresh database
+    pub async fn start() -> Self {
+        Self::start_with(SealboxConfig::default()).await
+    }
+
+    /// Like `start`, keeping the settings of `config` other than token, store and address
+    pub async fn start_with(config: SealboxConfig) -> Self {
+        let store_dir = TempDir::new().expect("Should create temp dir");
+        let store_path = store_dir.path().join("sealbox.db");
+        let config = SealboxConfig {
+            auth_token: AUTH_TOKEN.to_string(),
+            store_path: store_path.to_str().expect("UTF-8 temp path").to_string(),
+            listen_addr: "127.0.0.1:0".to_string(),
+            ..config
+        };
+
+        let app = create_app(&SharedConfig::new(config)).expect("Should build app");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Should bind ephemeral port");
+        let addr = listener.local_addr().expect("Should have local address");
+        let app = app.into_make_service_with_connect_info::<SocketAddr>();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("Server should run");
+        });
+
+        Self {
+            base_url: format!("http://{addr}"),
+            client: Client::new(),
+            _store_dir: store_dir,
+        }
+    }
+
+    /// Request `path` with the server's auth token
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{path}", self.base_url))
+            .bearer_auth(AUTH_TOKEN)
+    }
+
+    /// Generate an RSA key pair, register its public half and return `(id, private_pem)`
+    pub async fn register_master_key(&self) -> (String, String) {
+        let (private_pem, public_pem) = generate_key_pair().expect("Should generate key pair");
+        let response = self
+            .request(Method::POST, "/v1/master-key")
+            .json(&json!({ "public_key": public_pem }))
+            .send()
+            .await
+            .expect("Request should succeed");
+        assert!(response.status().is_success(), "{response:?}");
+
+        let body: Value = response.json().await.expect("JSON body");
+        let id = body["id"].as_str().expect("Master key id").to_string();
+        (id, private_pem)
+    }
+}
+
+/// Decrypt a `GET /v1/secrets/{key}` response body the way the CLI does
+pub fn decrypt_secret(private_pem: &str, secret: &Value) -> Vec<u8> {
+    let bytes = |field: &str| -> Vec<u8> {
+        secret[field]
+            .as_array()
+            .unwrap_or_else(|| panic!("Missing {field}"))
+            .iter()
+            .map(|byte| byte.as_u64().expect("Byte value") as u8)
+            .collect()
+    };
+
+    let private_key = PrivateMasterKey::from_str(private_pem).expect("Valid private key");
+    let envelope =
+        EnvelopeVersion::try_from(secret["envelope_version"].as_i64().unwrap_or(1) as i32)
+            .expect("Supported envelope version");
+    let version = secret["bound_version"]
+        .as_i64()
+        .or_else(|| secret["version"].as_i64())
+        .expect("Secret version") as i32;
+    let binding = SecretBinding {
+        key: secret["key"].as_str().expect("Secret key"),
+        version,
+    };
+    envelope
+        .open(
+            &private_key,
+            &bytes("encrypted_data_key"),
+            &bytes("encrypted_data"),
+            binding,
+        )
+        .expect("Should decrypt secret")
+}
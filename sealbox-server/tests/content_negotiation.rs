@@ -0,0 +1,46 @@
+mod common;
+
+use common::TestServer;
+use reqwest::{
+    Method, StatusCode,
+    header::{ACCEPT, CONTENT_TYPE},
+};
+
+#[tokio::test]
+async fn test_accept_selects_response_encoding() {
+    let server = TestServer::start().await;
+
+    let response = server
+        .request(Method::GET, "/v1/secrets?limit=1")
+        .header(ACCEPT, "application/yaml")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/yaml");
+    let body = response.text().await.unwrap();
+    assert!(body.starts_with("---\n"), "{body}");
+    assert!(body.contains("secrets: []"), "{body}");
+
+    // Errors follow the requested encoding too
+    let response = server
+        .request(Method::GET, "/v1/secrets/missing")
+        .header(ACCEPT, "application/msgpack")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/msgpack");
+    let body: serde_json::Value = rmp_serde::from_slice(&response.bytes().await.unwrap()).unwrap();
+    assert_eq!(body["error"]["code"], "secret_not_found");
+
+    // Unknown encodings fall back to JSON
+    let response = server
+        .request(Method::GET, "/v1/secrets")
+        .header(ACCEPT, "text/html")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert!(response.json::<serde_json::Value>().await.is_ok());
+}
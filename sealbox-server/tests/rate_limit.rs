@@ -0,0 +1,51 @@
+mod common;
+
+use common::TestServer;
+use reqwest::{Method, StatusCode, header::RETRY_AFTER};
+use sealbox_server::config::SealboxConfig;
+
+#[tokio::test]
+async fn test_rate_limit_returns_429_with_retry_after() {
+    let server = TestServer::start_with(SealboxConfig {
+        rate_limit_rps: Some(1),
+        rate_limit_burst: Some(2),
+        ..SealboxConfig::default()
+    })
+    .await;
+
+    for _ in 0..2 {
+        let response = server
+            .request(Method::GET, "/v1/secrets")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = server
+        .request(Method::GET, "/v1/secrets")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+    // Guessed tokens are limited per address, separately from the valid token
+    let response = server
+        .client
+        .get(format!("{}/v1/secrets", server.base_url))
+        .bearer_auth("guess")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // Health probes are never throttled
+    let response = server
+        .client
+        .get(format!("{}/healthz/live", server.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::TestServer;
+use reqwest::{Method, StatusCode};
+use sealbox_server::{config::SealboxConfig, crypto::master_key::generate_key_pair};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_plain_http_writes_are_refused() {
+    let server = TestServer::start_with(SealboxConfig {
+        require_https: true,
+        ..SealboxConfig::default()
+    })
+    .await;
+    let (_, public_pem) = generate_key_pair().unwrap();
+    let response = server
+        .request(Method::POST, "/v1/master-key")
+        .header("x-forwarded-proto", "https")
+        .json(&json!({ "public_key": public_pem }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let master_key: serde_json::Value = response.json().await.unwrap();
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db")
+        .json(&json!({ "secret": "hunter2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "https_required");
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db")
+        .header("x-forwarded-proto", "https")
+        .json(&json!({ "secret": "hunter2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Reads carry no plaintext and are still served
+    let response = server
+        .request(Method::GET, "/v1/secrets/db")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Master key rotation carries the old private key
+    let (old_private_pem, _) = generate_key_pair().unwrap();
+    let response = server
+        .request(Method::POST, "/v1/master-key/rotate")
+        .json(&json!({
+            "new_master_key_id": master_key["id"],
+            "old_master_key_id": master_key["id"],
+            "old_private_key_pem": old_private_pem,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+}
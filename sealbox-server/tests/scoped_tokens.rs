@@ -0,0 +1,74 @@
+mod common;
+
+use common::TestServer;
+use reqwest::{Method, StatusCode};
+use sealbox_server::config::{Scope, ScopedToken, SealboxConfig};
+
+#[tokio::test]
+async fn test_read_only_token_cannot_write() {
+    let server = TestServer::start_with(SealboxConfig {
+        scoped_tokens: vec![ScopedToken {
+            token: "read-token".to_string(),
+            scopes: vec![Scope::Read],
+        }],
+        ..SealboxConfig::default()
+    })
+    .await;
+    server.register_master_key().await;
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/scoped")
+        .json(&serde_json::json!({ "secret": "value" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let as_reader = |method: Method, path: &str| {
+        server
+            .client
+            .request(method, format!("{}{}", server.base_url, path))
+            .bearer_auth("read-token")
+    };
+
+    let response = as_reader(Method::GET, "/v1/secrets/scoped")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = as_reader(Method::PUT, "/v1/secrets/scoped")
+        .json(&serde_json::json!({ "secret": "other" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "forbidden");
+    assert_eq!(
+        body["error"]["message"],
+        "Forbidden: token lacks the write scope required for PUT /v1/secrets/scoped"
+    );
+
+    let response = as_reader(Method::DELETE, "/v1/secrets/scoped?version=1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = as_reader(Method::GET, "/v1/admin/audit")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // Unknown tokens are still unauthenticated
+    let response = server
+        .client
+        .get(format!("{}/v1/secrets/scoped", server.base_url))
+        .bearer_auth("unknown-token")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
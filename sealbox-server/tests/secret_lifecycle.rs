@@ -0,0 +1,317 @@
+mod common;
+
+use common::{TestServer, decrypt_secret};
+use reqwest::{Method, StatusCode};
+use sealbox_server::crypto::{
+    envelope::{EnvelopeVersion, SecretBinding},
+    master_key::{PublicMasterKey, generate_key_pair},
+};
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+#[tokio::test]
+async fn test_secret_lifecycle_over_http() {
+    let server = TestServer::start().await;
+    let (_, private_pem) = server.register_master_key().await;
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db-password")
+        .json(&json!({ "secret": "hunter2", "tags": ["env=prod"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let saved: Value = response.json().await.unwrap();
+    assert_eq!(saved["version"], 1);
+
+    let response = server
+        .request(Method::GET, "/v1/secrets/db-password")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key("x-request-id"));
+    let secret: Value = response.json().await.unwrap();
+    assert_eq!(decrypt_secret(&private_pem, &secret), b"hunter2");
+
+    let response = server
+        .request(
+            Method::DELETE,
+            "/v1/secrets/db-password?version=1&purge=true",
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = server
+        .request(Method::GET, "/v1/secrets/db-password")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["error"]["code"], "secret_not_found");
+    assert!(error["error"]["message"].is_string());
+    assert!(error["error"]["request_id"].is_string());
+
+    let audit: Value = server
+        .request(Method::GET, "/v1/admin/audit")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let actions: Vec<_> = audit["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            "secret.get",
+            "secret.delete",
+            "secret.get",
+            "secret.save",
+            "master_key.create"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_client_encrypted_secret_is_stored_verbatim() {
+    let server = TestServer::start().await;
+    // The client wraps data keys with its own copy of the public key; the server never
+    // hands public keys out
+    let (private_pem, public_pem) = generate_key_pair().unwrap();
+    let registered: Value = server
+        .request(Method::POST, "/v1/master-key")
+        .json(&json!({ "public_key": public_pem }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = registered["id"].as_str().unwrap();
+    let public_key = PublicMasterKey::from_str(&public_pem).unwrap();
+    let binding = SecretBinding {
+        key: "api-token",
+        version: 1,
+    };
+    let (encrypted_data, encrypted_data_key) = EnvelopeVersion::V2
+        .seal(&public_key, b"sealed by the client", binding)
+        .unwrap();
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/api-token/encrypted")
+        .json(&json!({
+            "encrypted_data": encrypted_data,
+            "encrypted_data_key": encrypted_data_key,
+            "master_key_id": id,
+            "envelope_version": 2,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let secret: Value = server
+        .request(Method::GET, "/v1/secrets/api-token")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(secret["encrypted_data"], json!(encrypted_data));
+    assert_eq!(
+        decrypt_secret(&private_pem, &secret),
+        b"sealed by the client"
+    );
+
+    // Data keys wrapped with anything but the active master key are refused
+    let response = server
+        .request(Method::PUT, "/v1/secrets/api-token/encrypted")
+        .json(&json!({
+            "encrypted_data": encrypted_data,
+            "encrypted_data_key": encrypted_data_key,
+            "master_key_id": "00000000-0000-0000-0000-000000000000",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["error"]["code"], "validation_failed");
+
+    // Clients cannot bind payloads to a version the server has yet to assign
+    let response = server
+        .request(Method::PUT, "/v1/secrets/api-token/encrypted")
+        .json(&json!({
+            "encrypted_data": encrypted_data,
+            "encrypted_data_key": encrypted_data_key,
+            "master_key_id": id,
+            "envelope_version": 4,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Blobs too short to hold a nonce and tag would break every reader
+    let response = server
+        .request(Method::PUT, "/v1/secrets/api-token/encrypted")
+        .json(&json!({
+            "encrypted_data": &encrypted_data[..11],
+            "encrypted_data_key": encrypted_data_key,
+            "master_key_id": id,
+            "envelope_version": 2,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error: Value = response.json().await.unwrap();
+    assert_eq!(error["error"]["code"], "invalid_input");
+}
+
+#[tokio::test]
+async fn test_rename_reseals_bound_secret() {
+    let server = TestServer::start().await;
+    let (_, private_pem) = server.register_master_key().await;
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db-password")
+        .json(&json!({ "secret": "hunter2" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Current envelopes are bound to their key, so the server needs the private key
+    let response = server
+        .request(Method::POST, "/v1/secrets/db-password/rename")
+        .json(&json!({ "new_key": "db-pass" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = server
+        .request(Method::POST, "/v1/secrets/db-password/rename")
+        .json(&json!({ "new_key": "db-pass", "private_key_pem": private_pem }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let secret: Value = server
+        .request(Method::GET, "/v1/secrets/db-pass")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(secret["envelope_version"], 3);
+    assert_eq!(decrypt_secret(&private_pem, &secret), b"hunter2");
+}
+
+#[tokio::test]
+async fn test_namespaced_keys_round_trip_percent_encoded() {
+    let server = TestServer::start().await;
+    let (_, private_pem) = server.register_master_key().await;
+
+    // The CLI sends the key as one percent-encoded path segment
+    for (key, path) in [
+        ("team/db/password", "/v1/secrets/team%2Fdb%2Fpassword"),
+        ("app/meta", "/v1/secrets/app%2Fmeta"),
+    ] {
+        let response = server
+            .request(Method::PUT, path)
+            .json(&json!({ "secret": key }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{key}");
+        let saved: Value = response.json().await.unwrap();
+        assert_eq!(saved["key"], key);
+
+        let secret: Value = server
+            .request(Method::GET, path)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(secret["key"], key);
+        assert_eq!(decrypt_secret(&private_pem, &secret), key.as_bytes());
+    }
+
+    let namespaces: Value = server
+        .request(Method::GET, "/v1/namespaces")
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(namespaces.to_string().contains("\"team\""), "{namespaces}");
+}
+
+#[tokio::test]
+async fn test_requests_without_token_are_rejected() {
+    let server = TestServer::start().await;
+
+    let response = server
+        .client
+        .get(format!("{}/v1/secrets", server.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .client
+        .get(format!("{}/healthz/live", server.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_shred_on_file_backed_store() {
+    let server = TestServer::start().await;
+    server.register_master_key().await;
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/leaked-token")
+        .json(&json!({ "secret": "oops" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = server
+        .request(
+            Method::DELETE,
+            "/v1/secrets/leaked-token?version=1&shred=true",
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = server
+        .request(Method::GET, "/v1/secrets/leaked-token?version=1")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::TestServer;
+use reqwest::{Method, StatusCode};
+use sealbox_server::{
+    config::SealboxConfig,
+    crypto::{
+        envelope::{EnvelopeVersion, SecretBinding},
+        master_key::{PublicMasterKey, generate_key_pair},
+    },
+};
+use serde_json::{Value, json};
+use std::str::FromStr;
+
+#[tokio::test]
+async fn test_oversized_secrets_are_rejected() {
+    let server = TestServer::start_with(SealboxConfig {
+        max_secret_bytes: 16,
+        ..SealboxConfig::default()
+    })
+    .await;
+    let (_, public_pem) = generate_key_pair().unwrap();
+    let registered: Value = server
+        .request(Method::POST, "/v1/master-key")
+        .json(&json!({ "public_key": public_pem }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db")
+        .json(&json!({ "secret": "x".repeat(16) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db")
+        .json(&json!({ "secret": "x".repeat(17) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "invalid_input");
+    assert!(
+        body["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("the maximum is 16 bytes"),
+        "{body}"
+    );
+
+    // Bodies far past the limit are refused before they are buffered
+    let response = server
+        .request(Method::PUT, "/v1/secrets/db")
+        .json(&json!({ "secret": "x".repeat(128 * 1024) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    // Client-encrypted values are held to the same limit
+    let public_key = PublicMasterKey::from_str(&public_pem).unwrap();
+    for (len, status) in [(16, StatusCode::OK), (17, StatusCode::BAD_REQUEST)] {
+        let binding = SecretBinding {
+            key: "sealed",
+            version: 1,
+        };
+        let (encrypted_data, encrypted_data_key) = EnvelopeVersion::V1
+            .seal(&public_key, "x".repeat(len).as_bytes(), binding)
+            .unwrap();
+        let response = server
+            .request(Method::PUT, "/v1/secrets/sealed/encrypted")
+            .json(&json!({
+                "encrypted_data": encrypted_data,
+                "encrypted_data_key": encrypted_data_key,
+                "master_key_id": registered["id"],
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{len} bytes");
+    }
+}
@@ -66,8 +66,13 @@ export class SealboxApi {
         errorBody = { error: response.statusText };
       }
 
+      // Current servers nest details in an object; older ones sent a string
+      const message =
+        typeof errorBody.error === "object"
+          ? errorBody.error?.message
+          : errorBody.error;
       throw new SealboxApiError(
-        errorBody.error || errorBody.message || `HTTP ${response.status}`,
+        message || errorBody.message || `HTTP ${response.status}`,
         response.status,
         errorBody,
       );
@@ -45,6 +45,7 @@ export interface CreateMasterKeyRequest {
 }
 
 export interface MasterKeysListResponse {
+  total: number;
   master_keys: MasterKey[];
 }
 
@@ -54,8 +55,15 @@ export interface CleanupExpiredResponse {
 }
 
 // Error response types
+export interface ApiErrorDetails {
+  code: string;
+  message: string;
+  request_id: string | null;
+  errors?: string[];
+}
+
 export interface ApiError {
-  error: string;
+  error: ApiErrorDetails | string;
   message?: string;
 }
 